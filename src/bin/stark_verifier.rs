@@ -0,0 +1,285 @@
+//! Command line front end of the plonky2 to halo2 pipeline.
+//!
+//! ```text
+//! stark-verifier setup           --common <file> --verifier-only <file> --params <file> --params-sha256 <hex> [--k <k>] [--public-inputs <mode>] --out <prefix>
+//! stark-verifier prove           --common <file> --verifier-only <file> --params <file> --params-sha256 <hex> [--k <k>] --pk <file> --proof <file> --artifact <file>
+//! stark-verifier verify          --common <file> --verifier-only <file> --params <file> --params-sha256 <hex> [--k <k>] --vk <file> --artifact <file>
+//! stark-verifier export-solidity --common <file> --verifier-only <file> --params <file> --params-sha256 <hex> [--k <k>] --vk <file> --out <prefix>
+//! stark-verifier evm-verify      --verifier-sol <file> --vk-sol <file> --artifact <file>
+//! stark-verifier row-budget      --common <file> --verifier-only <file> [--public-inputs <mode>]
+//! stark-verifier profile         --common <file> --verifier-only <file> [--public-inputs <mode>] [--json <file>] [--baseline <file>]
+//! ```
+//!
+//! Without `--k` `setup` uses the smallest degree the verifier circuit fits in, `row-budget` prints
+//! it. The other subcommands use the degree of the stored keys, which `--k` must match if given.
+//! `--public-inputs` is `instances` (the default), `packed` or `keccak256`, see `PublicInputsMode`.
+//! `setup` stores it with the keys, `row-budget` and `profile` measure the circuit in that mode.
+//! `profile` prints the rows and lookups of every verification stage, `--json` saves them and
+//! `--baseline` compares them to a profile saved earlier, e.g. for another inner `CircuitConfig`.
+//! `--common`, `--verifier-only` and `--proof` are plonky2's binary serializations of
+//! `CommonCircuitData`, `VerifierOnlyCircuitData` and `ProofWithPublicInputs`.
+//! Halo2 proofs are stored as a `SnarkArtifact`, JSON if the file ends in `.json`, binary otherwise.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use anyhow::{anyhow, bail, Result};
+use colored::Colorize;
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use log::{info, LevelFilter};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use semaphore_aggregation::plonky2_verifier::artifact::SnarkArtifact;
+use semaphore_aggregation::plonky2_verifier::bn245_poseidon::plonky2_config::Bn254PoseidonGoldilocksConfig;
+use semaphore_aggregation::plonky2_verifier::degree::{row_budget, select_degree};
+use semaphore_aggregation::plonky2_verifier::kzg_params::{load_params, ParamsFormat};
+use semaphore_aggregation::plonky2_verifier::profile::{cost_profile, CostProfile};
+use semaphore_aggregation::plonky2_verifier::types::{
+    common_data::CommonData, verification_key::VerificationKeyValues,
+};
+use semaphore_aggregation::plonky2_verifier::verifier_api::{
+    prove, run_evm_verifier, std_ops, verify_snark_proof,
+};
+use semaphore_aggregation::plonky2_verifier::verifier_circuit::{PublicInputsMode, Verifier};
+use semaphore_aggregation::plonky2_verifier::verifier_setup::{
+    read_halo2_vk, read_proving_key, render_solidity, VerifierSetup,
+};
+
+/// Config of the plonky2 proofs read by `std_ops`.
+type Config = Bn254PoseidonGoldilocksConfig;
+
+const USAGE: &str = "usage: stark-verifier <setup|prove|verify|export-solidity|evm-verify|row-budget|profile> [--<option> <value>]...";
+
+struct Args {
+    command: String,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = std::env::args().skip(1);
+        let command = args.next().ok_or_else(|| anyhow!(USAGE))?;
+        let mut options = HashMap::new();
+        while let Some(key) = args.next() {
+            let key = key
+                .strip_prefix("--")
+                .ok_or_else(|| anyhow!("unexpected argument {key}\n{USAGE}"))?
+                .to_string();
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("missing value for --{key}"))?;
+            options.insert(key, value);
+        }
+        Ok(Self { command, options })
+    }
+
+    fn get(&self, key: &str) -> Result<&str> {
+        self.options
+            .get(key)
+            .map(|value| value.as_str())
+            .ok_or_else(|| anyhow!("missing --{key}"))
+    }
+
+    fn public_inputs_mode(&self) -> Result<PublicInputsMode> {
+        match self.options.get("public-inputs").map(|mode| mode.as_str()) {
+            None | Some("instances") => Ok(PublicInputsMode::Instances),
+            Some("packed") => Ok(PublicInputsMode::Packed),
+            Some("keccak256") => Ok(PublicInputsMode::Keccak256),
+            Some(mode) => {
+                bail!("unknown public inputs mode {mode}, expected instances, packed or keccak256")
+            }
+        }
+    }
+}
+
+fn load_circuit(
+    args: &Args,
+) -> Result<(
+    CommonCircuitData<GoldilocksField, 2>,
+    VerificationKeyValues<Fr>,
+    CommonData<Fr>,
+)> {
+    let common = std_ops::load_common_circuit_data(args.get("common")?)?;
+    let verifier_only = std_ops::load_verifier_only_circuit_data(args.get("verifier-only")?)?;
+    let common_data = CommonData::try_from(common.clone())?;
+    Ok((
+        common,
+        VerificationKeyValues::from(verifier_only),
+        common_data,
+    ))
+}
+
+fn k_option(args: &Args) -> Result<Option<u32>> {
+    Ok(args
+        .options
+        .get("k")
+        .map(|k| k.parse::<u32>())
+        .transpose()?)
+}
+
+/// The degree of stored halo2 keys, which `--k` must match if given.
+fn stored_degree(args: &Args, vk: &VerifyingKey<G1Affine>) -> Result<u32> {
+    let k = vk.get_domain().k();
+    if let Some(option) = k_option(args)? {
+        if option != k {
+            bail!("--k {option} does not match the keys, which were generated for k = {k}");
+        }
+    }
+    Ok(k)
+}
+
+/// The trusted setup of `--params` for degree `k`.
+fn load_kzg_params(args: &Args, k: u32) -> Result<ParamsKZG<Bn256>> {
+    info!("{}", format!("Using k = {k}").blue().bold());
+    let params_path = args.get("params")?;
+    Ok(load_params(
+        params_path,
+        ParamsFormat::from_path(params_path),
+        k,
+        args.get("params-sha256")?,
+    )?)
+}
+
+fn setup(args: &Args) -> Result<()> {
+    let (_, vk, common_data) = load_circuit(args)?;
+    let out = args.get("out")?;
+    let circuit = Verifier::<Config>::from_common_data(vk, common_data)
+        .with_public_inputs_mode(args.public_inputs_mode()?);
+    let k = select_degree(&circuit, k_option(args)?)?;
+    let setup = VerifierSetup::<Config>::from_circuit(load_kzg_params(args, k)?, circuit)?;
+    setup.write(&mut BufWriter::new(File::create(format!("{out}.pk"))?))?;
+    setup.write_vk(&mut BufWriter::new(File::create(format!("{out}.vk"))?))?;
+    info!(
+        "{}",
+        format!("Keys written to {out}.pk and {out}.vk")
+            .green()
+            .bold()
+    );
+    Ok(())
+}
+
+fn prove_cmd(args: &Args) -> Result<()> {
+    let (common, vk, common_data) = load_circuit(args)?;
+    let proof = std_ops::load_plonky2_proof(args.get("proof")?, &common)?;
+    let (pk, circuit) = read_proving_key::<Config, _>(
+        &mut BufReader::new(File::open(args.get("pk")?)?),
+        &vk,
+        &common_data,
+    )?;
+    let params = load_kzg_params(args, stored_degree(args, pk.get_vk())?)?;
+    let setup = VerifierSetup::from_proving_key(params, pk, circuit)?;
+    let (snark_proof, instances) = prove(&setup, proof)?;
+    SnarkArtifact::new(setup.halo2_vk(), &setup.vk, &snark_proof, &instances)
+        .save(args.get("artifact")?)?;
+    Ok(())
+}
+
+fn verify(args: &Args) -> Result<()> {
+    let (_, vk, common_data) = load_circuit(args)?;
+    let (halo2_vk, _) = read_halo2_vk::<Config, _>(
+        &mut BufReader::new(File::open(args.get("vk")?)?),
+        &vk,
+        &common_data,
+    )?;
+    let params = load_kzg_params(args, stored_degree(args, &halo2_vk)?)?;
+    let artifact = SnarkArtifact::load(args.get("artifact")?, &halo2_vk, &vk)?;
+    verify_snark_proof(
+        &params,
+        &halo2_vk,
+        &artifact.proof_bytes()?,
+        &artifact.instances()?,
+    )?;
+    info!("{}", "SNARK proof is valid".green().bold());
+    Ok(())
+}
+
+fn export_solidity(args: &Args) -> Result<()> {
+    let (_, vk, common_data) = load_circuit(args)?;
+    let out = args.get("out")?;
+    let (halo2_vk, circuit) = read_halo2_vk::<Config, _>(
+        &mut BufReader::new(File::open(args.get("vk")?)?),
+        &vk,
+        &common_data,
+    )?;
+    let params = load_kzg_params(args, stored_degree(args, &halo2_vk)?)?;
+    let (verifier_solidity, vk_solidity) =
+        render_solidity(&params, &halo2_vk, circuit.instances().len())?;
+    std_ops::save_solidity(
+        std_ops::with_suffix(out, "_verifier.sol"),
+        &verifier_solidity,
+    )?;
+    std_ops::save_solidity(std_ops::with_suffix(out, "_vk.sol"), &vk_solidity)?;
+    Ok(())
+}
+
+fn evm_verify(args: &Args) -> Result<()> {
+    let verifier_solidity = std_ops::load_solidity(args.get("verifier-sol")?)?;
+    let vk_solidity = std_ops::load_solidity(args.get("vk-sol")?)?;
+    // the EVM verifier performs its own checks, the bundle is only decoded here
+    let artifact = SnarkArtifact::load_unchecked(args.get("artifact")?)?;
+    let gas_cost = run_evm_verifier(
+        &verifier_solidity,
+        &vk_solidity,
+        &artifact.proof_bytes()?,
+        &artifact.instances()?,
+    )?;
+    info!("{}", format!("Gas cost: {}", gas_cost).yellow().bold());
+    Ok(())
+}
+
+fn row_budget_cmd(args: &Args) -> Result<()> {
+    let (_, vk, common_data) = load_circuit(args)?;
+    let budget = row_budget(
+        &Verifier::<Config>::from_common_data(vk, common_data)
+            .with_public_inputs_mode(args.public_inputs_mode()?),
+    )?;
+    for (region, rows) in budget.regions.iter() {
+        println!("{region:<40} {rows:>10}");
+    }
+    println!("{:<40} {:>10}", "used rows", budget.used_rows);
+    println!("{:<40} {:>10}", "blinding rows", budget.blinding_rows);
+    println!("{:<40} {:>10}", "minimum k", budget.minimum_k);
+    Ok(())
+}
+
+fn profile_cmd(args: &Args) -> Result<()> {
+    let (_, vk, common_data) = load_circuit(args)?;
+    let profile = cost_profile(
+        &Verifier::<Config>::from_common_data(vk, common_data)
+            .with_public_inputs_mode(args.public_inputs_mode()?),
+    )?;
+    match args.options.get("baseline") {
+        Some(baseline) => {
+            let baseline = CostProfile::from_json(&std::fs::read_to_string(baseline)?)?;
+            print!("{}", profile.compare(&baseline));
+        }
+        None => print!("{}", profile.to_table()),
+    }
+    if let Some(json) = args.options.get("json") {
+        std::fs::write(json, profile.to_json()?)?;
+        info!("{}", format!("Profile written to {json}").green().bold());
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let mut log_builder = env_logger::Builder::from_default_env();
+    log_builder.format_timestamp(None);
+    log_builder.filter_level(LevelFilter::Info);
+    let _ = log_builder.try_init();
+
+    let args = Args::parse()?;
+    match args.command.as_str() {
+        "setup" => setup(&args),
+        "prove" => prove_cmd(&args),
+        "verify" => verify(&args),
+        "export-solidity" => export_solidity(&args),
+        "evm-verify" => evm_verify(&args),
+        "row-budget" => row_budget_cmd(&args),
+        "profile" => profile_cmd(&args),
+        command => bail!("unknown command {command}\n{USAGE}"),
+    }
+}
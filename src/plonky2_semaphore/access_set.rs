@@ -2,6 +2,8 @@ use std::time::Instant;
 
 use anyhow::Result;
 use colored::Colorize;
+use halo2_proofs::halo2curves::bn256::Bn256;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
 use plonky2::field::types::Field;
 use plonky2::fri::reduction_strategies::FriReductionStrategy;
 use plonky2::fri::FriConfig;
@@ -29,6 +31,7 @@ impl AccessSet {
         &self,
         signal: Signal,
         verifier_data: &VerifierCircuitData<F, C, 2>,
+        kzg_param: &ParamsKZG<Bn256>,
     ) -> Result<()> {
         let public_inputs: Vec<F> = self
             .0
@@ -48,14 +51,15 @@ impl AccessSet {
         let wrapper_circuit = WrapperCircuit::new(standard_stark_verifier_config(), &verifier_data);
         let wrapped_proof = wrapper_circuit.prove(&proof).unwrap();
         verify_inside_snark(
-            20,
+            None,
             (
                 wrapped_proof,
                 wrapper_circuit.data.verifier_only.clone(),
                 wrapper_circuit.data.common.clone(),
             ),
+            kzg_param,
             None
-        );
+        )?;
         Ok(())
     }
 
@@ -267,6 +267,8 @@ mod tests {
         hash::{merkle_tree::MerkleTree, poseidon::PoseidonHash},
         plonk::{config::Hasher, proof::ProofWithPublicInputs},
     };
+    use halo2_proofs::halo2curves::bn256::Bn256;
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
     use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
     use crate::{
@@ -333,15 +335,17 @@ mod tests {
         let wrapper_circuit =
             WrapperCircuit::new(standard_stark_verifier_config(), &verifier_circuit_data);
         let wrapped_proof = wrapper_circuit.prove(&proof).unwrap();
+        let kzg_param = ParamsKZG::<Bn256>::setup(20, rand::thread_rng());
         verify_inside_snark(
-            20,
+            None,
             (
                 wrapped_proof,
                 wrapper_circuit.data.verifier_only.clone(),
                 wrapper_circuit.data.common.clone(),
             ),
+            &kzg_param,
             None
-        );
+        )?;
 
         Ok(())
     }
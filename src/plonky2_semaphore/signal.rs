@@ -17,6 +17,8 @@ pub struct Signal {
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use halo2_proofs::halo2curves::bn256::Bn256;
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
     use plonky2::field::types::{Field, Sample};
     use plonky2::hash::merkle_tree::MerkleTree;
     use plonky2::hash::poseidon::PoseidonHash;
@@ -43,6 +45,7 @@ mod tests {
         let topic = F::rand_array();
 
         let (signal, vd) = access_set.make_signal(private_keys[i], topic, i)?;
-        access_set.verify_signal(signal, &vd)
+        let kzg_param = ParamsKZG::<Bn256>::setup(20, rand::thread_rng());
+        access_set.verify_signal(signal, &vd, &kzg_param)
     }
 }
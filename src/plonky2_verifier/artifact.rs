@@ -0,0 +1,414 @@
+//! Versioned bundle of a halo2 proof and everything needed to check it later.
+//!
+//! The JSON encoding is meant for humans and backends, the binary one is compact:
+//! `magic | format_version: u32 | crate_version | circuit_digest: [u64; 4] | degree: u32 |
+//! batch_open_scheme | vk_repr: [u8; 32] | proof | instances`, integers little endian, strings and
+//! byte vectors prefixed by their `u32` length, instances as `u32` count then 32 byte `Fr` reprs.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use halo2_proofs::halo2curves::bn256::{Fr, G1Affine};
+use halo2_proofs::halo2curves::ff::PrimeField;
+use halo2_proofs::plonk::VerifyingKey;
+use plonky2::field::types::PrimeField64;
+use serde::{Deserialize, Serialize};
+
+use super::error::VerifierError;
+use super::types::verification_key::VerificationKeyValues;
+
+pub const ARTIFACT_FORMAT_VERSION: u32 = 1;
+const ARTIFACT_MAGIC: &[u8; 4] = b"P2H2";
+
+/// SHPLONK, the only batch opening scheme the prover uses.
+pub const BATCH_OPEN_SCHEME_BDFG21: &str = "bdfg21";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnarkArtifact {
+    pub format_version: u32,
+    /// Version of this crate that produced the proof.
+    pub crate_version: String,
+    /// Digest of the inner plonky2 circuit.
+    pub circuit_digest: [u64; 4],
+    /// halo2 degree `k`.
+    pub degree: u32,
+    pub batch_open_scheme: String,
+    /// Hex encoded `VerifyingKey::transcript_repr` of the halo2 verifying key.
+    pub vk_repr: String,
+    /// Hex encoded halo2 proof.
+    pub proof: String,
+    /// Hex encoded little endian `Fr` reprs.
+    pub instances: Vec<String>,
+}
+
+impl SnarkArtifact {
+    pub fn new(
+        vk: &VerifyingKey<G1Affine>,
+        plonky2_vk: &VerificationKeyValues<Fr>,
+        proof: &[u8],
+        instances: &[Fr],
+    ) -> Self {
+        Self {
+            format_version: ARTIFACT_FORMAT_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            circuit_digest: circuit_digest(plonky2_vk),
+            degree: vk.get_domain().k(),
+            batch_open_scheme: BATCH_OPEN_SCHEME_BDFG21.to_string(),
+            vk_repr: hex::encode(vk.transcript_repr().to_repr()),
+            proof: hex::encode(proof),
+            instances: instances.iter().map(|e| hex::encode(e.to_repr())).collect(),
+        }
+    }
+
+    pub fn proof_bytes(&self) -> Result<Vec<u8>, VerifierError> {
+        hex::decode(&self.proof).map_err(|e| invalid(format!("proof: {e}")))
+    }
+
+    pub fn instances(&self) -> Result<Vec<Fr>, VerifierError> {
+        self.instances
+            .iter()
+            .map(|instance| {
+                let bytes = hex::decode(instance).map_err(|e| invalid(format!("instance: {e}")))?;
+                fr_from_bytes(&bytes)
+            })
+            .collect()
+    }
+
+    /// Checks that the bundle was produced for `vk` and the plonky2 circuit of `plonky2_vk` with a
+    /// format this crate understands.
+    pub fn validate(
+        &self,
+        vk: &VerifyingKey<G1Affine>,
+        plonky2_vk: &VerificationKeyValues<Fr>,
+    ) -> Result<(), VerifierError> {
+        if self.format_version != ARTIFACT_FORMAT_VERSION {
+            return Err(invalid(format!(
+                "unsupported format version {}",
+                self.format_version
+            )));
+        }
+        if self.batch_open_scheme != BATCH_OPEN_SCHEME_BDFG21 {
+            return Err(invalid(format!(
+                "unsupported batch open scheme {}",
+                self.batch_open_scheme
+            )));
+        }
+        if self.degree != vk.get_domain().k() {
+            return Err(invalid(format!(
+                "degree {} does not match the verifying key degree {}",
+                self.degree,
+                vk.get_domain().k()
+            )));
+        }
+        if self.vk_repr != hex::encode(vk.transcript_repr().to_repr()) {
+            return Err(invalid("produced for another verifying key".to_string()));
+        }
+        if self.circuit_digest != circuit_digest(plonky2_vk) {
+            return Err(invalid("produced for another plonky2 circuit".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Result<String, VerifierError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, VerifierError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn write_binary<W: Write>(&self, writer: &mut W) -> Result<(), VerifierError> {
+        writer.write_all(ARTIFACT_MAGIC)?;
+        writer.write_all(&self.format_version.to_le_bytes())?;
+        write_bytes(writer, self.crate_version.as_bytes())?;
+        for e in self.circuit_digest.iter() {
+            writer.write_all(&e.to_le_bytes())?;
+        }
+        writer.write_all(&self.degree.to_le_bytes())?;
+        write_bytes(writer, self.batch_open_scheme.as_bytes())?;
+        write_bytes(
+            writer,
+            &hex::decode(&self.vk_repr).map_err(|e| invalid(e.to_string()))?,
+        )?;
+        write_bytes(writer, &self.proof_bytes()?)?;
+        writer.write_all(&(self.instances.len() as u32).to_le_bytes())?;
+        for instance in self.instances()? {
+            writer.write_all(&instance.to_repr())?;
+        }
+        Ok(())
+    }
+
+    pub fn read_binary<R: Read>(reader: &mut R) -> Result<Self, VerifierError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != ARTIFACT_MAGIC {
+            return Err(invalid("missing artifact magic".to_string()));
+        }
+        let format_version = read_u32(reader)?;
+        let crate_version = read_string(reader)?;
+        let mut circuit_digest = [0u64; 4];
+        for e in circuit_digest.iter_mut() {
+            let mut buf = [0u8; 8];
+            reader.read_exact(&mut buf)?;
+            *e = u64::from_le_bytes(buf);
+        }
+        let degree = read_u32(reader)?;
+        let batch_open_scheme = read_string(reader)?;
+        let vk_repr = hex::encode(read_bytes(reader)?);
+        let proof = hex::encode(read_bytes(reader)?);
+        let num_instances = read_u32(reader)?;
+        let instances = (0..num_instances)
+            .map(|_| {
+                let mut buf = [0u8; 32];
+                reader.read_exact(&mut buf)?;
+                Ok(hex::encode(buf))
+            })
+            .collect::<Result<Vec<_>, VerifierError>>()?;
+        Ok(Self {
+            format_version,
+            crate_version,
+            circuit_digest,
+            degree,
+            batch_open_scheme,
+            vk_repr,
+            proof,
+            instances,
+        })
+    }
+
+    /// Writes the bundle to `path`, as JSON if the extension is `json` and binary otherwise.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), VerifierError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if is_json(path) {
+            fs::write(path, self.to_json()?)?;
+        } else {
+            let mut bytes = vec![];
+            self.write_binary(&mut bytes)?;
+            fs::write(path, bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a bundle written by `save` without validating it.
+    pub fn load_unchecked(path: impl AsRef<Path>) -> Result<Self, VerifierError> {
+        let path = path.as_ref();
+        if is_json(path) {
+            Self::from_json(&fs::read_to_string(path)?)
+        } else {
+            Self::read_binary(&mut fs::read(path)?.as_slice())
+        }
+    }
+
+    /// Reads a bundle written by `save` and validates it against `vk` and `plonky2_vk`.
+    pub fn load(
+        path: impl AsRef<Path>,
+        vk: &VerifyingKey<G1Affine>,
+        plonky2_vk: &VerificationKeyValues<Fr>,
+    ) -> Result<Self, VerifierError> {
+        let artifact = Self::load_unchecked(path)?;
+        artifact.validate(vk, plonky2_vk)?;
+        Ok(artifact)
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("json")
+}
+
+fn circuit_digest(plonky2_vk: &VerificationKeyValues<Fr>) -> [u64; 4] {
+    plonky2_vk
+        .circuit_digest
+        .elements
+        .map(|e| e.to_canonical_u64())
+}
+
+fn invalid(reason: String) -> VerifierError {
+    VerifierError::InvalidArtifact(reason)
+}
+
+fn fr_from_bytes(bytes: &[u8]) -> Result<Fr, VerifierError> {
+    let repr: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| invalid(format!("instance has {} bytes", bytes.len())))?;
+    Option::from(Fr::from_repr(repr)).ok_or_else(|| invalid("non canonical instance".to_string()))
+}
+
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<(), VerifierError> {
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, VerifierError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Reads a length prefixed byte vector, growing the buffer with the bytes actually read so that a
+/// corrupted length doesn't allocate up to 4 GiB.
+fn read_bytes<R: Read>(reader: &mut R) -> Result<Vec<u8>, VerifierError> {
+    let len = read_u32(reader)?;
+    let mut bytes = vec![];
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len as usize {
+        return Err(invalid(format!(
+            "expected {len} bytes, got {}",
+            bytes.len()
+        )));
+    }
+    Ok(bytes)
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, VerifierError> {
+    String::from_utf8(read_bytes(reader)?).map_err(|e| invalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::{Bn256, Fr},
+        plonk::{keygen_pk, keygen_vk, Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+        poly::kzg::commitment::ParamsKZG,
+    };
+    use plonky2::{
+        field::goldilocks_field::GoldilocksField, plonk::circuit_builder::CircuitBuilder,
+    };
+
+    use super::{SnarkArtifact, ARTIFACT_FORMAT_VERSION, BATCH_OPEN_SCHEME_BDFG21};
+    use crate::plonky2_verifier::{
+        bn245_poseidon::plonky2_config::{
+            standard_stark_verifier_config, Bn254PoseidonGoldilocksConfig,
+        },
+        chip::native_chip::test_utils::create_proof_checked,
+        error::VerifierError,
+        types::verification_key::VerificationKeyValues,
+        verifier_api::verify_snark_proof,
+    };
+
+    /// Exposes its witness as its only instance.
+    #[derive(Clone, Default)]
+    struct InstanceCircuit(Value<Fr>);
+
+    impl Circuit<Fr> for InstanceCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(advice);
+            meta.enable_equality(instance);
+            (advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let cell = layouter.assign_region(
+                || "witness",
+                |mut region| region.assign_advice(|| "witness", advice, 0, || self.0),
+            )?;
+            layouter.constrain_instance(cell.cell(), instance, 0)
+        }
+    }
+
+    /// Verification key of a plonky2 circuit with `num_public_inputs` public inputs.
+    fn plonky2_vk(num_public_inputs: usize) -> VerificationKeyValues<Fr> {
+        let mut builder =
+            CircuitBuilder::<GoldilocksField, 2>::new(standard_stark_verifier_config());
+        for _ in 0..num_public_inputs {
+            builder.add_virtual_public_input();
+        }
+        let data = builder.build::<Bn254PoseidonGoldilocksConfig>();
+        VerificationKeyValues::from(data.verifier_only)
+    }
+
+    #[test]
+    fn test_artifact_encodings() {
+        let artifact = SnarkArtifact {
+            format_version: ARTIFACT_FORMAT_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            circuit_digest: [1, 2, 3, u64::MAX],
+            degree: 19,
+            batch_open_scheme: BATCH_OPEN_SCHEME_BDFG21.to_string(),
+            vk_repr: hex::encode([7u8; 32]),
+            proof: hex::encode([0xab; 100]),
+            instances: [Fr::from(42), -Fr::from(1)]
+                .iter()
+                .map(|e| hex::encode(e.to_bytes()))
+                .collect(),
+        };
+        let json = artifact.to_json().unwrap();
+        assert_eq!(SnarkArtifact::from_json(&json).unwrap(), artifact);
+
+        let mut bytes = vec![];
+        artifact.write_binary(&mut bytes).unwrap();
+        let decoded = SnarkArtifact::read_binary(&mut bytes.as_slice()).unwrap();
+        assert_eq!(decoded, artifact);
+        assert_eq!(
+            decoded.instances().unwrap(),
+            vec![Fr::from(42), -Fr::from(1)]
+        );
+    }
+
+    #[test]
+    fn test_artifact_round_trip() {
+        let mut rng = rand::thread_rng();
+        let params = ParamsKZG::<Bn256>::setup(4, &mut rng);
+        let circuit = InstanceCircuit(Value::known(Fr::from(42)));
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).unwrap();
+        let instances = [Fr::from(42)];
+        let proof = create_proof_checked(&params, &pk, circuit, &instances, &mut rng).unwrap();
+        let plonky2_vk = plonky2_vk(1);
+        let artifact = SnarkArtifact::new(pk.get_vk(), &plonky2_vk, &proof, &instances);
+
+        let mut bytes = vec![];
+        artifact.write_binary(&mut bytes).unwrap();
+        let decoded = SnarkArtifact::read_binary(&mut bytes.as_slice()).unwrap();
+        assert_eq!(decoded, artifact);
+        decoded.validate(pk.get_vk(), &plonky2_vk).unwrap();
+        verify_snark_proof(
+            &params,
+            pk.get_vk(),
+            &decoded.proof_bytes().unwrap(),
+            &decoded.instances().unwrap(),
+        )
+        .unwrap();
+
+        // produced for another plonky2 circuit
+        assert!(matches!(
+            decoded.validate(pk.get_vk(), &plonky2_vk(2)),
+            Err(VerifierError::InvalidArtifact(_))
+        ));
+        // produced for another halo2 circuit
+        let other_params = ParamsKZG::<Bn256>::setup(5, &mut rng);
+        let other_vk = keygen_vk(&other_params, &InstanceCircuit::default()).unwrap();
+        assert!(matches!(
+            decoded.validate(&other_vk, &plonky2_vk),
+            Err(VerifierError::InvalidArtifact(_))
+        ));
+
+        // a corrupted length of `vk_repr` is rejected without allocating it
+        let vk_repr_offset =
+            4 + 4 + 4 + artifact.crate_version.len() + 32 + 4 + 4 + BATCH_OPEN_SCHEME_BDFG21.len();
+        bytes[vk_repr_offset..vk_repr_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            SnarkArtifact::read_binary(&mut bytes.as_slice()),
+            Err(VerifierError::InvalidArtifact(_))
+        ));
+    }
+}
@@ -0,0 +1,157 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::*,
+};
+use itertools::Itertools;
+use plonky2::{field::goldilocks_field::GoldilocksField, plonk::config::GenericConfig};
+
+use super::{
+    bn245_poseidon::plonky2_config::Bn254PoseidonGoldilocksConfig,
+    chip::{
+        goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
+        hasher_chip::AlgebraicHasherChip,
+        native_chip::all_chip::OptionalChips,
+    },
+    context::RegionCtx,
+    error::VerifierError,
+    profile::CostProfile,
+    types::{common_data::CommonData, verification_key::VerificationKeyValues},
+    verifier_circuit::{PublicInputsMode, Verifier},
+};
+
+/// Verifier circuit of several plonky2 proofs made with the config `C`, possibly of different
+/// inner circuits. The proofs share the range table, the constants and one halo2 proof, and the
+/// public inputs of every proof are exposed one after the other, in the order of `verifiers`.
+#[derive(Clone)]
+pub struct BatchVerifier<C = Bn254PoseidonGoldilocksConfig> {
+    verifiers: Vec<Verifier<C>>,
+}
+
+impl<C: GenericConfig<2, F = GoldilocksField>> BatchVerifier<C>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    pub fn new(verifiers: Vec<Verifier<C>>) -> Result<Self, VerifierError> {
+        if verifiers.is_empty() {
+            return Err(VerifierError::ShapeMismatch(
+                "a batch needs at least one proof".to_string(),
+            ));
+        }
+        Ok(Self { verifiers })
+    }
+
+    /// Circuit without witnesses with one slot per verification key and `CommonData`, e.g. one
+    /// per circuit of a fixed set of plonky2 circuits with different gates and FRI parameters.
+    /// Enough for keygen.
+    pub fn from_common_data(
+        slots: Vec<(VerificationKeyValues<Fr>, CommonData<Fr>)>,
+    ) -> Result<Self, VerifierError> {
+        Self::new(
+            slots
+                .into_iter()
+                .map(|(vk, common_data)| Verifier::from_common_data(vk, common_data))
+                .collect(),
+        )
+    }
+
+    /// Exposes the public inputs of every proof as `public_inputs_mode` says.
+    pub fn with_public_inputs_mode(self, public_inputs_mode: PublicInputsMode) -> Self {
+        Self {
+            verifiers: self
+                .verifiers
+                .into_iter()
+                .map(|verifier| verifier.with_public_inputs_mode(public_inputs_mode))
+                .collect(),
+        }
+    }
+
+    pub fn verifiers(&self) -> &[Verifier<C>] {
+        &self.verifiers
+    }
+
+    /// The instances of all the proofs, as exposed by the circuit.
+    pub fn instances(&self) -> Vec<Fr> {
+        self.verifiers
+            .iter()
+            .flat_map(|verifier| verifier.instances())
+            .collect()
+    }
+
+    /// Synthesizes the circuit and returns the cost of every stage of the verification region,
+    /// the stages of the `i`-th proof being nested in `proof_{i}`.
+    pub fn synthesize_with_profile(
+        &self,
+        config: GoldilocksChipConfig<Fr>,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<CostProfile, Error> {
+        let goldilocks_chip = GoldilocksChip::new(&config);
+        goldilocks_chip.load_table(&mut layouter)?;
+        let mut profile = CostProfile::default();
+        let public_inputs = layouter.assign_region(
+            || "Verify proofs",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                let mut public_inputs = vec![];
+                for (i, verifier) in self.verifiers.iter().enumerate() {
+                    public_inputs.extend(ctx.stage(&format!("proof_{i}"), |ctx| {
+                        verifier.verify_in_region(&config, ctx)
+                    })?);
+                }
+                profile = ctx.take_profile();
+                Ok(public_inputs)
+            },
+        )?;
+        for (row, public_input) in (0..self.instances().len()).zip_eq(public_inputs) {
+            goldilocks_chip.arithmetic_chip().expose_public(
+                layouter.namespace(|| ""),
+                public_input,
+                row,
+            )?;
+        }
+        Ok(profile)
+    }
+}
+
+impl<C: GenericConfig<2, F = GoldilocksField>> Circuit<Fr> for BatchVerifier<C>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    type Config = GoldilocksChipConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = OptionalChips;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            verifiers: self
+                .verifiers
+                .iter()
+                .map(|verifier| verifier.without_witnesses())
+                .collect(),
+        }
+    }
+
+    fn params(&self) -> Self::Params {
+        self.verifiers
+            .iter()
+            .fold(OptionalChips::NONE, |chips, verifier| {
+                chips.union(verifier.required_chips())
+            })
+    }
+
+    fn configure_with_params(
+        meta: &mut ConstraintSystem<Fr>,
+        params: Self::Params,
+    ) -> Self::Config {
+        Verifier::<C>::configure_with_params(meta, params)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Verifier::<C>::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        self.synthesize_with_profile(config, layouter)?;
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 pub mod constants;
 pub mod native;
 pub mod plonky2_config;
+pub mod transcript;
 pub mod value;
@@ -10,7 +10,10 @@ use crate::plonky2_verifier::chip::native_chip::{
 };
 
 use super::constants::{
-    MDS_MATRIX_FR, ROUND_CONSTANTS_FR, R_F_BN254_POSEIDON, R_P_BN254_POSEIDON, T_BN254_POSEIDON,
+    MDS_MATRIX_FR, POSEIDON2_EXTERNAL_CONSTANTS_FR, POSEIDON2_EXTERNAL_MATRIX,
+    POSEIDON2_INTERNAL_CONSTANTS_FR, POSEIDON2_INTERNAL_DIAG_M_1_FR, ROUND_CONSTANTS_FR,
+    R_F_BN254_POSEIDON, R_F_BN254_POSEIDON2, R_P_BN254_POSEIDON, R_P_BN254_POSEIDON2,
+    T_BN254_POSEIDON, T_BN254_POSEIDON2,
 };
 
 fn constant_layer(state: &mut [Fr; T_BN254_POSEIDON], counter: &mut usize) {
@@ -59,6 +62,47 @@ pub fn permute_bn254_poseidon_native(state: &mut [Fr; T_BN254_POSEIDON]) {
     }
 }
 
+fn poseidon2_external_layer(state: &mut [Fr; T_BN254_POSEIDON2]) {
+    let mut new_state = [Fr::from(0); T_BN254_POSEIDON2];
+    for i in 0..T_BN254_POSEIDON2 {
+        for j in 0..T_BN254_POSEIDON2 {
+            new_state[i] += state[j] * Fr::from(POSEIDON2_EXTERNAL_MATRIX[i][j]);
+        }
+    }
+    *state = new_state
+}
+
+fn poseidon2_internal_layer(state: &mut [Fr; T_BN254_POSEIDON2]) {
+    let sum = state.iter().fold(Fr::from(0), |acc, x| acc + x);
+    for i in 0..T_BN254_POSEIDON2 {
+        state[i] = sum + state[i] * POSEIDON2_INTERNAL_DIAG_M_1_FR[i];
+    }
+}
+
+fn poseidon2_external_round(state: &mut [Fr; T_BN254_POSEIDON2], round: usize) {
+    for i in 0..T_BN254_POSEIDON2 {
+        state[i] = (state[i] + POSEIDON2_EXTERNAL_CONSTANTS_FR[round][i]).pow(&[5]);
+    }
+    poseidon2_external_layer(state);
+}
+
+/// Poseidon2 over BN254 with `t = 4`: the external linear layer, `R_F / 2` external rounds,
+/// `R_P` internal rounds applying the S-box to the first element only, then `R_F / 2` external
+/// rounds.
+pub fn permute_bn254_poseidon2_native(state: &mut [Fr; T_BN254_POSEIDON2]) {
+    poseidon2_external_layer(state);
+    for round in 0..R_F_BN254_POSEIDON2 / 2 {
+        poseidon2_external_round(state, round);
+    }
+    for round in 0..R_P_BN254_POSEIDON2 {
+        state[0] = (state[0] + POSEIDON2_INTERNAL_CONSTANTS_FR[round]).pow(&[5]);
+        poseidon2_internal_layer(state);
+    }
+    for round in R_F_BN254_POSEIDON2 / 2..R_F_BN254_POSEIDON2 {
+        poseidon2_external_round(state, round);
+    }
+}
+
 pub fn encode_fe(x: [GoldilocksField; 3]) -> Fr {
     let acc = x.iter().enumerate().fold(Fr::from(0u64), |acc, (i, x)| {
         acc + Fr::from(x.to_canonical_u64()) * Fr::from(GOLDILOCKS_MODULUS).pow(&[i as u64])
@@ -75,3 +119,51 @@ pub fn decode_fe(x: Fr) -> [GoldilocksField; 3] {
         .to_vec();
     decomposed.try_into().unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use num_bigint::BigUint;
+
+    use crate::plonky2_verifier::bn245_poseidon::value::bg_to_fe;
+
+    use super::{permute_bn254_poseidon2_native, permute_bn254_poseidon_native};
+
+    fn hex_to_fr(x: &str) -> Fr {
+        bg_to_fe::<Fr>(&BigUint::parse_bytes(x[2..].as_bytes(), 16).unwrap())
+    }
+
+    /// Test vector of `poseidonperm_x5_254_5.sage` of the reference implementation of Poseidon
+    /// (ePrint 2019/458), whose round constants and MDS matrix are also those of the `poseidon`
+    /// crate used by snark-verifier.
+    #[test]
+    fn test_poseidon_known_answer() {
+        let mut state = [0u64, 1, 2, 3, 4].map(Fr::from);
+        permute_bn254_poseidon_native(&mut state);
+        let expected = [
+            "0x299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465",
+            "0x1148aaef609aa338b27dafd89bb98862d8bb2b429aceac47d86206154ffe053d",
+            "0x24febb87fed7462e23f6665ff9a0111f4044c38ee1672c1ac6b0637d34f24907",
+            "0x0eb08f6d809668a981c186beaf6110060707059576406b248e5d9cf6e78b3d3e",
+            "0x07748bc6877c9b82c8b98666ee9d0626ec7f5be4205f79ee8528ef1c4a376fc7",
+        ]
+        .map(hex_to_fr);
+        assert_eq!(state, expected);
+    }
+
+    /// Test vector of barretenberg's `poseidon2.test.cpp`, which hashes with the same
+    /// Poseidon2 instance.
+    #[test]
+    fn test_poseidon2_known_answer() {
+        let mut state = [0u64, 1, 2, 3].map(Fr::from);
+        permute_bn254_poseidon2_native(&mut state);
+        let expected = [
+            "0x01bd538c2ee014ed5141b29e9ae240bf8db3fe5b9a38629a9647cf8d76c01737",
+            "0x239b62e7db98aa3a2a8f6a0d2fa1709e7a35959aa6c7034814d9daa90cbac662",
+            "0x04cbb44c61d928ed06808456bf758cbf0c18d1e15a7b6dbc8245fa7515d5e3cb",
+            "0x2e11c5cff2a22c64d01304b778d78f6998eff1ab73163a35603f54794c30847a",
+        ]
+        .map(hex_to_fr);
+        assert_eq!(state, expected);
+    }
+}
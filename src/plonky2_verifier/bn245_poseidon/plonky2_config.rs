@@ -17,8 +17,8 @@ use plonky2::{
 use serde::{Deserialize, Serialize};
 
 use super::{
-    constants::T_BN254_POSEIDON,
-    native::{decode_fe, encode_fe, permute_bn254_poseidon_native},
+    constants::{T_BN254_POSEIDON, T_BN254_POSEIDON2},
+    native::{decode_fe, encode_fe, permute_bn254_poseidon2_native, permute_bn254_poseidon_native},
 };
 
 #[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
@@ -112,6 +112,95 @@ impl GenericConfig<2> for Bn254PoseidonGoldilocksConfig {
     type InnerHasher = PoseidonHash;
 }
 
+/// `Bn254PoseidonPermutation` with Poseidon2 over BN254: the 12 Goldilocks elements of the state
+/// are packed, three per `Fr`, into the `t = 4` state of the permutation.
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub struct Bn254Poseidon2Permutation {
+    state: [GoldilocksField; SPONGE_WIDTH],
+}
+
+impl Bn254Poseidon2Permutation {
+    pub fn new(state: [GoldilocksField; SPONGE_WIDTH]) -> Self {
+        Self { state }
+    }
+}
+
+impl AsRef<[GoldilocksField]> for Bn254Poseidon2Permutation {
+    fn as_ref(&self) -> &[GoldilocksField] {
+        &self.state
+    }
+}
+
+impl PlonkyPermutation<GoldilocksField> for Bn254Poseidon2Permutation {
+    const RATE: usize = SPONGE_RATE;
+    const WIDTH: usize = SPONGE_WIDTH;
+
+    fn permute(&mut self) {
+        let encoded_state = self
+            .state
+            .chunks(3)
+            .map(|x| encode_fe(x.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        let mut state: [Fr; T_BN254_POSEIDON2] = encoded_state.try_into().unwrap();
+        permute_bn254_poseidon2_native(&mut state);
+        let decoded_state = state.iter().flat_map(|x| decode_fe(*x)).collect::<Vec<_>>();
+        self.state = decoded_state.try_into().unwrap();
+    }
+
+    fn new<I: IntoIterator<Item = GoldilocksField>>(elts: I) -> Self {
+        let mut perm = Self {
+            state: [GoldilocksField::default(); SPONGE_WIDTH],
+        };
+        perm.set_from_iter(elts, 0);
+        perm
+    }
+
+    fn set_elt(&mut self, elt: GoldilocksField, idx: usize) {
+        self.state[idx] = elt;
+    }
+
+    fn set_from_slice(&mut self, elts: &[GoldilocksField], start_idx: usize) {
+        let begin = start_idx;
+        let end = start_idx + elts.len();
+        self.state[begin..end].copy_from_slice(elts);
+    }
+
+    fn set_from_iter<I: IntoIterator<Item = GoldilocksField>>(&mut self, elts: I, start_idx: usize) {
+        for (s, e) in self.state[start_idx..].iter_mut().zip(elts) {
+            *s = e;
+        }
+    }
+
+    fn squeeze(&self) -> &[GoldilocksField] {
+        &self.state[..Self::RATE]
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Bn254Poseidon2Hash;
+impl Hasher<GoldilocksField> for Bn254Poseidon2Hash {
+    const HASH_SIZE: usize = 4 * 8;
+    type Hash = HashOut<GoldilocksField>;
+    type Permutation = Bn254Poseidon2Permutation;
+
+    fn hash_no_pad(input: &[GoldilocksField]) -> Self::Hash {
+        hash_n_to_hash_no_pad::<GoldilocksField, Self::Permutation>(input)
+    }
+    fn two_to_one(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        compress::<GoldilocksField, Self::Permutation>(left, right)
+    }
+}
+
+/// `Bn254PoseidonGoldilocksConfig` with Poseidon2, which takes fewer rows and cells to verify.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Bn254Poseidon2GoldilocksConfig;
+impl GenericConfig<2> for Bn254Poseidon2GoldilocksConfig {
+    type F = GoldilocksField;
+    type FE = QuadraticExtension<Self::F>;
+    type Hasher = Bn254Poseidon2Hash;
+    type InnerHasher = PoseidonHash;
+}
+
 // If you use recursive proof in the plonky2's circuit, use this config for the inner circuit.
 pub fn standard_inner_stark_verifier_config() -> CircuitConfig {
     CircuitConfig {
@@ -140,3 +229,11 @@ pub fn standard_stark_verifier_config() -> CircuitConfig {
         ..inner_config
     }
 }
+
+// Like `standard_stark_verifier_config`, but folding by 16 in every FRI round, which shortens the
+// proof and the in-circuit verification at the cost of a slower plonky2 prover.
+pub fn high_arity_stark_verifier_config() -> CircuitConfig {
+    let mut config = standard_stark_verifier_config();
+    config.fri_config.reduction_strategy = FriReductionStrategy::ConstantArityBits(4, 5);
+    config
+}
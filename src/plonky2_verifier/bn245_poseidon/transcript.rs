@@ -0,0 +1,253 @@
+use std::io::{self, Read, Write};
+
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    halo2curves::{
+        bn256::{Fr, G1Affine},
+        ff::{Field, PrimeField},
+        group::GroupEncoding,
+    },
+    transcript::{
+        EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
+        TranscriptWriterBuffer,
+    },
+};
+use halo2wrong_maingate::{big_to_fe, fe_to_big};
+
+use super::{constants::T_BN254_POSEIDON, native::permute_bn254_poseidon_native};
+
+/// Elements absorbed per permutation, the first element of the state being the capacity.
+const RATE: usize = T_BN254_POSEIDON - 1;
+
+/// Challenge squeezed by `PoseidonTranscript`, a scalar used as is.
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonChallenge(Fr);
+
+impl EncodedChallenge<G1Affine> for PoseidonChallenge {
+    type Input = Fr;
+
+    fn new(challenge_input: &Fr) -> Self {
+        Self(*challenge_input)
+    }
+
+    fn get_scalar(&self) -> Fr {
+        self.0
+    }
+}
+
+/// halo2 transcript over a sponge of the BN254 Poseidon permutation with `t = 5`, which is cheap
+/// to replay in a halo2 circuit, unlike `Keccak256Transcript`. Scalars are absorbed as is and
+/// points as `[x mod r, y mod r]`, like snark-verifier's `PoseidonTranscript` does natively and
+/// in its aggregation circuits.
+#[derive(Debug)]
+pub struct PoseidonTranscript<S> {
+    stream: S,
+    state: [Fr; T_BN254_POSEIDON],
+    absorbing: Vec<Fr>,
+}
+
+impl<S> PoseidonTranscript<S> {
+    pub fn new(stream: S) -> Self {
+        let mut state = [Fr::ZERO; T_BN254_POSEIDON];
+        // capacity of a variable length sponge
+        state[0] = Fr::from_u128(1 << 64);
+        Self {
+            stream,
+            state,
+            absorbing: vec![],
+        }
+    }
+
+    fn absorb(&mut self, elements: &[Fr]) {
+        self.absorbing.extend_from_slice(elements);
+        while self.absorbing.len() >= RATE {
+            let chunk = self.absorbing.drain(..RATE).collect::<Vec<_>>();
+            self.permute_with(&chunk);
+        }
+    }
+
+    fn permute_with(&mut self, chunk: &[Fr]) {
+        for (state, input) in self.state.iter_mut().skip(1).zip(chunk) {
+            *state += input;
+        }
+        permute_bn254_poseidon_native(&mut self.state);
+    }
+
+    /// Pads the pending elements with a one, which also separates two consecutive squeezes.
+    fn squeeze(&mut self) -> Fr {
+        let mut last_chunk = std::mem::take(&mut self.absorbing);
+        last_chunk.push(Fr::ONE);
+        self.permute_with(&last_chunk);
+        self.state[1]
+    }
+}
+
+impl<S> Transcript<G1Affine, PoseidonChallenge> for PoseidonTranscript<S> {
+    fn squeeze_challenge(&mut self) -> PoseidonChallenge {
+        PoseidonChallenge::new(&self.squeeze())
+    }
+
+    fn common_point(&mut self, point: G1Affine) -> io::Result<()> {
+        let coordinates = Option::from(point.coordinates()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "cannot absorb the point at infinity".to_string(),
+            )
+        })?;
+        let elements = [*coordinates.x(), *coordinates.y()].map(|e| big_to_fe::<Fr>(fe_to_big(e)));
+        self.absorb(&elements);
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.absorb(&[scalar]);
+        Ok(())
+    }
+}
+
+impl<R: Read> TranscriptRead<G1Affine, PoseidonChallenge> for PoseidonTranscript<R> {
+    fn read_point(&mut self) -> io::Result<G1Affine> {
+        let mut repr = <G1Affine as GroupEncoding>::Repr::default();
+        self.stream.read_exact(repr.as_mut())?;
+        let point = Option::from(G1Affine::from_bytes(&repr)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid point encoding in proof".to_string(),
+            )
+        })?;
+        self.common_point(point)?;
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<Fr> {
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        self.stream.read_exact(repr.as_mut())?;
+        let scalar = Option::from(Fr::from_repr(repr)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof".to_string(),
+            )
+        })?;
+        self.common_scalar(scalar)?;
+        Ok(scalar)
+    }
+}
+
+impl<R: Read> TranscriptReadBuffer<R, G1Affine, PoseidonChallenge> for PoseidonTranscript<R> {
+    fn init(reader: R) -> Self {
+        Self::new(reader)
+    }
+}
+
+impl<W: Write> TranscriptWrite<G1Affine, PoseidonChallenge> for PoseidonTranscript<W> {
+    fn write_point(&mut self, point: G1Affine) -> io::Result<()> {
+        self.common_point(point)?;
+        self.stream.write_all(point.to_bytes().as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: Fr) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        self.stream.write_all(scalar.to_repr().as_ref())
+    }
+}
+
+impl<W: Write> TranscriptWriterBuffer<W, G1Affine, PoseidonChallenge> for PoseidonTranscript<W> {
+    fn init(writer: W) -> Self {
+        Self::new(writer)
+    }
+
+    fn finalize(self) -> W {
+        self.stream
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        halo2curves::{
+            bn256::{Fr, G1Affine},
+            ff::Field,
+            group::Curve,
+        },
+        transcript::{EncodedChallenge, Transcript, TranscriptRead, TranscriptWrite},
+    };
+    use num_bigint::BigUint;
+    use rand::rngs::OsRng;
+
+    use super::PoseidonTranscript;
+    use crate::plonky2_verifier::bn245_poseidon::value::bg_to_fe;
+
+    fn replay(proof: &[u8], num_points: usize, num_scalars: usize) -> Vec<Fr> {
+        let mut reader = PoseidonTranscript::new(proof);
+        for _ in 0..num_points {
+            reader.read_point().unwrap();
+        }
+        let mut challenges = vec![reader.squeeze_challenge().get_scalar()];
+        challenges.push(reader.squeeze_challenge().get_scalar());
+        for _ in 0..num_scalars {
+            reader.read_scalar().unwrap();
+        }
+        challenges.push(reader.squeeze_challenge().get_scalar());
+        challenges
+    }
+
+    #[test]
+    fn test_poseidon_transcript() {
+        let points = (0..3)
+            .map(|_| (G1Affine::generator() * Fr::random(OsRng)).to_affine())
+            .collect::<Vec<_>>();
+        let scalars = (0..5).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
+
+        let mut writer = PoseidonTranscript::new(vec![]);
+        for point in points.iter() {
+            writer.write_point(*point).unwrap();
+        }
+        let mut challenges = vec![writer.squeeze_challenge().get_scalar()];
+        challenges.push(writer.squeeze_challenge().get_scalar());
+        for scalar in scalars.iter() {
+            writer.write_scalar(*scalar).unwrap();
+        }
+        challenges.push(writer.squeeze_challenge().get_scalar());
+        assert_ne!(challenges[0], challenges[1]);
+
+        let proof = writer.stream;
+        assert_eq!(replay(&proof, points.len(), scalars.len()), challenges);
+
+        // flips the least significant bit of the last scalar
+        let mut tampered = proof.clone();
+        let offset = tampered.len() - 32;
+        tampered[offset] ^= 1;
+        let tampered_challenges = replay(&tampered, points.len(), scalars.len());
+        assert_eq!(tampered_challenges[..2], challenges[..2]);
+        assert_ne!(tampered_challenges[2], challenges[2]);
+    }
+
+    /// Challenges of the sponge of snark-verifier's `PoseidonTranscript<_, _, _, 5, 4, 8, 60>`,
+    /// computed from its definition over the permutation of `test_poseidon_known_answer`: the
+    /// state starts as `[2^64, 0, 0, 0, 0]`, every chunk of 4 absorbed elements is added to the
+    /// last 4 words before permuting, a squeeze pads the last chunk with a one and outputs the
+    /// second word. The points `G` and `2G` are absorbed as `[x mod r, y mod r]`.
+    #[test]
+    fn test_poseidon_transcript_known_answer() {
+        let mut transcript = PoseidonTranscript::new(vec![]);
+        for scalar in [1u64, 2, 3] {
+            transcript.write_scalar(Fr::from(scalar)).unwrap();
+        }
+        let mut challenges = vec![transcript.squeeze_challenge().get_scalar()];
+        let generator = G1Affine::generator();
+        transcript.write_point(generator).unwrap();
+        transcript
+            .write_point((generator + generator).to_affine())
+            .unwrap();
+        challenges.push(transcript.squeeze_challenge().get_scalar());
+        challenges.push(transcript.squeeze_challenge().get_scalar());
+
+        let expected = [
+            "1c4c71bf23f0083307876ea17881ab2306f99b4921d9d85097cffa6922de9641",
+            "060d6fa08bc9b2953682a3c26080994e69d3636507aa44c7a582a5444f5514aa",
+            "21b3c9d3ce015e426f96628da412799aad77f741780596d09a9ce9928fc1f999",
+        ]
+        .map(|x| bg_to_fe::<Fr>(&BigUint::parse_bytes(x.as_bytes(), 16).unwrap()));
+        assert_eq!(challenges, expected);
+    }
+}
@@ -2,7 +2,10 @@ use halo2_proofs::{circuit::Value, halo2curves::ff::PrimeField};
 use num_bigint::BigUint;
 
 use super::constants::{
-    MDS_MATRIX_BG, ROUND_CONSTANTS_BG, R_F_BN254_POSEIDON, R_P_BN254_POSEIDON, T_BN254_POSEIDON,
+    MDS_MATRIX_BG, POSEIDON2_EXTERNAL_CONSTANTS_BG, POSEIDON2_EXTERNAL_MATRIX,
+    POSEIDON2_INTERNAL_CONSTANTS_BG, POSEIDON2_INTERNAL_DIAG_M_1_BG, ROUND_CONSTANTS_BG,
+    R_F_BN254_POSEIDON, R_F_BN254_POSEIDON2, R_P_BN254_POSEIDON, R_P_BN254_POSEIDON2,
+    T_BN254_POSEIDON, T_BN254_POSEIDON2,
 };
 
 pub fn bg_to_fe<F: PrimeField>(x: &BigUint) -> F {
@@ -68,15 +71,65 @@ pub fn permute_value<F: PrimeField>(state: &mut [Value<F>; T_BN254_POSEIDON]) {
     }
 }
 
+pub fn poseidon2_external_layer_value<F: PrimeField>(state: &mut [Value<F>; T_BN254_POSEIDON2]) {
+    let mut new_state = [Value::known(F::from(0)); T_BN254_POSEIDON2];
+    for i in 0..T_BN254_POSEIDON2 {
+        for j in 0..T_BN254_POSEIDON2 {
+            new_state[i] =
+                new_state[i] + state[j] * Value::known(F::from(POSEIDON2_EXTERNAL_MATRIX[i][j]));
+        }
+    }
+    *state = new_state
+}
+
+pub fn poseidon2_external_round_value<F: PrimeField>(
+    state: &mut [Value<F>; T_BN254_POSEIDON2],
+    round: usize,
+) {
+    for i in 0..T_BN254_POSEIDON2 {
+        let x = state[i] + Value::known(bg_to_fe::<F>(&POSEIDON2_EXTERNAL_CONSTANTS_BG[round][i]));
+        state[i] = x * x * x * x * x;
+    }
+    poseidon2_external_layer_value(state);
+}
+
+pub fn poseidon2_internal_round_value<F: PrimeField>(
+    state: &mut [Value<F>; T_BN254_POSEIDON2],
+    round: usize,
+) {
+    let x = state[0] + Value::known(bg_to_fe::<F>(&POSEIDON2_INTERNAL_CONSTANTS_BG[round]));
+    state[0] = x * x * x * x * x;
+    let sum = state
+        .iter()
+        .fold(Value::known(F::from(0)), |acc, x| acc + *x);
+    for i in 0..T_BN254_POSEIDON2 {
+        state[i] = sum + state[i] * Value::known(bg_to_fe::<F>(&POSEIDON2_INTERNAL_DIAG_M_1_BG[i]));
+    }
+}
+
+pub fn permute_poseidon2_value<F: PrimeField>(state: &mut [Value<F>; T_BN254_POSEIDON2]) {
+    poseidon2_external_layer_value(state);
+    for round in 0..R_F_BN254_POSEIDON2 / 2 {
+        poseidon2_external_round_value(state, round);
+    }
+    for round in 0..R_P_BN254_POSEIDON2 {
+        poseidon2_internal_round_value(state, round);
+    }
+    for round in R_F_BN254_POSEIDON2 / 2..R_F_BN254_POSEIDON2 {
+        poseidon2_external_round_value(state, round);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
 
     use crate::plonky2_verifier::bn245_poseidon::{
-        constants::T_BN254_POSEIDON, native::permute_bn254_poseidon_native,
+        constants::{T_BN254_POSEIDON, T_BN254_POSEIDON2},
+        native::{permute_bn254_poseidon2_native, permute_bn254_poseidon_native},
     };
 
-    use super::permute_value;
+    use super::{permute_poseidon2_value, permute_value};
 
     #[test]
     fn test_poseidon_correspondence_with_value() {
@@ -91,4 +144,18 @@ mod tests {
                 x.map(|x| assert_eq!(x, *y));
             });
     }
+
+    #[test]
+    fn test_poseidon2_correspondence_with_value() {
+        let mut state_native = [Fr::from(0u64); T_BN254_POSEIDON2];
+        let mut state_value = state_native.map(|x| Value::known(x));
+        permute_poseidon2_value(&mut state_value);
+        permute_bn254_poseidon2_native(&mut state_native);
+        state_value
+            .iter()
+            .zip(state_native.iter())
+            .for_each(|(x, y)| {
+                x.map(|x| assert_eq!(x, *y));
+            });
+    }
 }
@@ -1,3 +1,5 @@
+use std::marker::PhantomData;
+
 use halo2_proofs::circuit::AssignedCell;
 use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
 use halo2wrong_maingate::AssignedValue;
@@ -8,6 +10,7 @@ use plonky2::{field::goldilocks_field::GoldilocksField, util::reverse_index_bits
 use super::{
     goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
     goldilocks_extension_chip::GoldilocksExtensionChip,
+    hasher_chip::HasherChip,
     merkle_proof_chip::MerkleProofChip,
     vector_chip::VectorChip,
 };
@@ -23,15 +26,16 @@ use crate::plonky2_verifier::types::{
     fri::{FriBatchInfo, FriInstanceInfo},
 };
 
-pub struct FriVerifierChip<F: PrimeField> {
+pub struct FriVerifierChip<F: PrimeField, H: HasherChip<F>> {
     goldilocks_chip_config: GoldilocksChipConfig<F>,
     /// Representative `g` of the coset used in FRI, so that LDEs in FRI are done over `gH`.
     offset: AssignedValue<F>,
     /// The degree of the purported codeword, measured in bits.
     fri_params: FriParams,
+    _marker: PhantomData<H>,
 }
 
-impl<F: PrimeField> FriVerifierChip<F> {
+impl<F: PrimeField, H: HasherChip<F>> FriVerifierChip<F, H> {
     pub fn construct(
         goldilocks_chip_config: &GoldilocksChipConfig<F>,
 
@@ -42,6 +46,7 @@ impl<F: PrimeField> FriVerifierChip<F> {
             goldilocks_chip_config: goldilocks_chip_config.clone(),
             offset: offset.clone(),
             fri_params,
+            _marker: PhantomData,
         }
     }
 
@@ -90,7 +95,7 @@ impl<F: PrimeField> FriVerifierChip<F> {
         initial_merkle_caps: &[AssignedMerkleCapValues<F>],
         initial_trees_proof: &AssignedFriInitialTreeProofValues<F>,
     ) -> Result<(), Error> {
-        let merkle_proof_chip = MerkleProofChip::new(&self.goldilocks_chip_config);
+        let merkle_proof_chip = MerkleProofChip::<F, H>::new(&self.goldilocks_chip_config);
         for (_, ((evals, merkle_proof), cap)) in initial_trees_proof
             .evals_proofs
             .iter()
@@ -177,7 +182,8 @@ impl<F: PrimeField> FriVerifierChip<F> {
         let goldilocks_chip = self.goldilocks_chip();
         let goldilocks_extension_chip = self.goldilocks_extension_chip();
         // computes `P'(x^arity)` where `arity = 1 << arity_bits` from `P(x*g^i), (i = 0, ..., arity)` where
-        // g is `arity`-th primitive root of unity. P' is FRI folded polynomial.
+        // g is `arity`-th primitive root of unity. P' is FRI folded polynomial. Any arity supported
+        // by `CommonData` works, see `MAX_FRI_ARITY_BITS`.
         let arity = 1 << arity_bits;
         let g = GoldilocksField::MULTIPLICATIVE_GROUP_GENERATOR
             .exp_u64(GoldilocksField::NEG_ONE.to_canonical_u64() / (arity as u64));
@@ -200,29 +206,35 @@ impl<F: PrimeField> FriVerifierChip<F> {
         let coset_start = goldilocks_chip.mul(ctx, &start, x)?;
 
         // The answer is gotten by interpolating {(x*g^i, P(x*g^i))} and evaluating at beta.
+        // With `s = coset_start` and `n = arity`, `prod_i(X - s*g^i) = X^n - s^n` and the
+        // barycentric weight of `x_i = s*g^i` is `x_i / (n * s^n)`, so that
+        // P(beta) = (beta^n - s^n) / (n * s^n) * sum_i(P(x_i) * x_i / (beta - x_i)).
         let mut g_power = goldilocks_chip.assign_constant(ctx, GoldilocksField::ONE)?;
-        let mut points = vec![];
-        for (_, eval) in evals.iter().enumerate() {
+        let mut sum = goldilocks_extension_chip.zero_extension(ctx)?;
+        for eval in evals.iter() {
             let x = goldilocks_chip.mul(ctx, &coset_start, &g_power)?;
-            let x = goldilocks_extension_chip.convert_to_extension(ctx, &x)?;
             g_power = goldilocks_chip.mul(ctx, &g_power, &g)?;
-            points.push((x, eval.clone()));
+            let x = goldilocks_extension_chip.convert_to_extension(ctx, &x)?;
+            let numerator = goldilocks_extension_chip.mul_extension(ctx, eval, &x)?;
+            let denominator = goldilocks_extension_chip.sub_extension(ctx, beta, &x)?;
+            sum =
+                goldilocks_extension_chip.div_add_extension(ctx, &numerator, &denominator, &sum)?;
         }
-        // TODO : For now, only 2-arity is supported. Otherwise, FFT implementation over extension Field is required.
-        // a0 -> a1
-        // b0 -> b1
-        // x  -> a1 + (x-a0)*(b1-a1)/(b0-a0)
-        let (a0, a1) = &points[0];
-        let (b0, b1) = &points[1];
-
-        // a1 + (x - a0) * (b1 - a1) / (b0 - a0)
-        let x_minus_a0 = goldilocks_extension_chip.sub_extension(ctx, beta, a0)?;
-        let b1_minus_a1 = goldilocks_extension_chip.sub_extension(ctx, b1, a1)?;
-        let numerator = goldilocks_extension_chip.mul_extension(ctx, &x_minus_a0, &b1_minus_a1)?;
-        let denominator = goldilocks_extension_chip.sub_extension(ctx, b0, a0)?;
-        let result =
-            goldilocks_extension_chip.div_add_extension(ctx, &numerator, &denominator, a1)?;
-        Ok(result)
+        let coset_start_pow_n = goldilocks_chip.exp_power_of_2(ctx, &coset_start, arity_bits)?;
+        let coset_start_pow_n =
+            goldilocks_extension_chip.convert_to_extension(ctx, &coset_start_pow_n)?;
+        let beta_pow_n =
+            goldilocks_extension_chip.exp_power_of_2_extension(ctx, beta.clone(), arity_bits)?;
+        let vanishing =
+            goldilocks_extension_chip.sub_extension(ctx, &beta_pow_n, &coset_start_pow_n)?;
+        let n_coset_start_pow_n = goldilocks_extension_chip.scalar_mul(
+            ctx,
+            &coset_start_pow_n,
+            GoldilocksField::from_canonical_usize(arity),
+        )?;
+        let scale =
+            goldilocks_extension_chip.div_extension(ctx, &vanishing, &n_coset_start_pow_n)?;
+        goldilocks_extension_chip.mul_extension(ctx, &scale, &sum)
     }
 
     fn check_consistency(
@@ -251,26 +263,30 @@ impl<F: PrimeField> FriVerifierChip<F> {
 
         let cap_index = self.calculate_cap_index(ctx, &x_index_bits[..])?;
         // verify evaluation proofs for initial polynomials at `x_index` point
-        self.verify_initial_merkle_proof(
-            ctx,
-            &x_index_bits,
-            &cap_index,
-            initial_merkle_caps,
-            &round_proof.initial_trees_proof,
-        )?;
+        ctx.stage("initial_merkle_proofs", |ctx| {
+            self.verify_initial_merkle_proof(
+                ctx,
+                &x_index_bits,
+                &cap_index,
+                initial_merkle_caps,
+                &round_proof.initial_trees_proof,
+            )
+        })?;
 
         let x_from_subgroup =
             self.x_from_subgroup(ctx, &x_index_bits.iter().rev().cloned().collect_vec())?;
         let mut x_from_subgroup = goldilocks_chip.mul(ctx, &self.offset, &x_from_subgroup)?;
 
-        let mut prev_eval = self.batch_initial_polynomials(
-            ctx,
-            fri_instance_info,
-            fri_alpha,
-            &x_from_subgroup,
-            &round_proof.initial_trees_proof,
-            reduced_openings,
-        )?;
+        let mut prev_eval = ctx.stage("batch_initial_polynomials", |ctx| {
+            self.batch_initial_polynomials(
+                ctx,
+                fri_instance_info,
+                fri_alpha,
+                &x_from_subgroup,
+                &round_proof.initial_trees_proof,
+                reduced_openings,
+            )
+        })?;
 
         for (i, &arity_bits) in self.fri_params.reduction_arity_bits.iter().enumerate() {
             let evals = &round_proof.steps[i].evals;
@@ -300,15 +316,17 @@ impl<F: PrimeField> FriVerifierChip<F> {
                 &fri_betas[i],
             )?;
 
-            let merkle_proof_chip = MerkleProofChip::new(&self.goldilocks_chip_config);
-            merkle_proof_chip.verify_merkle_proof_to_cap_with_cap_index(
-                ctx,
-                &evals.iter().flat_map(|eval| eval.0.clone()).collect_vec(),
-                &coset_index_bits,
-                &cap_index,
-                &fri_proof.commit_phase_merkle_cap_values[i],
-                &round_proof.steps[i].merkle_proof,
-            )?;
+            let merkle_proof_chip = MerkleProofChip::<F, H>::new(&self.goldilocks_chip_config);
+            ctx.stage("commit_phase_merkle_proofs", |ctx| {
+                merkle_proof_chip.verify_merkle_proof_to_cap_with_cap_index(
+                    ctx,
+                    &evals.iter().flat_map(|eval| eval.0.clone()).collect_vec(),
+                    &coset_index_bits,
+                    &cap_index,
+                    &fri_proof.commit_phase_merkle_cap_values[i],
+                    &round_proof.steps[i].merkle_proof,
+                )
+            })?;
             // Update the point x to x^arity.
             x_from_subgroup = goldilocks_chip.exp_power_of_2(ctx, &x_from_subgroup, arity_bits)?;
 
@@ -336,27 +354,32 @@ impl<F: PrimeField> FriVerifierChip<F> {
         fri_instance_info: &FriInstanceInfo<F, 2>,
     ) -> Result<(), Error> {
         // verify proof of work
-        self.fri_verify_proof_of_work(
-            ctx,
-            &fri_challenges.fri_pow_response,
-            &self.fri_params.config,
-        )?;
+        ctx.stage("proof_of_work", |ctx| {
+            self.fri_verify_proof_of_work(
+                ctx,
+                &fri_challenges.fri_pow_response,
+                &self.fri_params.config,
+            )
+        })?;
 
         // this value is the same across all queries
-        let reduced_openings =
-            self.compute_reduced_openings(ctx, &fri_challenges.fri_alpha, fri_openings)?;
+        let reduced_openings = ctx.stage("reduced_openings", |ctx| {
+            self.compute_reduced_openings(ctx, &fri_challenges.fri_alpha, fri_openings)
+        })?;
         for (i, round_proof) in fri_proof.query_round_proofs.iter().enumerate() {
-            self.check_consistency(
-                ctx,
-                initial_merkle_caps,
-                fri_instance_info,
-                &fri_challenges.fri_alpha,
-                &fri_challenges.fri_betas,
-                fri_proof,
-                &fri_challenges.fri_query_indices[i],
-                round_proof,
-                &reduced_openings,
-            )?;
+            ctx.stage("check_consistency", |ctx| {
+                self.check_consistency(
+                    ctx,
+                    initial_merkle_caps,
+                    fri_instance_info,
+                    &fri_challenges.fri_alpha,
+                    &fri_challenges.fri_betas,
+                    fri_proof,
+                    &fri_challenges.fri_query_indices[i],
+                    round_proof,
+                    &reduced_openings,
+                )
+            })?;
         }
         Ok(())
     }
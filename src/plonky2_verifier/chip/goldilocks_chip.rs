@@ -417,7 +417,7 @@ impl<F: PrimeField> GoldilocksChip<F> {
         &self,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), halo2_proofs::plonk::Error> {
-        self.arithmetic_chip().load_table(layouter)
+        self.all_chip().load_table(layouter)
     }
 }
 
@@ -445,6 +445,7 @@ mod tests {
         type Config = GoldilocksChipConfig<Fr>;
 
         type FloorPlanner = V1;
+        type Params = ();
 
         fn without_witnesses(&self) -> Self {
             Self::default()
@@ -0,0 +1,463 @@
+use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
+use halo2wrong_maingate::AssignedValue;
+use itertools::Itertools;
+use plonky2::{
+    field::{goldilocks_field::GoldilocksField, types::Field},
+    hash::{keccak::KeccakHash, poseidon::SPONGE_WIDTH},
+};
+
+use crate::plonky2_verifier::{context::RegionCtx, types::assigned::AssignedHashValues};
+
+use super::{
+    goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
+    hasher_chip::{AlgebraicHasherChip, HasherChip},
+    native_chip::{
+        all_chip::OptionalChips,
+        keccak_chip::{KeccakChip, NIBBLES_PER_LANE, OUTPUT_NIBBLES},
+    },
+};
+
+/// Digests of `KeccakGoldilocksConfig` are the first 25 bytes of Keccak-256.
+pub const KECCAK_HASH_BYTES: usize = 25;
+/// `BytesHash::to_vec` packs 7 bytes per Goldilocks element.
+const BYTES_PER_ELEMENT: usize = 7;
+/// Outputs of `KeccakPermutation` are read from 3 chained Keccak-256 digests.
+const PERMUTATION_DIGESTS: usize = 3;
+
+/// Constrains plonky2's `KeccakHash<25>` over Goldilocks elements: elements are hashed as their
+/// canonical little-endian `u64` bytes, and digests are packed into 4 elements of 7 bytes.
+#[derive(Debug, Clone)]
+pub struct GoldilocksKeccakChip<F: PrimeField> {
+    goldilocks_chip_config: GoldilocksChipConfig<F>,
+}
+
+impl<F: PrimeField> GoldilocksKeccakChip<F> {
+    pub fn new(goldilocks_chip_config: &GoldilocksChipConfig<F>) -> Self {
+        Self {
+            goldilocks_chip_config: goldilocks_chip_config.clone(),
+        }
+    }
+
+    pub fn goldilocks_chip(&self) -> GoldilocksChip<F> {
+        GoldilocksChip::new(&self.goldilocks_chip_config)
+    }
+
+    fn keccak_chip(&self) -> Result<KeccakChip<F>, Error> {
+        self.goldilocks_chip().all_chip().keccak_chip()
+    }
+
+    /// Nibbles of the 8 little-endian bytes of each of the canonical `elements`
+    fn elements_to_nibbles(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        elements: &[AssignedValue<F>],
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        let keccak_chip = self.keccak_chip()?;
+        let nibbles = elements
+            .iter()
+            .map(|element| keccak_chip.decompose(ctx, element, NIBBLES_PER_LANE))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(nibbles.concat())
+    }
+
+    /// Nibbles of the `KECCAK_HASH_BYTES` bytes of `digest`
+    fn digest_to_nibbles(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        digest: &AssignedHashValues<F>,
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        let keccak_chip = self.keccak_chip()?;
+        let nibbles = digest
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, element)| {
+                let num_bytes = BYTES_PER_ELEMENT.min(KECCAK_HASH_BYTES - i * BYTES_PER_ELEMENT);
+                keccak_chip.decompose(ctx, element, 2 * num_bytes)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(nibbles.concat())
+    }
+
+    /// Digest of the first `KECCAK_HASH_BYTES` bytes given by `nibbles`
+    fn nibbles_to_digest(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        nibbles: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error> {
+        let keccak_chip = self.keccak_chip()?;
+        let elements = nibbles[..2 * KECCAK_HASH_BYTES]
+            .chunks(2 * BYTES_PER_ELEMENT)
+            .map(|chunk| keccak_chip.compose(ctx, chunk))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(AssignedHashValues {
+            elements: elements.try_into().unwrap(),
+        })
+    }
+
+    pub fn hash_no_pad(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        inputs: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error> {
+        let nibbles = self.elements_to_nibbles(ctx, inputs)?;
+        let digest = self.keccak_chip()?.keccak256(ctx, &nibbles)?;
+        self.nibbles_to_digest(ctx, &digest)
+    }
+
+    /// Keccak-256 of the big-endian `u64` bytes of the canonical `elements`, i.e. Solidity's
+    /// `keccak256(abi.encodePacked(elements))` over `uint64` values, returned as the high and
+    /// the low 128 bits of the digest
+    pub fn keccak256_be(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        elements: &[AssignedValue<F>],
+    ) -> Result<[AssignedValue<F>; 2], Error> {
+        let keccak_chip = self.keccak_chip()?;
+        let mut nibbles = vec![];
+        for element in elements {
+            let le_nibbles = keccak_chip.decompose(ctx, element, NIBBLES_PER_LANE)?;
+            // bytes in reverse order, each keeping its low nibble first
+            nibbles.extend(le_nibbles.chunks(2).rev().flatten().cloned());
+        }
+        let digest = keccak_chip.keccak256(ctx, &nibbles)?;
+        let halves = digest
+            .chunks(OUTPUT_NIBBLES / 2)
+            .map(|half| {
+                let le_nibbles = half.chunks(2).rev().flatten().cloned().collect_vec();
+                keccak_chip.compose(ctx, &le_nibbles)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(halves.try_into().unwrap())
+    }
+
+    /// Inputs that fit in a digest are its bytes, padded with zeros
+    pub fn hash_or_noop(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        inputs: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error> {
+        if inputs.len() * 8 > KECCAK_HASH_BYTES {
+            return self.hash_no_pad(ctx, inputs);
+        }
+        let mut nibbles = self.elements_to_nibbles(ctx, inputs)?;
+        let zero = self
+            .goldilocks_chip()
+            .assign_constant(ctx, GoldilocksField::ZERO)?;
+        nibbles.resize(2 * KECCAK_HASH_BYTES, zero);
+        self.nibbles_to_digest(ctx, &nibbles)
+    }
+
+    pub fn two_to_one(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        left: &AssignedHashValues<F>,
+        right: &AssignedHashValues<F>,
+    ) -> Result<AssignedHashValues<F>, Error> {
+        let mut nibbles = self.digest_to_nibbles(ctx, left)?;
+        nibbles.extend(self.digest_to_nibbles(ctx, right)?);
+        let digest = self.keccak_chip()?.keccak256(ctx, &nibbles)?;
+        self.nibbles_to_digest(ctx, &digest)
+    }
+
+    /// Constrains `KeccakPermutation`: the state is hashed, and the new state is read from the
+    /// `u64` words of that digest and of the digests chained from it. plonky2 skips the words that
+    /// are not canonical Goldilocks elements, which happens with probability about `2^-28` per
+    /// permutation, and such a permutation can't be proven here.
+    pub fn permute(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: [AssignedValue<F>; SPONGE_WIDTH],
+    ) -> Result<[AssignedValue<F>; SPONGE_WIDTH], Error> {
+        let keccak_chip = self.keccak_chip()?;
+        let arithmetic_chip = self.goldilocks_chip().arithmetic_chip();
+        let mut digest = self.elements_to_nibbles(ctx, &state)?;
+        let mut words = vec![];
+        for _ in 0..PERMUTATION_DIGESTS {
+            digest = keccak_chip.keccak256(ctx, &digest)?;
+            for word_nibbles in digest.chunks(NIBBLES_PER_LANE) {
+                let word = keccak_chip.compose(ctx, word_nibbles)?;
+                arithmetic_chip.range_check(ctx, &word)?;
+                words.push(word);
+            }
+        }
+        debug_assert_eq!(
+            words.len(),
+            PERMUTATION_DIGESTS * OUTPUT_NIBBLES / NIBBLES_PER_LANE
+        );
+        Ok(words.try_into().unwrap())
+    }
+}
+
+impl<F: PrimeField> HasherChip<F> for KeccakHash<KECCAK_HASH_BYTES> {
+    const REQUIRED_CHIPS: OptionalChips = OptionalChips {
+        keccak: true,
+        ..OptionalChips::NONE
+    };
+
+    fn hash_no_pad(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        inputs: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error> {
+        GoldilocksKeccakChip::new(goldilocks_chip_config).hash_no_pad(ctx, inputs)
+    }
+
+    fn two_to_one(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        left: &AssignedHashValues<F>,
+        right: &AssignedHashValues<F>,
+    ) -> Result<AssignedHashValues<F>, Error> {
+        GoldilocksKeccakChip::new(goldilocks_chip_config).two_to_one(ctx, left, right)
+    }
+
+    fn hash_or_noop(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        inputs: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error> {
+        GoldilocksKeccakChip::new(goldilocks_chip_config).hash_or_noop(ctx, inputs)
+    }
+}
+
+/// `KeccakPermutation` isn't algebraic, but it is what plonky2's challenger runs on for
+/// `KeccakGoldilocksConfig` proofs, so the transcript keeps using `SpongeChip` over it.
+impl<F: PrimeField> AlgebraicHasherChip<F> for KeccakHash<KECCAK_HASH_BYTES> {
+    fn permute(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        state: [AssignedValue<F>; SPONGE_WIDTH],
+    ) -> Result<[AssignedValue<F>; SPONGE_WIDTH], Error> {
+        GoldilocksKeccakChip::new(goldilocks_chip_config).permute(ctx, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Layouter, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2wrong_maingate::AssignedValue;
+    use plonky2::{
+        field::{
+            goldilocks_field::GoldilocksField,
+            types::{Field, Sample},
+        },
+        hash::{
+            hash_types::BytesHash,
+            hashing::PlonkyPermutation,
+            keccak::{KeccakHash, KeccakPermutation},
+            merkle_tree::MerkleTree,
+        },
+        plonk::config::{GenericHashOut, Hasher},
+    };
+
+    use crate::plonky2_verifier::{
+        chip::{
+            goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
+            merkle_proof_chip::MerkleProofChip,
+            native_chip::{
+                all_chip::AllChipConfig,
+                utils::{fe_to_goldilocks, goldilocks_to_fe},
+            },
+        },
+        context::RegionCtx,
+        types::{assigned::AssignedHashValues, proof::MerkleProofValues, MerkleCapValues},
+        verifier_circuit::public_inputs_digest,
+    };
+
+    use super::{GoldilocksKeccakChip, HasherChip, KECCAK_HASH_BYTES};
+
+    type Keccak = KeccakHash<KECCAK_HASH_BYTES>;
+
+    fn assign_elements(
+        goldilocks_chip: &GoldilocksChip<Fr>,
+        ctx: &mut RegionCtx<'_, Fr>,
+        elements: &[GoldilocksField],
+    ) -> Result<Vec<AssignedValue<Fr>>, Error> {
+        elements
+            .iter()
+            .map(|x| goldilocks_chip.assign_value(ctx, Value::known(goldilocks_to_fe(*x))))
+            .collect()
+    }
+
+    fn assert_elements(assigned: &[AssignedValue<Fr>], expected: &[GoldilocksField]) {
+        assert_eq!(assigned.len(), expected.len());
+        for (x, e) in assigned.iter().zip(expected.iter()) {
+            x.value().map(|x| assert_eq!(fe_to_goldilocks(*x), *e));
+        }
+    }
+
+    /// Checks `hash_no_pad` over several blocks, `hash_or_noop`, `two_to_one` and the
+    /// permutation against plonky2, and `keccak256_be` against `public_inputs_digest`
+    #[derive(Clone)]
+    struct KeccakHasherCircuit {
+        long_input: Vec<GoldilocksField>,
+        short_input: Vec<GoldilocksField>,
+        left: BytesHash<KECCAK_HASH_BYTES>,
+        right: BytesHash<KECCAK_HASH_BYTES>,
+        state: [GoldilocksField; 12],
+    }
+
+    impl Circuit<Fr> for KeccakHasherCircuit {
+        type Config = GoldilocksChipConfig<Fr>;
+        type FloorPlanner = V1;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let chips = <Keccak as HasherChip<Fr>>::REQUIRED_CHIPS;
+            let all_chip = AllChipConfig::<Fr>::configure_with(meta, chips);
+            GoldilocksChip::configure(&all_chip)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let goldilocks_chip = GoldilocksChip::new(&config);
+            goldilocks_chip.load_table(&mut layouter)?;
+            let keccak_chip = GoldilocksKeccakChip::new(&config);
+            layouter.assign_region(
+                || "keccak hasher",
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let long_input = assign_elements(&goldilocks_chip, ctx, &self.long_input)?;
+                    let output = keccak_chip.hash_no_pad(ctx, &long_input)?;
+                    let expected = Keccak::hash_no_pad(&self.long_input);
+                    assert_elements(&output.elements, &expected.to_vec());
+
+                    let output = keccak_chip.keccak256_be(ctx, &long_input)?;
+                    let expected = public_inputs_digest(
+                        &self.long_input.iter().map(|x| x.0).collect::<Vec<_>>(),
+                    );
+                    for (x, e) in output.iter().zip(expected) {
+                        x.value().map(|x| assert_eq!(*x, e));
+                    }
+
+                    let short_input = assign_elements(&goldilocks_chip, ctx, &self.short_input)?;
+                    let output = keccak_chip.hash_or_noop(ctx, &short_input)?;
+                    let expected = Keccak::hash_or_noop(&self.short_input);
+                    assert_elements(&output.elements, &expected.to_vec());
+
+                    let mut digests = vec![];
+                    for digest in [self.left, self.right] {
+                        let elements = assign_elements(&goldilocks_chip, ctx, &digest.to_vec())?;
+                        digests.push(AssignedHashValues {
+                            elements: elements.try_into().unwrap(),
+                        });
+                    }
+                    let output = keccak_chip.two_to_one(ctx, &digests[0], &digests[1])?;
+                    let expected = Keccak::two_to_one(self.left, self.right);
+                    assert_elements(&output.elements, &expected.to_vec());
+
+                    let state = assign_elements(&goldilocks_chip, ctx, &self.state)?;
+                    let output = keccak_chip.permute(ctx, state.try_into().unwrap())?;
+                    let mut permutation = KeccakPermutation::new(self.state);
+                    permutation.permute();
+                    assert_elements(&output, permutation.as_ref());
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_keccak_hasher_mock() {
+        let circuit = KeccakHasherCircuit {
+            // 320 bytes, absorbed in 3 blocks
+            long_input: GoldilocksField::rand_vec(40),
+            short_input: GoldilocksField::rand_vec(3),
+            left: Keccak::hash_no_pad(&GoldilocksField::rand_vec(4)),
+            right: Keccak::hash_no_pad(&GoldilocksField::rand_vec(4)),
+            state: [(); 12].map(|_| GoldilocksField::rand()),
+        };
+        const DEGREE: u32 = 18;
+        let mock_prover = MockProver::run(DEGREE, &circuit, vec![vec![]]).unwrap();
+        mock_prover.assert_satisfied();
+    }
+
+    /// Opens a leaf of a Keccak Merkle tree against its cap
+    #[derive(Clone)]
+    struct KeccakMerkleProofCircuit {
+        tree: MerkleTree<GoldilocksField, Keccak>,
+        leaf_index: usize,
+    }
+
+    impl Circuit<Fr> for KeccakMerkleProofCircuit {
+        type Config = GoldilocksChipConfig<Fr>;
+        type FloorPlanner = V1;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let chips = <Keccak as HasherChip<Fr>>::REQUIRED_CHIPS;
+            let all_chip = AllChipConfig::<Fr>::configure_with(meta, chips);
+            GoldilocksChip::configure(&all_chip)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let goldilocks_chip = GoldilocksChip::new(&config);
+            goldilocks_chip.load_table(&mut layouter)?;
+            let cap = MerkleCapValues::from(self.tree.cap.clone());
+            let proof = MerkleProofValues::from(self.tree.prove(self.leaf_index));
+            let cap_height = self.tree.cap.height();
+            let num_bits = proof.siblings.len() + cap_height;
+            let index_bits = (0..num_bits)
+                .map(|i| GoldilocksField::from_canonical_usize((self.leaf_index >> i) & 1))
+                .collect::<Vec<_>>();
+            layouter.assign_region(
+                || "keccak merkle proof",
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let leaf =
+                        assign_elements(&goldilocks_chip, ctx, &self.tree.leaves[self.leaf_index])?;
+                    let index_bits = assign_elements(&goldilocks_chip, ctx, &index_bits)?;
+                    let (leaf_index_bits, cap_index_bits) =
+                        index_bits.split_at(proof.siblings.len());
+                    let cap_index = goldilocks_chip.from_bits(ctx, &cap_index_bits.to_vec())?;
+                    let assigned_cap = MerkleCapValues::assign(&config, ctx, &cap)?;
+                    let assigned_proof = MerkleProofValues::assign(&config, ctx, &proof)?;
+                    MerkleProofChip::<Fr, Keccak>::new(&config)
+                        .verify_merkle_proof_to_cap_with_cap_index(
+                            ctx,
+                            &leaf,
+                            leaf_index_bits,
+                            &cap_index,
+                            &assigned_cap,
+                            &assigned_proof,
+                        )
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_keccak_merkle_proof_mock() {
+        let leaves = (0..16).map(|_| GoldilocksField::rand_vec(7)).collect();
+        let circuit = KeccakMerkleProofCircuit {
+            tree: MerkleTree::new(leaves, 1),
+            leaf_index: 5,
+        };
+        const DEGREE: u32 = 18;
+        let mock_prover = MockProver::run(DEGREE, &circuit, vec![vec![]]).unwrap();
+        mock_prover.assert_satisfied();
+    }
+}
@@ -0,0 +1,314 @@
+use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
+use halo2wrong_maingate::{AssignedValue, Term};
+use lazy_static::lazy_static;
+use plonky2::{
+    field::{goldilocks_field::GoldilocksField, types::Field},
+    hash::poseidon::{PoseidonHash, SPONGE_WIDTH},
+};
+
+use crate::plonky2_verifier::{context::RegionCtx, types::assigned::AssignedHashValues};
+
+use super::{
+    goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
+    hasher_chip::{sponge_hash_no_pad, sponge_two_to_one, AlgebraicHasherChip, HasherChip},
+    native_chip::utils::goldilocks_to_fe,
+    poseidon_spec::spec::{SparseMDSMatrix, Spec},
+};
+
+const T: usize = SPONGE_WIDTH;
+const T_MINUS_ONE: usize = T - 1;
+
+lazy_static! {
+    /// plonky2's width 12 Poseidon, 8 full and 22 partial rounds.
+    static ref SPEC: Spec<T, T_MINUS_ONE> = Spec::new(8, 22);
+}
+
+type State<F> = [AssignedValue<F>; T];
+
+/// Constrains plonky2's native Poseidon permutation over Goldilocks, i.e. the permutation of
+/// `PoseidonHash`, with the optimized constants and sparse MDS matrices of `poseidon_spec`.
+#[derive(Debug, Clone)]
+pub struct GoldilocksPoseidonChip<F: PrimeField> {
+    goldilocks_chip_config: GoldilocksChipConfig<F>,
+}
+
+impl<F: PrimeField> GoldilocksPoseidonChip<F> {
+    pub fn new(goldilocks_chip_config: &GoldilocksChipConfig<F>) -> Self {
+        Self {
+            goldilocks_chip_config: goldilocks_chip_config.clone(),
+        }
+    }
+
+    pub fn goldilocks_chip(&self) -> GoldilocksChip<F> {
+        GoldilocksChip::new(&self.goldilocks_chip_config)
+    }
+
+    /// Applies full state sbox then adds constants to each word in the state
+    fn sbox_full(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: &mut State<F>,
+        constants: &[GoldilocksField; T],
+    ) -> Result<(), Error> {
+        let goldilocks_chip = self.goldilocks_chip();
+        for (word, constant) in state.iter_mut().zip(constants.iter()) {
+            let word2 = goldilocks_chip.mul(ctx, word, word)?;
+            let word4 = goldilocks_chip.mul(ctx, &word2, &word2)?;
+            let word6 = goldilocks_chip.mul(ctx, &word2, &word4)?;
+            *word = goldilocks_chip.mul_add_constant(ctx, &word6, word, *constant)?;
+        }
+        Ok(())
+    }
+
+    /// Applies sbox to the first word then adds constants to each word in the
+    /// state
+    fn sbox_part(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: &mut State<F>,
+        constant: GoldilocksField,
+    ) -> Result<(), Error> {
+        let goldilocks_chip = self.goldilocks_chip();
+        let word = &mut state[0];
+        let word2 = goldilocks_chip.mul(ctx, word, word)?;
+        let word4 = goldilocks_chip.mul(ctx, &word2, &word2)?;
+        let word6 = goldilocks_chip.mul(ctx, &word2, &word4)?;
+        *word = goldilocks_chip.mul_add_constant(ctx, &word6, word, constant)?;
+        Ok(())
+    }
+
+    // Adds pre constants to the state.
+    fn absorb_with_pre_constants(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: &mut State<F>,
+        pre_constants: &[GoldilocksField; T],
+    ) -> Result<(), Error> {
+        let goldilocks_chip = self.goldilocks_chip();
+        for (word, constant) in state.iter_mut().zip(pre_constants.iter()) {
+            *word = goldilocks_chip.add_constant(ctx, word, *constant)?;
+        }
+        Ok(())
+    }
+
+    /// Applies MDS State multiplication
+    fn apply_mds(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: &mut State<F>,
+        mds: &[[GoldilocksField; T]; T],
+    ) -> Result<(), Error> {
+        let goldilocks_chip = self.goldilocks_chip();
+        let new_state = mds
+            .iter()
+            .map(|row| {
+                // term_i = s_0 * e_i_0 + s_1 * e_i_1 + ....
+                let terms = state
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(e, word)| Term::Assigned(e, goldilocks_to_fe(*word)))
+                    .collect::<Vec<Term<F>>>();
+                goldilocks_chip.compose(ctx, &terms[..], GoldilocksField::ZERO)
+            })
+            .collect::<Result<Vec<AssignedValue<F>>, Error>>()?;
+        *state = new_state.try_into().unwrap();
+        Ok(())
+    }
+
+    /// Applies sparse MDS to the state
+    fn apply_sparse_mds(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: &mut State<F>,
+        mds: &SparseMDSMatrix<T, T_MINUS_ONE>,
+    ) -> Result<(), Error> {
+        let goldilocks_chip = self.goldilocks_chip();
+        // For the 0th word
+        let terms = state
+            .iter()
+            .zip(mds.row().iter())
+            .map(|(e, word)| Term::Assigned(e, goldilocks_to_fe(*word)))
+            .collect::<Vec<Term<F>>>();
+        let mut new_state =
+            vec![goldilocks_chip.compose(ctx, &terms[..], GoldilocksField::ZERO)?];
+
+        // Rest of the trainsition ie the sparse part
+        for (e, word) in mds.col_hat().iter().zip(state.iter().skip(1)) {
+            new_state.push(goldilocks_chip.compose(
+                ctx,
+                &[
+                    Term::Assigned(&state[0], goldilocks_to_fe(*e)),
+                    Term::Assigned(word, F::from(1)),
+                ],
+                GoldilocksField::ZERO,
+            )?);
+        }
+        *state = new_state.try_into().unwrap();
+        Ok(())
+    }
+
+    /// Constrains poseidon permutation of `state`
+    pub fn permute(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        mut state: State<F>,
+    ) -> Result<State<F>, Error> {
+        let r_f = SPEC.r_f() / 2;
+        let mds = SPEC.mds_matrices().mds().rows();
+        let pre_sparse_mds = SPEC.mds_matrices().pre_sparse_mds().rows();
+
+        // First half of the full rounds
+        let constants = SPEC.constants().start();
+        self.absorb_with_pre_constants(ctx, &mut state, &constants[0])?;
+        for constants in constants.iter().skip(1).take(r_f - 1) {
+            self.sbox_full(ctx, &mut state, constants)?;
+            self.apply_mds(ctx, &mut state, &mds)?;
+        }
+        self.sbox_full(ctx, &mut state, constants.last().unwrap())?;
+        self.apply_mds(ctx, &mut state, &pre_sparse_mds)?;
+
+        // Partial rounds
+        let constants = SPEC.constants().partial();
+        let sparse_matrices = SPEC.mds_matrices().sparse_matrices();
+        for (constant, sparse_mds) in constants.iter().zip(sparse_matrices.iter()) {
+            self.sbox_part(ctx, &mut state, *constant)?;
+            self.apply_sparse_mds(ctx, &mut state, sparse_mds)?;
+        }
+
+        // Second half of the full rounds
+        for constants in SPEC.constants().end().iter() {
+            self.sbox_full(ctx, &mut state, constants)?;
+            self.apply_mds(ctx, &mut state, &mds)?;
+        }
+        self.sbox_full(ctx, &mut state, &[GoldilocksField::ZERO; T])?;
+        self.apply_mds(ctx, &mut state, &mds)?;
+
+        Ok(state)
+    }
+}
+
+impl<F: PrimeField> HasherChip<F> for PoseidonHash {
+    fn hash_no_pad(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        inputs: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error> {
+        sponge_hash_no_pad::<F, Self>(ctx, goldilocks_chip_config, inputs)
+    }
+
+    fn two_to_one(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        left: &AssignedHashValues<F>,
+        right: &AssignedHashValues<F>,
+    ) -> Result<AssignedHashValues<F>, Error> {
+        sponge_two_to_one::<F, Self>(ctx, goldilocks_chip_config, left, right)
+    }
+}
+
+impl<F: PrimeField> AlgebraicHasherChip<F> for PoseidonHash {
+    fn permute(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        state: [AssignedValue<F>; SPONGE_WIDTH],
+    ) -> Result<[AssignedValue<F>; SPONGE_WIDTH], Error> {
+        GoldilocksPoseidonChip::new(goldilocks_chip_config).permute(ctx, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Layouter, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use plonky2::{
+        field::{goldilocks_field::GoldilocksField, types::Sample},
+        hash::{hashing::PlonkyPermutation, poseidon::PoseidonPermutation},
+    };
+
+    use crate::plonky2_verifier::{
+        chip::{
+            goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
+            native_chip::{
+                all_chip::AllChipConfig,
+                utils::{fe_to_goldilocks, goldilocks_to_fe},
+            },
+        },
+        context::RegionCtx,
+    };
+
+    use super::GoldilocksPoseidonChip;
+
+    #[derive(Clone, Default)]
+    pub struct TestCircuit {
+        input: [GoldilocksField; 12],
+        expected_output: [GoldilocksField; 12],
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = GoldilocksChipConfig<Fr>;
+        type FloorPlanner = V1;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let all_chip = AllChipConfig::<Fr>::configure(meta);
+            GoldilocksChip::configure(&all_chip)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let goldilocks_chip = GoldilocksChip::new(&config);
+            goldilocks_chip.load_table(&mut layouter)?;
+            layouter.assign_region(
+                || "goldilocks poseidon chip",
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let input_assigned = self
+                        .input
+                        .iter()
+                        .map(|x| {
+                            goldilocks_chip.assign_value(ctx, Value::known(goldilocks_to_fe(*x)))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+
+                    let poseidon_chip = GoldilocksPoseidonChip::new(&config);
+                    let output = poseidon_chip.permute(ctx, input_assigned.try_into().unwrap())?;
+                    output
+                        .iter()
+                        .zip(self.expected_output.iter())
+                        .for_each(|(x, e)| {
+                            x.value().map(|x| assert_eq!(fe_to_goldilocks(*x), *e));
+                        });
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_goldilocks_poseidon_chip_mock() {
+        let input = [(); 12].map(|_| GoldilocksField::rand());
+
+        let mut permutation = PoseidonPermutation::new(input);
+        permutation.permute();
+
+        const DEGREE: u32 = 17;
+        let circuit = TestCircuit {
+            input,
+            expected_output: permutation.as_ref().to_vec().try_into().unwrap(),
+        };
+        let mock_prover = MockProver::run(DEGREE, &circuit, vec![vec![]]).unwrap();
+        mock_prover.assert_satisfied();
+    }
+}
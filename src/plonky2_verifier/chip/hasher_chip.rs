@@ -1,10 +1,20 @@
+use std::marker::PhantomData;
+
 use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
 use halo2wrong_maingate::AssignedValue;
 use plonky2::field::{goldilocks_field::GoldilocksField, types::Field};
+use plonky2::plonk::config::Hasher;
 
-use crate::plonky2_verifier::context::RegionCtx;
+use crate::plonky2_verifier::{
+    bn245_poseidon::plonky2_config::{Bn254Poseidon2Hash, Bn254PoseidonHash},
+    context::RegionCtx,
+    types::assigned::AssignedHashValues,
+};
 
-use super::goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig};
+use super::{
+    goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
+    native_chip::all_chip::OptionalChips,
+};
 
 use plonky2::hash::poseidon::SPONGE_WIDTH;
 
@@ -14,18 +24,167 @@ const RATE: usize = 8;
 #[derive(Debug, Clone)]
 pub struct AssignedState<F: PrimeField>(pub(super) [AssignedValue<F>; SPONGE_WIDTH]);
 
-/// `HasherChip` is basically responsible for contraining permutation part of
-/// transcript pipeline
+/// In-circuit counterpart of plonky2's `Hasher`: the digests of the Merkle trees of proofs made
+/// with this hasher. Digests are assigned as the words of `GenericHashOut::to_vec`.
+pub trait HasherChip<F: PrimeField>: Hasher<GoldilocksField> {
+    /// Optional chips to configure in circuits hashing with this hasher.
+    const REQUIRED_CHIPS: OptionalChips = OptionalChips::NONE;
+
+    fn hash_no_pad(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        inputs: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error>;
+
+    fn two_to_one(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        left: &AssignedHashValues<F>,
+        right: &AssignedHashValues<F>,
+    ) -> Result<AssignedHashValues<F>, Error>;
+
+    /// Inputs that fit in a digest are used as is, padded with zeros, like
+    /// `Hasher::hash_or_noop`.
+    fn hash_or_noop(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        inputs: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error> {
+        if inputs.len() * 8 > Self::HASH_SIZE {
+            return <Self as HasherChip<F>>::hash_no_pad(ctx, goldilocks_chip_config, inputs);
+        }
+        let goldilocks_chip = GoldilocksChip::new(goldilocks_chip_config);
+        let mut elements = inputs.to_vec();
+        while elements.len() < 4 {
+            elements.push(goldilocks_chip.assign_constant(ctx, GoldilocksField::ZERO)?);
+        }
+        Ok(AssignedHashValues {
+            elements: elements.try_into().unwrap(),
+        })
+    }
+}
+
+/// In-circuit counterpart of plonky2's `AlgebraicHasher`: a permutation over Goldilocks words,
+/// so that `SpongeChip` follows the challenger of the proofs made with this hasher.
+pub trait AlgebraicHasherChip<F: PrimeField>: HasherChip<F> {
+    fn permute(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        state: [AssignedValue<F>; SPONGE_WIDTH],
+    ) -> Result<[AssignedValue<F>; SPONGE_WIDTH], Error>;
+}
+
+/// `hash_no_pad` of an algebraic hasher, i.e. `hash_n_to_hash_no_pad` over its permutation
+pub fn sponge_hash_no_pad<F: PrimeField, H: AlgebraicHasherChip<F>>(
+    ctx: &mut RegionCtx<'_, F>,
+    goldilocks_chip_config: &GoldilocksChipConfig<F>,
+    inputs: &[AssignedValue<F>],
+) -> Result<AssignedHashValues<F>, Error> {
+    let mut sponge = SpongeChip::<F, H>::new(ctx, goldilocks_chip_config)?;
+    let outputs = sponge.hash(ctx, inputs.to_vec(), 4)?;
+    Ok(AssignedHashValues {
+        elements: outputs.try_into().unwrap(),
+    })
+}
+
+/// `two_to_one` of an algebraic hasher, i.e. `compress` over its permutation
+pub fn sponge_two_to_one<F: PrimeField, H: AlgebraicHasherChip<F>>(
+    ctx: &mut RegionCtx<'_, F>,
+    goldilocks_chip_config: &GoldilocksChipConfig<F>,
+    left: &AssignedHashValues<F>,
+    right: &AssignedHashValues<F>,
+) -> Result<AssignedHashValues<F>, Error> {
+    let mut sponge = SpongeChip::<F, H>::new(ctx, goldilocks_chip_config)?;
+    let inputs = left
+        .elements
+        .iter()
+        .chain(right.elements.iter())
+        .cloned()
+        .collect();
+    let outputs = sponge.permute(ctx, inputs, 4)?;
+    Ok(AssignedHashValues {
+        elements: outputs.try_into().unwrap(),
+    })
+}
+
+impl<F: PrimeField> HasherChip<F> for Bn254PoseidonHash {
+    fn hash_no_pad(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        inputs: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error> {
+        sponge_hash_no_pad::<F, Self>(ctx, goldilocks_chip_config, inputs)
+    }
+
+    fn two_to_one(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        left: &AssignedHashValues<F>,
+        right: &AssignedHashValues<F>,
+    ) -> Result<AssignedHashValues<F>, Error> {
+        sponge_two_to_one::<F, Self>(ctx, goldilocks_chip_config, left, right)
+    }
+}
+
+impl<F: PrimeField> AlgebraicHasherChip<F> for Bn254PoseidonHash {
+    fn permute(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        state: [AssignedValue<F>; SPONGE_WIDTH],
+    ) -> Result<[AssignedValue<F>; SPONGE_WIDTH], Error> {
+        let all_chip = GoldilocksChip::new(goldilocks_chip_config).all_chip();
+        all_chip.permute(ctx, state)
+    }
+}
+
+impl<F: PrimeField> HasherChip<F> for Bn254Poseidon2Hash {
+    const REQUIRED_CHIPS: OptionalChips = OptionalChips {
+        poseidon2: true,
+        ..OptionalChips::NONE
+    };
+
+    fn hash_no_pad(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        inputs: &[AssignedValue<F>],
+    ) -> Result<AssignedHashValues<F>, Error> {
+        sponge_hash_no_pad::<F, Self>(ctx, goldilocks_chip_config, inputs)
+    }
+
+    fn two_to_one(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        left: &AssignedHashValues<F>,
+        right: &AssignedHashValues<F>,
+    ) -> Result<AssignedHashValues<F>, Error> {
+        sponge_two_to_one::<F, Self>(ctx, goldilocks_chip_config, left, right)
+    }
+}
+
+impl<F: PrimeField> AlgebraicHasherChip<F> for Bn254Poseidon2Hash {
+    fn permute(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        state: [AssignedValue<F>; SPONGE_WIDTH],
+    ) -> Result<[AssignedValue<F>; SPONGE_WIDTH], Error> {
+        let all_chip = GoldilocksChip::new(goldilocks_chip_config).all_chip();
+        all_chip.permute_poseidon2(ctx, state)
+    }
+}
+
+/// `SpongeChip` is basically responsible for contraining permutation part of
+/// transcript pipeline, with the permutation of the plonky2 hasher `H`
 #[derive(Debug, Clone)]
-pub struct HasherChip<F: PrimeField> {
+pub struct SpongeChip<F: PrimeField, H: AlgebraicHasherChip<F>> {
     state: AssignedState<F>,
     absorbing: Vec<AssignedValue<F>>,
     output_buffer: Vec<AssignedValue<F>>,
     goldilocks_chip_config: GoldilocksChipConfig<F>,
+    _marker: PhantomData<H>,
 }
 
-impl<F: PrimeField> HasherChip<F> {
-    // Constructs new hasher chip with assigned initial state
+impl<F: PrimeField, H: AlgebraicHasherChip<F>> SpongeChip<F, H> {
+    // Constructs new sponge chip with assigned initial state
     pub fn new(
         ctx: &mut RegionCtx<'_, F>,
         goldilocks_chip_config: &GoldilocksChipConfig<F>,
@@ -42,12 +201,13 @@ impl<F: PrimeField> HasherChip<F> {
             absorbing: vec![],
             output_buffer: vec![],
             goldilocks_chip_config: goldilocks_chip_config.clone(),
+            _marker: PhantomData,
         })
     }
 
     /// Appends field elements to the absorbation line. It won't perform
     /// permutation here
-    pub fn update(
+    pub fn absorb(
         &mut self,
         _ctx: &mut RegionCtx<'_, F>,
         element: &AssignedValue<F>,
@@ -88,18 +248,17 @@ impl<F: PrimeField> HasherChip<F> {
     }
 }
 
-impl<F: PrimeField> HasherChip<F> {
+impl<F: PrimeField, H: AlgebraicHasherChip<F>> SpongeChip<F, H> {
     /// Construct main gate
     pub fn goldilocks_chip(&self) -> GoldilocksChip<F> {
         GoldilocksChip::new(&self.goldilocks_chip_config)
     }
 }
 
-impl<F: PrimeField> HasherChip<F> {
-    /// Constrains poseidon permutation while mutating the given state
+impl<F: PrimeField, H: AlgebraicHasherChip<F>> SpongeChip<F, H> {
+    /// Constrains the permutation of `H` while mutating the given state
     pub fn permutation(&mut self, ctx: &mut RegionCtx<'_, F>) -> Result<(), Error> {
-        let all_chip = self.goldilocks_chip().all_chip();
-        self.state.0 = all_chip.permute(ctx, self.state.0.clone())?;
+        self.state.0 = H::permute(ctx, &self.goldilocks_chip_config, self.state.0.clone())?;
         Ok(())
     }
 
@@ -172,6 +331,7 @@ impl<F: PrimeField> HasherChip<F> {
 
 #[cfg(test)]
 mod tests {
+    use std::marker::PhantomData;
 
     use halo2_proofs::{
         circuit::{floor_planner::V1, Layouter, Value},
@@ -181,11 +341,14 @@ mod tests {
     };
     use plonky2::{
         field::{goldilocks_field::GoldilocksField, types::Sample},
-        hash::hashing::PlonkyPermutation,
+        hash::{hash_types::HashOut, hashing::PlonkyPermutation, poseidon::PoseidonHash},
+        plonk::config::Hasher,
     };
 
     use crate::plonky2_verifier::{
-        bn245_poseidon::plonky2_config::Bn254PoseidonPermutation,
+        bn245_poseidon::plonky2_config::{
+            Bn254Poseidon2Hash, Bn254PoseidonHash, Bn254PoseidonPermutation,
+        },
         chip::{
             goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
             native_chip::{
@@ -194,9 +357,10 @@ mod tests {
             },
         },
         context::RegionCtx,
+        types::assigned::AssignedHashValues,
     };
 
-    use super::HasherChip;
+    use super::{HasherChip, SpongeChip};
 
     #[derive(Clone, Default)]
     pub struct TestCircuit {
@@ -207,6 +371,7 @@ mod tests {
     impl Circuit<Fr> for TestCircuit {
         type Config = GoldilocksChipConfig<Fr>;
         type FloorPlanner = V1;
+        type Params = ();
 
         fn without_witnesses(&self) -> Self {
             Self::default()
@@ -239,7 +404,7 @@ mod tests {
                         .map(|x| goldilocks_chip.assign_value(ctx, Value::known(*x)))
                         .collect::<Result<Vec<_>, Error>>()?;
 
-                    let mut hasher_chip = HasherChip::<Fr>::new(ctx, &config)?;
+                    let mut hasher_chip = SpongeChip::<Fr, Bn254PoseidonHash>::new(ctx, &config)?;
                     hasher_chip.state.0 = input_assigned.try_into().unwrap();
                     hasher_chip.permutation(ctx)?;
                     hasher_chip
@@ -274,4 +439,115 @@ mod tests {
         let mock_prover = MockProver::run(DEGREE, &circuit, vec![instance.clone()]).unwrap();
         mock_prover.assert_satisfied();
     }
+
+    /// Checks `hash_no_pad`, `hash_or_noop` and `two_to_one` of `H` against plonky2
+    #[derive(Clone)]
+    struct MerkleHashCircuit<H: HasherChip<Fr>> {
+        long_input: Vec<GoldilocksField>,
+        short_input: Vec<GoldilocksField>,
+        left: HashOut<GoldilocksField>,
+        right: HashOut<GoldilocksField>,
+        _marker: PhantomData<H>,
+    }
+
+    impl<H: HasherChip<Fr> + Hasher<GoldilocksField, Hash = HashOut<GoldilocksField>>>
+        MerkleHashCircuit<H>
+    {
+        fn rand() -> Self {
+            Self {
+                long_input: GoldilocksField::rand_vec(10),
+                short_input: GoldilocksField::rand_vec(3),
+                left: HashOut::rand(),
+                right: HashOut::rand(),
+                _marker: PhantomData,
+            }
+        }
+
+        fn expected_outputs(&self) -> [HashOut<GoldilocksField>; 3] {
+            [
+                H::hash_no_pad(&self.long_input),
+                H::hash_or_noop(&self.short_input),
+                H::two_to_one(self.left, self.right),
+            ]
+        }
+    }
+
+    impl<H: HasherChip<Fr> + Hasher<GoldilocksField, Hash = HashOut<GoldilocksField>>> Circuit<Fr>
+        for MerkleHashCircuit<H>
+    {
+        type Config = GoldilocksChipConfig<Fr>;
+        type FloorPlanner = V1;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let all_chip = AllChipConfig::<Fr>::configure_with(meta, H::REQUIRED_CHIPS);
+            GoldilocksChip::configure(&all_chip)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let goldilocks_chip = GoldilocksChip::new(&config);
+            goldilocks_chip.load_table(&mut layouter)?;
+            layouter.assign_region(
+                || "merkle hashes",
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let mut assign = |values: &[GoldilocksField]| {
+                        values
+                            .iter()
+                            .map(|x| {
+                                goldilocks_chip
+                                    .assign_value(ctx, Value::known(goldilocks_to_fe(*x)))
+                            })
+                            .collect::<Result<Vec<_>, Error>>()
+                    };
+                    let long_input = assign(&self.long_input)?;
+                    let short_input = assign(&self.short_input)?;
+                    let left = AssignedHashValues {
+                        elements: assign(&self.left.elements)?.try_into().unwrap(),
+                    };
+                    let right = AssignedHashValues {
+                        elements: assign(&self.right.elements)?.try_into().unwrap(),
+                    };
+
+                    let outputs = [
+                        <H as HasherChip<Fr>>::hash_no_pad(ctx, &config, &long_input)?,
+                        <H as HasherChip<Fr>>::hash_or_noop(ctx, &config, &short_input)?,
+                        <H as HasherChip<Fr>>::two_to_one(ctx, &config, &left, &right)?,
+                    ];
+                    for (output, expected) in outputs.iter().zip(self.expected_outputs()) {
+                        for (x, e) in output.elements.iter().zip(expected.elements) {
+                            x.value().map(|x| assert_eq!(fe_to_goldilocks(*x), e));
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_merkle_hashes_mock() {
+        const DEGREE: u32 = 17;
+        let circuit = MerkleHashCircuit::<PoseidonHash>::rand();
+        MockProver::run(DEGREE, &circuit, vec![vec![]])
+            .unwrap()
+            .assert_satisfied();
+        let circuit = MerkleHashCircuit::<Bn254PoseidonHash>::rand();
+        MockProver::run(DEGREE, &circuit, vec![vec![]])
+            .unwrap()
+            .assert_satisfied();
+        let circuit = MerkleHashCircuit::<Bn254Poseidon2Hash>::rand();
+        MockProver::run(DEGREE, &circuit, vec![vec![]])
+            .unwrap()
+            .assert_satisfied();
+    }
 }
@@ -6,7 +6,7 @@ use itertools::Itertools;
 
 use crate::plonky2_verifier::{
     context::RegionCtx,
-    types::assigned::{AssignedMerkleCapValues, AssignedMerkleProofValues},
+    types::assigned::{AssignedHashValues, AssignedMerkleCapValues, AssignedMerkleProofValues},
 };
 
 use super::{
@@ -15,12 +15,12 @@ use super::{
     vector_chip::VectorChip,
 };
 
-pub struct MerkleProofChip<F: PrimeField> {
+pub struct MerkleProofChip<F: PrimeField, H: HasherChip<F>> {
     goldilocks_chip_config: GoldilocksChipConfig<F>,
-    _marker: PhantomData<F>,
+    _marker: PhantomData<(F, H)>,
 }
 
-impl<F: PrimeField> MerkleProofChip<F> {
+impl<F: PrimeField, H: HasherChip<F>> MerkleProofChip<F, H> {
     pub fn new(goldilocks_chip_config: &GoldilocksChipConfig<F>) -> Self {
         Self {
             goldilocks_chip_config: goldilocks_chip_config.clone(),
@@ -32,10 +32,6 @@ impl<F: PrimeField> MerkleProofChip<F> {
         GoldilocksChip::new(&self.goldilocks_chip_config)
     }
 
-    fn hasher(&self, ctx: &mut RegionCtx<'_, F>) -> Result<HasherChip<F>, Error> {
-        HasherChip::new(ctx, &self.goldilocks_chip_config)
-    }
-
     pub fn verify_merkle_proof_to_cap_with_cap_index(
         &self,
         ctx: &mut RegionCtx<'_, F>,
@@ -45,29 +41,25 @@ impl<F: PrimeField> MerkleProofChip<F> {
         merkle_cap: &AssignedMerkleCapValues<F>,
         proof: &AssignedMerkleProofValues<F>,
     ) -> Result<(), Error> {
-        let mut hasher = self.hasher(ctx)?;
         let goldilocks_chip = self.goldilocks_chip();
 
-        let mut state;
-        if leaf_data.len() <= 4 {
-            state = leaf_data.clone();
-        } else {
-            state = hasher.hash(ctx, leaf_data.clone(), 4)?;
-        }
+        let mut state = H::hash_or_noop(ctx, &self.goldilocks_chip_config, leaf_data)?;
 
         for (bit, sibling) in leaf_index_bits.iter().zip(proof.siblings.iter()) {
-            let mut hasher = self.hasher(ctx)?;
-            let mut inputs = vec![];
-            for i in 0..4 {
-                let left = goldilocks_chip.select(ctx, &sibling.elements[i], &state[i], bit)?;
-                inputs.push(left);
-            }
-
+            let mut left = vec![];
+            let mut right = vec![];
             for i in 0..4 {
-                let right = goldilocks_chip.select(ctx, &state[i], &sibling.elements[i], bit)?;
-                inputs.push(right);
+                let (state_i, sibling_i) = (&state.elements[i], &sibling.elements[i]);
+                left.push(goldilocks_chip.select(ctx, sibling_i, state_i, bit)?);
+                right.push(goldilocks_chip.select(ctx, state_i, sibling_i, bit)?);
             }
-            state = hasher.permute(ctx, inputs, 4)?;
+            let left = AssignedHashValues {
+                elements: left.try_into().unwrap(),
+            };
+            let right = AssignedHashValues {
+                elements: right.try_into().unwrap(),
+            };
+            state = H::two_to_one(ctx, &self.goldilocks_chip_config, &left, &right)?;
         }
 
         for i in 0..4 {
@@ -80,7 +72,7 @@ impl<F: PrimeField> MerkleProofChip<F> {
                     .collect_vec(),
             );
             let cap_i = vector_chip.access(ctx, &cap_index)?;
-            goldilocks_chip.assert_equal(ctx, &cap_i, &state[i])?;
+            goldilocks_chip.assert_equal(ctx, &cap_i, &state.elements[i])?;
         }
 
         Ok(())
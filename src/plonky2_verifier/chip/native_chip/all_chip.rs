@@ -5,30 +5,78 @@ use halo2_proofs::{
 };
 use plonky2::hash::poseidon::SPONGE_WIDTH;
 
-use crate::plonky2_verifier::{bn245_poseidon::constants::T_BN254_POSEIDON, context::RegionCtx};
+use crate::plonky2_verifier::context::RegionCtx;
 
 use super::{
     arithmetic_chip::{ArithmeticChip, ArithmeticChipConfig},
+    keccak_chip::{KeccakChip, KeccakChipConfig},
+    poseidon2_bn254_chip::{Poseidon2Bn254Chip, Poseidon2Bn254ChipConfig},
     poseidon_bn254_chip::{PoseidonBn254Chip, PoseidonBn254ChipConfig},
 };
 
+/// Chips configured on demand, as their columns, gates and tables change the halo2 keys of every
+/// circuit they are configured in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OptionalChips {
+    /// `KeccakChip` and its lookup table.
+    pub keccak: bool,
+    /// `Poseidon2Bn254Chip`.
+    pub poseidon2: bool,
+}
+
+impl OptionalChips {
+    pub const NONE: Self = Self {
+        keccak: false,
+        poseidon2: false,
+    };
+    pub const ALL: Self = Self {
+        keccak: true,
+        poseidon2: true,
+    };
+
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            keccak: self.keccak || other.keccak,
+            poseidon2: self.poseidon2 || other.poseidon2,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct AllChipConfig<F: PrimeField> {
     pub arithmetic_config: ArithmeticChipConfig<F>,
     pub poseidon_config: PoseidonBn254ChipConfig<F>,
+    pub poseidon2_config: Option<Poseidon2Bn254ChipConfig<F>>,
+    pub keccak_config: Option<KeccakChipConfig<F>>,
 }
 
 impl<F: PrimeField> AllChipConfig<F> {
     pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        Self::configure_with(meta, OptionalChips::NONE)
+    }
+
+    pub fn configure_with(meta: &mut ConstraintSystem<F>, chips: OptionalChips) -> Self {
         let arithmetic_config = ArithmeticChipConfig::configure(meta);
         let poseidon_config = PoseidonBn254ChipConfig::configure(meta);
+        let poseidon2_config = chips
+            .poseidon2
+            .then(|| Poseidon2Bn254ChipConfig::configure(meta));
+        let keccak_config = chips.keccak.then(|| KeccakChipConfig::configure(meta));
         Self {
             arithmetic_config,
             poseidon_config,
+            poseidon2_config,
+            keccak_config,
         }
     }
 }
 
+// error of a chip used by a circuit configured without it
+fn not_configured(chip: &str) -> Error {
+    log::error!("{chip} is used but not configured, see `OptionalChips`");
+    Error::Synthesis
+}
+
 #[derive(Clone, Debug)]
 pub struct AllChip<F: PrimeField> {
     config: AllChipConfig<F>,
@@ -49,13 +97,53 @@ impl<F: PrimeField> AllChip<F> {
         PoseidonBn254Chip::new(&self.config.poseidon_config)
     }
 
+    pub fn poseidon2_chip(&self) -> Result<Poseidon2Bn254Chip<F>, Error> {
+        let config = self.config.poseidon2_config.as_ref();
+        let config = config.ok_or_else(|| not_configured("Poseidon2Bn254Chip"))?;
+        Ok(Poseidon2Bn254Chip::new(config))
+    }
+
+    pub fn keccak_chip(&self) -> Result<KeccakChip<F>, Error> {
+        let config = self.config.keccak_config.as_ref();
+        let config = config.ok_or_else(|| not_configured("KeccakChip"))?;
+        Ok(KeccakChip::new(config, &self.arithmetic_chip()))
+    }
+
     pub fn permute(
         &self,
         ctx: &mut RegionCtx<'_, F>,
         state: [AssignedCell<F, F>; SPONGE_WIDTH],
     ) -> Result<[AssignedCell<F, F>; SPONGE_WIDTH], halo2_proofs::plonk::Error> {
-        let arithmetic_chip = self.arithmetic_chip();
         let poseidon_chip = self.poseidon_chip();
+        self.permute_packed(ctx, state, |ctx, state| {
+            poseidon_chip.apply_permute(ctx, state)
+        })
+    }
+
+    /// `permute` with Poseidon2, the permutation of `Bn254Poseidon2GoldilocksConfig`.
+    pub fn permute_poseidon2(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: [AssignedCell<F, F>; SPONGE_WIDTH],
+    ) -> Result<[AssignedCell<F, F>; SPONGE_WIDTH], halo2_proofs::plonk::Error> {
+        let poseidon2_chip = self.poseidon2_chip()?;
+        self.permute_packed(ctx, state, |ctx, state| {
+            poseidon2_chip.apply_permute(ctx, state)
+        })
+    }
+
+    // packs the goldilocks state into `T` field elements, applies `permutation` to them and
+    // unpacks the first 12 goldilocks elements of the output
+    fn permute_packed<const T: usize>(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: [AssignedCell<F, F>; SPONGE_WIDTH],
+        permutation: impl FnOnce(
+            &mut RegionCtx<'_, F>,
+            [AssignedCell<F, F>; T],
+        ) -> Result<[AssignedCell<F, F>; T], Error>,
+    ) -> Result<[AssignedCell<F, F>; SPONGE_WIDTH], halo2_proofs::plonk::Error> {
+        let arithmetic_chip = self.arithmetic_chip();
         let zero = arithmetic_chip.assign_constant(ctx, F::ZERO)?;
         let offset_start = ctx.offset();
 
@@ -67,12 +155,12 @@ impl<F: PrimeField> AllChip<F> {
                 Ok(composed)
             })
             .collect::<Result<Vec<_>, Error>>()?;
-        encoded_state.resize(T_BN254_POSEIDON, zero.clone());
+        encoded_state.resize(T, zero.clone());
         let offset_end_compose = ctx.offset();
 
         // aplly permutation
         ctx.set_offset(offset_start);
-        let output_state = poseidon_chip.apply_permute(ctx, encoded_state.try_into().unwrap())?;
+        let output_state = permutation(ctx, encoded_state.try_into().unwrap())?;
         let offset_end_permute = ctx.offset();
 
         // decompose output
@@ -92,7 +180,11 @@ impl<F: PrimeField> AllChip<F> {
         &self,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), halo2_proofs::plonk::Error> {
-        self.arithmetic_chip().load_table(layouter)
+        self.arithmetic_chip().load_table(layouter)?;
+        if self.config.keccak_config.is_some() {
+            self.keccak_chip()?.load_table(layouter)?;
+        }
+        Ok(())
     }
 }
 
@@ -107,7 +199,7 @@ mod tests {
 
     use crate::plonky2_verifier::chip::native_chip::test_utils::test_verify_on_contract;
 
-    use super::AllChipConfig;
+    use super::{AllChipConfig, OptionalChips};
 
     #[derive(Clone, Default)]
     pub struct TestCircuit;
@@ -115,13 +207,14 @@ mod tests {
     impl Circuit<Fr> for TestCircuit {
         type Config = AllChipConfig<Fr>;
         type FloorPlanner = V1;
+        type Params = ();
 
         fn without_witnesses(&self) -> Self {
             Self::default()
         }
 
         fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
-            AllChipConfig::<Fr>::configure(meta)
+            AllChipConfig::<Fr>::configure_with(meta, OptionalChips::ALL)
         }
 
         fn synthesize(
@@ -514,6 +514,7 @@ fn assign_q_and_r<F: PrimeField>(
     q: Value<F>,
     r: Value<F>,
 ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+    ctx.record_lookups(Q_LIMBS + 4);
     let q_limb = q.map(|x| decompose(x, Q_LIMBS, 16)).transpose_vec(Q_LIMBS);
     let r_limb = r.map(|x| decompose(x, 4, 16)).transpose_vec(4);
     config
@@ -552,6 +553,7 @@ mod tests {
     impl Circuit<Fr> for TestCircuit {
         type Config = ArithmeticChipConfig<Fr>;
         type FloorPlanner = V1;
+        type Params = ();
 
         fn without_witnesses(&self) -> Self {
             Self::default()
@@ -0,0 +1,775 @@
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    halo2curves::ff::PrimeField,
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+use itertools::Itertools;
+
+use crate::plonky2_verifier::context::RegionCtx;
+
+use super::{arithmetic_chip::ArithmeticChip, utils::fe_to_u64};
+
+/// Number of lookups laid out side by side in a row.
+const LOOKUP_GROUPS: usize = 4;
+
+pub const NIBBLES_PER_LANE: usize = 16;
+pub const NUM_LANES: usize = 25;
+/// Keccak-256 absorbs 136 bytes per permutation.
+pub const RATE_LANES: usize = 17;
+const RATE_NIBBLES: usize = RATE_LANES * NIBBLES_PER_LANE;
+/// Keccak-256 outputs 32 bytes.
+pub const OUTPUT_NIBBLES: usize = 64;
+
+const NUM_ROUNDS: usize = 24;
+
+const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of rho, indexed by `x + 5 * y`.
+const RHO_OFFSETS: [usize; NUM_LANES] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// A 64-bit lane as nibbles, least significant first.
+pub type Lane<F> = [AssignedCell<F, F>; NIBBLES_PER_LANE];
+
+/// Kinds of rows of the lookup table, each row being `(tag, a, b, c, out)` over nibbles.
+/// Tag 0 is the all-zero row, which unused lookup groups fall back to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tag {
+    /// `(a, 0, 0, 0)`: range check of a nibble
+    Nibble = 1,
+    /// `(a, b, 0, a ^ b)`
+    Xor = 2,
+    /// `(a, b, c, a ^ b ^ c)`
+    Xor3 = 3,
+    /// `(a, b, c, a ^ (!b & c))`
+    Chi = 4,
+    /// `(a, a << s, a >> (4 - s), 0)` for rotations by `s = 1, 2, 3`
+    Rotate1 = 5,
+    Rotate2 = 6,
+    Rotate3 = 7,
+}
+
+impl Tag {
+    const ALL: [Tag; 7] = [
+        Tag::Nibble,
+        Tag::Xor,
+        Tag::Xor3,
+        Tag::Chi,
+        Tag::Rotate1,
+        Tag::Rotate2,
+        Tag::Rotate3,
+    ];
+
+    fn num_inputs(self) -> usize {
+        match self {
+            Tag::Nibble | Tag::Rotate1 | Tag::Rotate2 | Tag::Rotate3 => 1,
+            Tag::Xor => 2,
+            Tag::Xor3 | Tag::Chi => 3,
+        }
+    }
+
+    fn rotate(shift: usize) -> Self {
+        match shift {
+            1 => Tag::Rotate1,
+            2 => Tag::Rotate2,
+            3 => Tag::Rotate3,
+            _ => unreachable!("nibble rotations are by 1, 2 or 3 bits"),
+        }
+    }
+
+    /// `(a, b, c, out)` of the table row with the given inputs
+    fn row(self, inputs: &[u64]) -> [u64; 4] {
+        let input = |i: usize| inputs.get(i).copied().unwrap_or(0);
+        let (a, b, c) = (input(0), input(1), input(2));
+        match self {
+            Tag::Nibble => [a, 0, 0, 0],
+            Tag::Xor => [a, b, 0, a ^ b],
+            Tag::Xor3 => [a, b, c, a ^ b ^ c],
+            Tag::Chi => [a, b, c, a ^ (!b & c & 0xf)],
+            Tag::Rotate1 | Tag::Rotate2 | Tag::Rotate3 => {
+                let shift = self as u64 - Tag::Rotate1 as u64 + 1;
+                [a, (a << shift) & 0xf, a >> (4 - shift), 0]
+            }
+        }
+    }
+
+    fn table_rows(self) -> Vec<[u64; 4]> {
+        (0..1u64 << (4 * self.num_inputs()))
+            .map(|inputs| {
+                let inputs = (0..self.num_inputs())
+                    .map(|i| (inputs >> (4 * i)) & 0xf)
+                    .collect_vec();
+                self.row(&inputs)
+            })
+            .collect()
+    }
+}
+
+/// Keccak-f[1600] over nibbles. Every bitwise operation is a lookup into a single tagged table,
+/// and lanes are packed from and to integers with the "compose" gate.
+#[derive(Clone, Debug)]
+pub struct KeccakChipConfig<F: PrimeField> {
+    pub a: [Column<Advice>; LOOKUP_GROUPS],
+    pub b: [Column<Advice>; LOOKUP_GROUPS],
+    pub c: [Column<Advice>; LOOKUP_GROUPS],
+    pub out: [Column<Advice>; LOOKUP_GROUPS],
+    pub tag: [Column<Fixed>; LOOKUP_GROUPS],
+    pub acc: Column<Advice>,
+    pub table: [TableColumn; 5],
+    pub s_lookup: Selector,       // lookup of every group into the table
+    pub s_compose: Selector,      // acc = sum_g a_g * 16^g + 16^LOOKUP_GROUPS * acc_next
+    pub s_compose_last: Selector, // acc = sum_g a_g * 16^g
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> KeccakChipConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let a = [(); LOOKUP_GROUPS].map(|_| meta.advice_column());
+        let b = [(); LOOKUP_GROUPS].map(|_| meta.advice_column());
+        let c = [(); LOOKUP_GROUPS].map(|_| meta.advice_column());
+        let out = [(); LOOKUP_GROUPS].map(|_| meta.advice_column());
+        let tag = [(); LOOKUP_GROUPS].map(|_| meta.fixed_column());
+        let acc = meta.advice_column();
+        let table = [(); 5].map(|_| meta.lookup_table_column());
+        let s_lookup = meta.complex_selector();
+        let s_compose = meta.selector();
+        let s_compose_last = meta.selector();
+
+        a.iter()
+            .chain(b.iter())
+            .chain(c.iter())
+            .chain(out.iter())
+            .chain([acc].iter())
+            .for_each(|column| meta.enable_equality(*column));
+
+        for g in 0..LOOKUP_GROUPS {
+            meta.lookup("keccak table", |meta| {
+                let s = meta.query_selector(s_lookup);
+                let inputs = [
+                    meta.query_fixed(tag[g], Rotation::cur()),
+                    meta.query_advice(a[g], Rotation::cur()),
+                    meta.query_advice(b[g], Rotation::cur()),
+                    meta.query_advice(c[g], Rotation::cur()),
+                    meta.query_advice(out[g], Rotation::cur()),
+                ];
+                inputs
+                    .into_iter()
+                    .zip(table)
+                    .map(|(input, column)| (s.clone() * input, column))
+                    .collect()
+            });
+        }
+
+        meta.create_gate("compose", |meta| {
+            let s_compose = meta.query_selector(s_compose);
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let shift = Expression::Constant(F::from(1 << (4 * LOOKUP_GROUPS)));
+            vec![s_compose * (acc_cur - composed(meta, &a) - shift * acc_next)]
+        });
+        meta.create_gate("compose last", |meta| {
+            let s_compose_last = meta.query_selector(s_compose_last);
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            vec![s_compose_last * (acc_cur - composed(meta, &a))]
+        });
+
+        Self {
+            a,
+            b,
+            c,
+            out,
+            tag,
+            acc,
+            table,
+            s_lookup,
+            s_compose,
+            s_compose_last,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Integer composed by the nibbles of a row, least significant first
+fn composed<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    a: &[Column<Advice>; LOOKUP_GROUPS],
+) -> Expression<F> {
+    (0..LOOKUP_GROUPS).fold(Expression::Constant(F::ZERO), |acc, g| {
+        acc + meta.query_advice(a[g], Rotation::cur()) * Expression::Constant(F::from(1 << (4 * g)))
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakChip<F: PrimeField> {
+    config: KeccakChipConfig<F>,
+    arithmetic_chip: ArithmeticChip<F>,
+}
+
+impl<F: PrimeField> KeccakChip<F> {
+    /// `arithmetic_chip` provides the constants, e.g. padding and round constants.
+    pub fn new(config: &KeccakChipConfig<F>, arithmetic_chip: &ArithmeticChip<F>) -> Self {
+        Self {
+            config: config.clone(),
+            arithmetic_chip: arithmetic_chip.clone(),
+        }
+    }
+
+    fn assign_constant(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        nibble: u64,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.arithmetic_chip.assign_constant(ctx, F::from(nibble))
+    }
+
+    /// Assigns up to `LOOKUP_GROUPS` table rows in the current row, filling the remaining
+    /// groups with the all-zero row
+    fn assign_lookup_row(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        rows: &[(Tag, [Value<F>; 4])],
+    ) -> Result<Vec<[AssignedCell<F, F>; 4]>, Error> {
+        assert!(rows.len() <= LOOKUP_GROUPS);
+        ctx.enable(self.config.s_lookup)?;
+        ctx.record_lookups(rows.len());
+        let mut assigned = vec![];
+        for g in 0..LOOKUP_GROUPS {
+            let (tag, values) = rows
+                .get(g)
+                .map(|(tag, values)| (*tag as u64, *values))
+                .unwrap_or((0, [Value::known(F::ZERO); 4]));
+            ctx.assign_fixed(|| "tag", self.config.tag[g], F::from(tag))?;
+            let columns = [
+                self.config.a[g],
+                self.config.b[g],
+                self.config.c[g],
+                self.config.out[g],
+            ];
+            let cells = columns
+                .iter()
+                .zip(values)
+                .map(|(column, value)| ctx.assign_advice(|| "keccak", *column, value))
+                .collect::<Result<Vec<_>, Error>>()?;
+            if g < rows.len() {
+                assigned.push(cells.try_into().unwrap());
+            }
+        }
+        Ok(assigned)
+    }
+
+    /// Looks up `tag` rows with the given inputs, returning the assigned `(a, b, c, out)`
+    fn lookup(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        tag: Tag,
+        inputs: &[Vec<&AssignedCell<F, F>>],
+    ) -> Result<Vec<[AssignedCell<F, F>; 4]>, Error> {
+        let mut assigned = vec![];
+        for chunk in inputs.chunks(LOOKUP_GROUPS) {
+            let rows = chunk
+                .iter()
+                .map(|op| {
+                    assert_eq!(op.len(), tag.num_inputs());
+                    let values: Value<Vec<u64>> = op
+                        .iter()
+                        .map(|x| x.value().map(|x| fe_to_u64(*x)))
+                        .collect();
+                    let row = values
+                        .map(|values| tag.row(&values).map(F::from).to_vec())
+                        .transpose_vec(4);
+                    (tag, row.try_into().unwrap())
+                })
+                .collect_vec();
+            let rows_assigned = self.assign_lookup_row(ctx, &rows)?;
+            ctx.next();
+            for (op, row_assigned) in chunk.iter().zip(rows_assigned.iter()) {
+                for (input, cell) in op.iter().zip(row_assigned.iter()) {
+                    ctx.constrain_equal(input.cell(), cell.cell())?;
+                }
+            }
+            assigned.extend(rows_assigned);
+        }
+        Ok(assigned)
+    }
+
+    /// Lays out the nibbles of an integer, least significant first, and its accumulators
+    fn assign_composition(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        nibbles: &[Value<F>],
+    ) -> Result<(Vec<AssignedCell<F, F>>, AssignedCell<F, F>), Error> {
+        let chunks = nibbles.chunks(LOOKUP_GROUPS).collect_vec();
+        let shift = F::from(1 << (4 * LOOKUP_GROUPS));
+        let mut accs = vec![Value::known(F::ZERO); chunks.len() + 1];
+        for (i, chunk) in chunks.iter().enumerate().rev() {
+            let composed = chunk
+                .iter()
+                .enumerate()
+                .fold(Value::known(F::ZERO), |acc, (g, nibble)| {
+                    acc + *nibble * Value::known(F::from(1 << (4 * g)))
+                });
+            accs[i] = composed + accs[i + 1] * Value::known(shift);
+        }
+
+        let mut assigned_nibbles = vec![];
+        let mut assigned_acc = None;
+        for (i, chunk) in chunks.iter().enumerate() {
+            if i + 1 < chunks.len() {
+                ctx.enable(self.config.s_compose)?;
+            } else {
+                ctx.enable(self.config.s_compose_last)?;
+            }
+            let rows = chunk
+                .iter()
+                .map(|nibble| {
+                    let zero = Value::known(F::ZERO);
+                    (Tag::Nibble, [*nibble, zero, zero, zero])
+                })
+                .collect_vec();
+            let rows_assigned = self.assign_lookup_row(ctx, &rows)?;
+            let acc = ctx.assign_advice(|| "acc", self.config.acc, accs[i])?;
+            ctx.next();
+            assigned_nibbles.extend(rows_assigned.into_iter().map(|[a, _, _, _]| a));
+            assigned_acc.get_or_insert(acc);
+        }
+        Ok((assigned_nibbles, assigned_acc.unwrap()))
+    }
+
+    /// Constrains `nibbles` to be nibbles and returns the integer they compose, least
+    /// significant first
+    pub fn compose(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        nibbles: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let values = nibbles.iter().map(|x| x.value().cloned()).collect_vec();
+        let (assigned_nibbles, composed) = self.assign_composition(ctx, &values)?;
+        for (nibble, assigned) in nibbles.iter().zip(assigned_nibbles.iter()) {
+            ctx.constrain_equal(nibble.cell(), assigned.cell())?;
+        }
+        Ok(composed)
+    }
+
+    /// Decomposes `x` into `num_nibbles` nibbles, least significant first, which constrains
+    /// `x < 16^num_nibbles`
+    pub fn decompose(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        x: &AssignedCell<F, F>,
+        num_nibbles: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        assert!(num_nibbles <= NIBBLES_PER_LANE);
+        let values = x
+            .value()
+            .map(|x| {
+                let x = fe_to_u64(*x);
+                (0..num_nibbles)
+                    .map(|i| F::from((x >> (4 * i)) & 0xf))
+                    .collect_vec()
+            })
+            .transpose_vec(num_nibbles);
+        let (nibbles, composed) = self.assign_composition(ctx, &values)?;
+        ctx.constrain_equal(x.cell(), composed.cell())?;
+        Ok(nibbles)
+    }
+
+    fn xor(&self, ctx: &mut RegionCtx<'_, F>, lanes: &[&Lane<F>]) -> Result<Lane<F>, Error> {
+        let tag = match lanes.len() {
+            2 => Tag::Xor,
+            3 => Tag::Xor3,
+            _ => unreachable!("lanes are xored two or three at a time"),
+        };
+        let inputs = (0..NIBBLES_PER_LANE)
+            .map(|i| lanes.iter().map(|lane| &lane[i]).collect_vec())
+            .collect_vec();
+        let outputs = self.lookup(ctx, tag, &inputs)?;
+        Ok(outputs
+            .into_iter()
+            .map(|[_, _, _, out]| out)
+            .collect_vec()
+            .try_into()
+            .unwrap())
+    }
+
+    fn xor_constant(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        lane: &Lane<F>,
+        constant: u64,
+    ) -> Result<Lane<F>, Error> {
+        let constants = (0..NIBBLES_PER_LANE)
+            .map(|i| (constant >> (4 * i)) & 0xf)
+            .map(|nibble| (nibble != 0).then(|| self.assign_constant(ctx, nibble)))
+            .map(Option::transpose)
+            .collect::<Result<Vec<_>, Error>>()?;
+        let inputs = lane
+            .iter()
+            .zip(constants.iter())
+            .filter_map(|(nibble, constant)| constant.as_ref().map(|c| vec![nibble, c]))
+            .collect_vec();
+        let mut outputs = self.lookup(ctx, Tag::Xor, &inputs)?.into_iter();
+        let mut result = lane.clone();
+        for (nibble, constant) in result.iter_mut().zip(constants.iter()) {
+            if constant.is_some() {
+                let [_, _, _, out] = outputs.next().unwrap();
+                *nibble = out;
+            }
+        }
+        Ok(result)
+    }
+
+    fn rotate_left(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        lane: &Lane<F>,
+        offset: usize,
+    ) -> Result<Lane<F>, Error> {
+        let (nibble_offset, shift) = (offset / 4, offset % 4);
+        let rotated: Lane<F> = (0..NIBBLES_PER_LANE)
+            .map(|i| lane[(i + NIBBLES_PER_LANE - nibble_offset) % NIBBLES_PER_LANE].clone())
+            .collect_vec()
+            .try_into()
+            .unwrap();
+        if shift == 0 {
+            return Ok(rotated);
+        }
+
+        // nibble `i` gets its high bits from nibble `i` and its low bits from nibble `i - 1`
+        let inputs = rotated.iter().map(|nibble| vec![nibble]).collect_vec();
+        let split = self.lookup(ctx, Tag::rotate(shift), &inputs)?;
+        let inputs = (0..NIBBLES_PER_LANE)
+            .map(|i| {
+                let high = &split[i][1];
+                let low = &split[(i + NIBBLES_PER_LANE - 1) % NIBBLES_PER_LANE][2];
+                vec![high, low]
+            })
+            .collect_vec();
+        let outputs = self.lookup(ctx, Tag::Xor, &inputs)?;
+        Ok(outputs
+            .into_iter()
+            .map(|[_, _, _, out]| out)
+            .collect_vec()
+            .try_into()
+            .unwrap())
+    }
+
+    fn chi(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        a: &Lane<F>,
+        b: &Lane<F>,
+        c: &Lane<F>,
+    ) -> Result<Lane<F>, Error> {
+        let inputs = (0..NIBBLES_PER_LANE)
+            .map(|i| vec![&a[i], &b[i], &c[i]])
+            .collect_vec();
+        let outputs = self.lookup(ctx, Tag::Chi, &inputs)?;
+        Ok(outputs
+            .into_iter()
+            .map(|[_, _, _, out]| out)
+            .collect_vec()
+            .try_into()
+            .unwrap())
+    }
+
+    /// Constrains Keccak-f[1600] of `state`, whose lanes are indexed by `x + 5 * y`
+    pub fn keccak_f(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        mut state: [Lane<F>; NUM_LANES],
+    ) -> Result<[Lane<F>; NUM_LANES], Error> {
+        for round_constant in ROUND_CONSTANTS.iter() {
+            // theta
+            let parities = (0..5)
+                .map(|x| {
+                    let parity = self.xor(ctx, &[&state[x], &state[x + 5], &state[x + 10]])?;
+                    self.xor(ctx, &[&parity, &state[x + 15], &state[x + 20]])
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let rotated_parities = parities
+                .iter()
+                .map(|parity| self.rotate_left(ctx, parity, 1))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let state_theta = (0..NUM_LANES)
+                .map(|i| {
+                    let x = i % 5;
+                    self.xor(
+                        ctx,
+                        &[
+                            &state[i],
+                            &parities[(x + 4) % 5],
+                            &rotated_parities[(x + 1) % 5],
+                        ],
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            // rho and pi
+            let mut state_pi = vec![None; NUM_LANES];
+            for (i, lane) in state_theta.iter().enumerate() {
+                let (x, y) = (i % 5, i / 5);
+                let rotated = self.rotate_left(ctx, lane, RHO_OFFSETS[i])?;
+                state_pi[y + 5 * ((2 * x + 3 * y) % 5)] = Some(rotated);
+            }
+            let state_pi = state_pi.into_iter().map(Option::unwrap).collect_vec();
+
+            // chi
+            let state_chi = (0..NUM_LANES)
+                .map(|i| {
+                    let (x, y) = (i % 5, i / 5);
+                    self.chi(
+                        ctx,
+                        &state_pi[i],
+                        &state_pi[(x + 1) % 5 + 5 * y],
+                        &state_pi[(x + 2) % 5 + 5 * y],
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            state = state_chi.try_into().unwrap();
+
+            // iota
+            state[0] = self.xor_constant(ctx, &state[0], *round_constant)?;
+        }
+        Ok(state)
+    }
+
+    /// Constrains Keccak-256 of a byte string given as nibbles, the low nibble of each byte
+    /// first, and returns the 32 bytes of the digest the same way
+    pub fn keccak256(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        input: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        assert!(input.len() % 2 == 0, "input must be a byte string");
+        let rate_bytes = RATE_NIBBLES / 2;
+        let mut padding = vec![0u8; rate_bytes - (input.len() / 2) % rate_bytes];
+        padding[0] |= 0x01;
+        *padding.last_mut().unwrap() |= 0x80;
+        let mut padded = input.to_vec();
+        for byte in padding {
+            padded.push(self.assign_constant(ctx, (byte & 0xf) as u64)?);
+            padded.push(self.assign_constant(ctx, (byte >> 4) as u64)?);
+        }
+
+        let zero = self.assign_constant(ctx, 0)?;
+        let zero_lane: Lane<F> = [(); NIBBLES_PER_LANE].map(|_| zero.clone());
+        let mut state: Option<[Lane<F>; NUM_LANES]> = None;
+        for block in padded.chunks(RATE_NIBBLES) {
+            let block_lanes = block
+                .chunks(NIBBLES_PER_LANE)
+                .map(|lane| lane.to_vec().try_into().unwrap())
+                .collect_vec();
+            let absorbed = match state {
+                None => block_lanes
+                    .into_iter()
+                    .chain(std::iter::repeat(zero_lane.clone()))
+                    .take(NUM_LANES)
+                    .collect_vec(),
+                Some(state) => {
+                    let mut absorbed = state.to_vec();
+                    for (lane, block_lane) in absorbed.iter_mut().zip(block_lanes.iter()) {
+                        *lane = self.xor(ctx, &[&*lane, block_lane])?;
+                    }
+                    absorbed
+                }
+            };
+            state = Some(self.keccak_f(ctx, absorbed.try_into().unwrap())?);
+        }
+
+        Ok(state
+            .unwrap()
+            .iter()
+            .flatten()
+            .take(OUTPUT_NIBBLES)
+            .cloned()
+            .collect())
+    }
+
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "keccak table",
+            |mut table| {
+                let rows =
+                    std::iter::once((0, [0; 4])).chain(Tag::ALL.iter().flat_map(|tag| {
+                        tag.table_rows().into_iter().map(|row| (*tag as u64, row))
+                    }));
+                for (offset, (tag, row)) in rows.enumerate() {
+                    let values = std::iter::once(tag).chain(row);
+                    for (column, value) in self.config.table.iter().zip(values) {
+                        table.assign_cell(
+                            || "keccak table",
+                            *column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Layouter, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use itertools::Itertools;
+    use rand::Rng;
+
+    use crate::plonky2_verifier::{
+        chip::native_chip::{
+            all_chip::{AllChip, AllChipConfig, OptionalChips},
+            utils::fe_to_u64,
+        },
+        context::RegionCtx,
+        profile::costs_by_name,
+    };
+
+    use super::{NIBBLES_PER_LANE, NUM_LANES, RHO_OFFSETS, ROUND_CONSTANTS};
+
+    /// Keccak-f[1600] over whole lanes
+    fn keccak_f(state: &mut [u64; NUM_LANES]) {
+        for round_constant in ROUND_CONSTANTS.iter() {
+            let parities = (0..5)
+                .map(|x| (0..5).fold(0, |acc, y| acc ^ state[x + 5 * y]))
+                .collect_vec();
+            for i in 0..NUM_LANES {
+                let x = i % 5;
+                state[i] ^= parities[(x + 4) % 5] ^ parities[(x + 1) % 5].rotate_left(1);
+            }
+            let mut state_pi = [0; NUM_LANES];
+            for i in 0..NUM_LANES {
+                let (x, y) = (i % 5, i / 5);
+                state_pi[y + 5 * ((2 * x + 3 * y) % 5)] =
+                    state[i].rotate_left(RHO_OFFSETS[i] as u32);
+            }
+            for i in 0..NUM_LANES {
+                let (x, y) = (i % 5, i / 5);
+                state[i] =
+                    state_pi[i] ^ (!state_pi[(x + 1) % 5 + 5 * y] & state_pi[(x + 2) % 5 + 5 * y]);
+            }
+            state[0] ^= round_constant;
+        }
+    }
+
+    #[derive(Clone, Default)]
+    pub struct TestCircuit {
+        input: [u64; NUM_LANES],
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = AllChipConfig<Fr>;
+        type FloorPlanner = V1;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            AllChipConfig::<Fr>::configure_with(
+                meta,
+                OptionalChips {
+                    keccak: true,
+                    ..OptionalChips::NONE
+                },
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let all_chip = AllChip::new(&config);
+            all_chip.load_table(&mut layouter)?;
+            let keccak_chip = all_chip.keccak_chip()?;
+            let mut expected = self.input;
+            keccak_f(&mut expected);
+            layouter.assign_region(
+                || "keccak chip",
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let lanes = self
+                        .input
+                        .iter()
+                        .map(|lane| {
+                            let lane = all_chip
+                                .arithmetic_chip()
+                                .assign_value(ctx, Value::known(Fr::from(*lane)))?;
+                            let nibbles = keccak_chip.decompose(ctx, &lane, NIBBLES_PER_LANE)?;
+                            Ok(nibbles.try_into().unwrap())
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+                    let output = ctx.stage("keccak_f", |ctx| {
+                        keccak_chip.keccak_f(ctx, lanes.try_into().unwrap())
+                    })?;
+                    for (lane, expected) in output.iter().zip(expected.iter()) {
+                        let lane = keccak_chip.compose(ctx, lane)?;
+                        lane.value()
+                            .map(|lane| assert_eq!(fe_to_u64(*lane), *expected));
+                    }
+                    // no range checks in keccak_f, its lookups are the ones into the Keccak table
+                    let (_, lookups) = costs_by_name(&ctx.take_profile())["keccak_f"];
+                    assert_ne!(lookups, 0);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_keccak_f_known_answer() {
+        let mut state = [0; NUM_LANES];
+        keccak_f(&mut state);
+        assert_eq!(state[0], 0xF1258F7940E1DDE7);
+    }
+
+    #[test]
+    fn test_keccak_chip_mock() {
+        let mut rng = rand::thread_rng();
+        // below the Goldilocks modulus, so that input lanes can be assigned as Goldilocks values
+        let circuit = TestCircuit {
+            input: [(); NUM_LANES].map(|_| rng.gen::<u64>() >> 1),
+        };
+        const DEGREE: u32 = 17;
+        let mock_prover = MockProver::run(DEGREE, &circuit, vec![vec![]]).unwrap();
+        mock_prover.assert_satisfied();
+    }
+}
@@ -1,5 +1,7 @@
 pub mod all_chip;
 pub mod arithmetic_chip;
+pub mod keccak_chip;
+pub mod poseidon2_bn254_chip;
 pub mod poseidon_bn254_chip;
 pub mod test_utils;
 pub mod utils;
@@ -0,0 +1,369 @@
+use crate::plonky2_verifier::context::RegionCtx;
+use halo2_proofs::{
+    circuit::{AssignedCell, Value},
+    halo2curves::ff::PrimeField,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, VirtualCells},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+use crate::plonky2_verifier::bn245_poseidon::{
+    constants::{
+        POSEIDON2_EXTERNAL_CONSTANTS_BG, POSEIDON2_EXTERNAL_MATRIX,
+        POSEIDON2_INTERNAL_CONSTANTS_BG, POSEIDON2_INTERNAL_DIAG_M_1_BG, R_F_BN254_POSEIDON2,
+        R_P_BN254_POSEIDON2, T_BN254_POSEIDON2,
+    },
+    value::{
+        bg_to_fe, poseidon2_external_layer_value, poseidon2_external_round_value,
+        poseidon2_internal_round_value,
+    },
+};
+
+/// Poseidon2 over BN254 with `t = 4`, one row per round. Unlike `PoseidonBn254ChipConfig`, the
+/// linear layers are `M4` and the all-ones matrix plus a diagonal instead of a dense MDS matrix,
+/// and internal rounds only take a round constant for the first element.
+#[derive(Clone, Debug)]
+pub struct Poseidon2Bn254ChipConfig<F: PrimeField> {
+    pub state: [Column<Advice>; T_BN254_POSEIDON2],
+    pub constants: [Column<Fixed>; T_BN254_POSEIDON2],
+    pub q_linear: Selector,
+    pub q_external: Selector,
+    pub q_internal: Selector,
+    _maker: PhantomData<F>,
+}
+
+fn external_layer<F: PrimeField>(
+    state: &[Expression<F>; T_BN254_POSEIDON2],
+) -> [Expression<F>; T_BN254_POSEIDON2] {
+    POSEIDON2_EXTERNAL_MATRIX.map(|row| {
+        state
+            .iter()
+            .zip(row)
+            .fold(Expression::Constant(F::from(0)), |acc, (s, m)| {
+                acc + s.clone() * Expression::Constant(F::from(m))
+            })
+    })
+}
+
+fn pow5<F: PrimeField>(x: Expression<F>) -> Expression<F> {
+    x.clone() * x.clone() * x.clone() * x.clone() * x
+}
+
+fn query_state<F: PrimeField>(
+    meta: &mut VirtualCells<'_, F>,
+    state: [Column<Advice>; T_BN254_POSEIDON2],
+    rotation: Rotation,
+) -> [Expression<F>; T_BN254_POSEIDON2] {
+    state.map(|s| meta.query_advice(s, rotation))
+}
+
+fn constrain<F: PrimeField>(
+    q: Expression<F>,
+    next_state: [Expression<F>; T_BN254_POSEIDON2],
+    expected: [Expression<F>; T_BN254_POSEIDON2],
+) -> Vec<Expression<F>> {
+    next_state
+        .into_iter()
+        .zip(expected)
+        .map(|(n, e)| q.clone() * (n - e))
+        .collect()
+}
+
+impl<F: PrimeField> Poseidon2Bn254ChipConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let state = [(); T_BN254_POSEIDON2].map(|_| meta.advice_column());
+        let constants = [(); T_BN254_POSEIDON2].map(|_| meta.fixed_column());
+        let q_linear = meta.selector();
+        let q_external = meta.selector();
+        let q_internal = meta.selector();
+        state.iter().for_each(|s| meta.enable_equality(*s));
+
+        meta.create_gate("poseidon2 linear layer", |meta| {
+            let next_state = query_state(meta, state, Rotation::next());
+            let state = query_state(meta, state, Rotation::cur());
+            let q = meta.query_selector(q_linear);
+            constrain(q, next_state, external_layer(&state))
+        });
+        meta.create_gate("poseidon2 external round", |meta| {
+            let next_state = query_state(meta, state, Rotation::next());
+            let state = query_state(meta, state, Rotation::cur());
+            let constants = constants.map(|c| meta.query_fixed(c, Rotation::cur()));
+            let q = meta.query_selector(q_external);
+            let mut after_sbox = state;
+            for (s, c) in after_sbox.iter_mut().zip(constants) {
+                *s = pow5(s.clone() + c);
+            }
+            constrain(q, next_state, external_layer(&after_sbox))
+        });
+        meta.create_gate("poseidon2 internal round", |meta| {
+            let next_state = query_state(meta, state, Rotation::next());
+            let mut state = query_state(meta, state, Rotation::cur());
+            let constant = meta.query_fixed(constants[0], Rotation::cur());
+            let q = meta.query_selector(q_internal);
+            state[0] = pow5(state[0].clone() + constant);
+            let sum = state
+                .iter()
+                .fold(Expression::Constant(F::from(0)), |acc, s| acc + s.clone());
+            let mut after_linear = state;
+            for (s, d) in after_linear
+                .iter_mut()
+                .zip(POSEIDON2_INTERNAL_DIAG_M_1_BG.iter())
+            {
+                *s = sum.clone() + s.clone() * Expression::Constant(bg_to_fe(d));
+            }
+            constrain(q, next_state, after_linear)
+        });
+
+        Self {
+            state,
+            constants,
+            q_linear,
+            q_external,
+            q_internal,
+            _maker: PhantomData,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Poseidon2Bn254Chip<F: PrimeField> {
+    config: Poseidon2Bn254ChipConfig<F>,
+}
+
+impl<F: PrimeField> Poseidon2Bn254Chip<F> {
+    pub fn new(config: &Poseidon2Bn254ChipConfig<F>) -> Self {
+        Poseidon2Bn254Chip {
+            config: config.clone(),
+        }
+    }
+
+    pub fn assign_initial_state(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: [Value<F>; T_BN254_POSEIDON2],
+    ) -> Result<[AssignedCell<F, F>; T_BN254_POSEIDON2], Error> {
+        let state_assigned = state
+            .iter()
+            .zip(self.config.state.iter())
+            .map(|(s, c)| ctx.assign_advice(|| "", *c, *s))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(state_assigned.try_into().unwrap())
+    }
+
+    // assume that the state is already assigned at the current row, enable `selector` on it
+    // and assign the state after `round` to the next row
+    fn assign_round(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        selector: Selector,
+        constants: &[F],
+        state: &[AssignedCell<F, F>; T_BN254_POSEIDON2],
+        round: impl FnOnce(&mut [Value<F>; T_BN254_POSEIDON2]),
+    ) -> Result<[AssignedCell<F, F>; T_BN254_POSEIDON2], Error> {
+        ctx.enable(selector)?;
+        self.config
+            .constants
+            .iter()
+            .zip(constants.iter())
+            .map(|(c, r)| ctx.assign_fixed(|| "", *c, *r))
+            .collect::<Result<Vec<_>, _>>()?;
+        ctx.next();
+        let mut state = state.clone().map(|s| s.value().cloned());
+        round(&mut state);
+        self.assign_initial_state(ctx, state)
+    }
+
+    fn assign_external_round(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: &[AssignedCell<F, F>; T_BN254_POSEIDON2],
+        round: usize,
+    ) -> Result<[AssignedCell<F, F>; T_BN254_POSEIDON2], Error> {
+        let constants = POSEIDON2_EXTERNAL_CONSTANTS_BG[round]
+            .iter()
+            .map(bg_to_fe::<F>)
+            .collect::<Vec<_>>();
+        self.assign_round(ctx, self.config.q_external, &constants, state, |state| {
+            poseidon2_external_round_value(state, round)
+        })
+    }
+
+    fn assign_internal_round(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: &[AssignedCell<F, F>; T_BN254_POSEIDON2],
+        round: usize,
+    ) -> Result<[AssignedCell<F, F>; T_BN254_POSEIDON2], Error> {
+        let constant = bg_to_fe::<F>(&POSEIDON2_INTERNAL_CONSTANTS_BG[round]);
+        self.assign_round(ctx, self.config.q_internal, &[constant], state, |state| {
+            poseidon2_internal_round_value(state, round)
+        })
+    }
+
+    pub fn apply_permute(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        state: [AssignedCell<F, F>; T_BN254_POSEIDON2],
+    ) -> Result<[AssignedCell<F, F>; T_BN254_POSEIDON2], Error> {
+        // re-assign state to the current row.
+        let state_assigned =
+            self.assign_initial_state(ctx, state.clone().map(|s| s.value().cloned()))?;
+        for i in 0..T_BN254_POSEIDON2 {
+            ctx.constrain_equal(state[i].cell(), state_assigned[i].cell())?;
+        }
+
+        let mut state = self.assign_round(
+            ctx,
+            self.config.q_linear,
+            &[],
+            &state_assigned,
+            poseidon2_external_layer_value,
+        )?;
+        for round in 0..R_F_BN254_POSEIDON2 / 2 {
+            state = self.assign_external_round(ctx, &state, round)?;
+        }
+        for round in 0..R_P_BN254_POSEIDON2 {
+            state = self.assign_internal_round(ctx, &state, round)?;
+        }
+        for round in R_F_BN254_POSEIDON2 / 2..R_F_BN254_POSEIDON2 {
+            state = self.assign_external_round(ctx, &state, round)?;
+        }
+        ctx.next();
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plonky2_verifier::{
+        bn245_poseidon::{constants::T_BN254_POSEIDON, native::permute_bn254_poseidon2_native},
+        chip::native_chip::poseidon_bn254_chip::{PoseidonBn254Chip, PoseidonBn254ChipConfig},
+        context::RegionCtx,
+        degree::row_budget,
+    };
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Layouter, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    use super::{Poseidon2Bn254Chip, Poseidon2Bn254ChipConfig};
+
+    const NUM_PERMUTATIONS: usize = 100;
+
+    #[derive(Clone, Default)]
+    pub struct TestCircuit;
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = Poseidon2Bn254ChipConfig<Fr>;
+        type FloorPlanner = V1;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            Poseidon2Bn254ChipConfig::<Fr>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let poseidon2_chip = Poseidon2Bn254Chip::new(&config);
+            layouter.assign_region(
+                || "test",
+                |region| {
+                    let mut ctx = RegionCtx::new(region, 0);
+                    let initial_state = [0u64, 1, 2, 3].map(|x| Value::known(Fr::from(x)));
+                    let state = poseidon2_chip.assign_initial_state(&mut ctx, initial_state)?;
+                    let output = poseidon2_chip.apply_permute(&mut ctx, state)?;
+
+                    let mut expected = [0u64, 1, 2, 3].map(Fr::from);
+                    permute_bn254_poseidon2_native(&mut expected);
+                    for (output, expected) in output.iter().zip(expected) {
+                        output.value().map(|x| assert_eq!(*x, expected));
+                    }
+
+                    let mut state = output;
+                    for _ in 1..NUM_PERMUTATIONS {
+                        state = poseidon2_chip.apply_permute(&mut ctx, state)?;
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    /// The same number of permutations with the classic Poseidon chip.
+    #[derive(Clone, Default)]
+    pub struct PoseidonCircuit;
+
+    impl Circuit<Fr> for PoseidonCircuit {
+        type Config = PoseidonBn254ChipConfig<Fr>;
+        type FloorPlanner = V1;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            PoseidonBn254ChipConfig::<Fr>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let poseidon_chip = PoseidonBn254Chip::new(&config);
+            layouter.assign_region(
+                || "test",
+                |region| {
+                    let mut ctx = RegionCtx::new(region, 0);
+                    let initial_state = [Value::known(Fr::from(0)); T_BN254_POSEIDON];
+                    let mut state = poseidon_chip.assign_initial_state(&mut ctx, initial_state)?;
+                    for _ in 0..NUM_PERMUTATIONS {
+                        state = poseidon_chip.apply_permute(&mut ctx, state)?;
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    /// `(rows, advice cells, fixed cells)` used by `circuit`.
+    fn cost<C: Circuit<Fr>>(circuit: &C) -> (usize, usize, usize) {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        C::configure_with_params(&mut cs, circuit.params());
+        let rows = row_budget(circuit).unwrap().used_rows;
+        (
+            rows,
+            rows * cs.num_advice_columns(),
+            rows * (cs.num_fixed_columns() + cs.num_selectors()),
+        )
+    }
+
+    #[test]
+    fn test_poseidon2_mock() {
+        const DEGREE: u32 = 17;
+        let circuit = TestCircuit;
+        let mock_prover = MockProver::run(DEGREE, &circuit, vec![]).unwrap();
+        mock_prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_poseidon2_constraint_savings() {
+        let (rows, advice_cells, fixed_cells) = cost(&TestCircuit);
+        let (poseidon_rows, poseidon_advice_cells, poseidon_fixed_cells) = cost(&PoseidonCircuit);
+        assert!(rows < poseidon_rows);
+        assert!(advice_cells < poseidon_advice_cells);
+        assert!(fixed_cells < poseidon_fixed_cells);
+    }
+}
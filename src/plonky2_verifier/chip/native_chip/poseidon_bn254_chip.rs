@@ -253,6 +253,7 @@ mod tests {
     impl Circuit<Fr> for TestCircuit {
         type Config = PoseidonBn254ChipConfig<Fr>;
         type FloorPlanner = V1;
+        type Params = ();
 
         fn without_witnesses(&self) -> Self {
             Self::default()
@@ -1,7 +1,6 @@
 use halo2_proofs::halo2curves::bn256::G1Affine;
 use halo2_proofs::plonk::keygen_pk;
 use halo2_proofs::plonk::keygen_vk;
-use halo2_proofs::plonk::verify_proof;
 use halo2_proofs::plonk::ProvingKey;
 use halo2_proofs::poly::kzg::commitment::ParamsKZG;
 use halo2_proofs::transcript::TranscriptWriterBuffer;
@@ -15,6 +14,9 @@ use halo2_solidity_verifier::Keccak256Transcript;
 use halo2_solidity_verifier::{compile_solidity, Evm, SolidityGenerator};
 use rand::RngCore;
 
+use crate::plonky2_verifier::error::VerifierError;
+use crate::plonky2_verifier::verifier_api::verify_snark_proof;
+
 pub fn test_contract_size(k: u32, circuit: &impl Circuit<Fr>) {
     let mut rng = rand::thread_rng();
     let param = ParamsKZG::<Bn256>::setup(k, &mut rng);
@@ -45,7 +47,8 @@ pub fn test_verify_on_contract(k: u32, circuit: &(impl Circuit<Fr> + Clone), ins
     let pk = keygen_pk(&param, vk, circuit).unwrap();
     let now = std::time::Instant::now();
     let calldata = {
-        let proof = create_proof_checked(&param, &pk, circuit.clone(), &instance, &mut rng);
+        let proof =
+            create_proof_checked(&param, &pk, circuit.clone(), &instance, &mut rng).unwrap();
         encode_calldata(Some(vk_address.into()), &proof, &instance)
     };
     println!("Proof creation time: {:?}", now.elapsed());
@@ -60,11 +63,8 @@ pub fn create_proof_checked(
     circuit: impl Circuit<Fr>,
     instances: &[Fr],
     mut rng: impl RngCore,
-) -> Vec<u8> {
-    use halo2_proofs::poly::kzg::{
-        multiopen::{ProverSHPLONK, VerifierSHPLONK},
-        strategy::SingleStrategy,
-    };
+) -> Result<Vec<u8>, VerifierError> {
+    use halo2_proofs::poly::kzg::multiopen::ProverSHPLONK;
 
     let proof = {
         let mut transcript = Keccak256Transcript::new(Vec::new());
@@ -76,20 +76,10 @@ pub fn create_proof_checked(
             &mut rng,
             &mut transcript,
         )
-        .unwrap();
+        .map_err(VerifierError::Proving)?;
         transcript.finalize()
     };
 
-    let result = {
-        let mut transcript = Keccak256Transcript::new(proof.as_slice());
-        verify_proof::<_, VerifierSHPLONK<_>, _, _, SingleStrategy<_>>(
-            params,
-            pk.get_vk(),
-            SingleStrategy::new(params),
-            &[&[instances]],
-            &mut transcript,
-        )
-    };
-    assert!(result.is_ok());
-    proof
+    verify_snark_proof(params, pk.get_vk(), &proof, instances)?;
+    Ok(proof)
 }
@@ -18,6 +18,12 @@ pub fn fe_to_goldilocks<F: PrimeField>(x: F) -> GoldilocksField {
     GoldilocksField::from_canonical_u64(x)
 }
 
+pub fn fe_to_u64<F: PrimeField>(x: F) -> u64 {
+    let x_limbs = fe_to_big(x).to_u64_digits();
+    assert!(x_limbs.len() <= 1);
+    x_limbs.first().copied().unwrap_or(0)
+}
+
 pub fn goldilocks_to_fe<F: PrimeField>(x: GoldilocksField) -> F {
     F::from(x.to_canonical_u64())
 }
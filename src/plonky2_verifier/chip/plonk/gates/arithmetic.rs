@@ -74,7 +74,7 @@ impl<F: PrimeField> CustomGateConstrainer<F> for ArithmeticGateConstrainer {
 #[cfg(test)]
 mod tests {
     use super::ArithmeticGateConstrainer;
-    use crate::plonky2_verifier::chip::plonk::gates::gate_test::test_custom_gate;
+    use crate::plonky2_verifier::chip::plonk::gates::gate_test::{narrow_config, test_custom_gate};
     use plonky2::{gates::arithmetic_base::ArithmeticGate, plonk::circuit_data::CircuitConfig};
 
     #[test]
@@ -86,4 +86,14 @@ mod tests {
         };
         test_custom_gate(plonky2_gate, halo2_gate, 17);
     }
+
+    #[test]
+    fn test_arithmetic_gate_narrow_config() {
+        let plonky2_gate = ArithmeticGate::new_from_config(&narrow_config());
+        assert_eq!(plonky2_gate.num_ops, 7);
+        let halo2_gate = ArithmeticGateConstrainer {
+            num_ops: plonky2_gate.num_ops,
+        };
+        test_custom_gate(plonky2_gate, halo2_gate, 17);
+    }
 }
@@ -85,7 +85,7 @@ impl<F: PrimeField> CustomGateConstrainer<F> for ArithmeticExtensionGateConstrai
 #[cfg(test)]
 mod tests {
 
-    use crate::plonky2_verifier::chip::plonk::gates::gate_test::test_custom_gate;
+    use crate::plonky2_verifier::chip::plonk::gates::gate_test::{narrow_config, test_custom_gate};
     use plonky2::{
         gates::arithmetic_extension::ArithmeticExtensionGate, plonk::circuit_data::CircuitConfig,
     };
@@ -101,4 +101,14 @@ mod tests {
         };
         test_custom_gate(plonky2_gate, halo2_gate, 17);
     }
+
+    #[test]
+    fn test_arithmetic_extension_gate_narrow_config() {
+        let plonky2_gate = ArithmeticExtensionGate::new_from_config(&narrow_config());
+        assert_eq!(plonky2_gate.num_ops, 3);
+        let halo2_gate = ArithmeticExtensionGateConstrainer {
+            num_ops: plonky2_gate.num_ops,
+        };
+        test_custom_gate(plonky2_gate, halo2_gate, 17);
+    }
 }
@@ -13,6 +13,8 @@ use super::CustomGateConstrainer;
 #[derive(Debug, Clone)]
 pub struct BaseSumGateConstrainer {
     pub num_limbs: usize,
+    /// The `B` of plonky2's `BaseSumGate<B>`.
+    pub base: usize,
 }
 
 impl BaseSumGateConstrainer {
@@ -35,7 +37,13 @@ impl<F: PrimeField> CustomGateConstrainer<F> for BaseSumGateConstrainer {
         _public_inputs_hash: &AssignedHashValues<F>,
     ) -> Result<Vec<AssignedExtensionFieldValue<F, 2>>, halo2_proofs::plonk::Error> {
         let goldilocks_extension_chip = self.goldilocks_extension_chip(goldilocks_chip_config);
-        let base = goldilocks_extension_chip.two_extension(ctx)?;
+        let base = goldilocks_extension_chip.constant_extension(
+            ctx,
+            &[
+                GoldilocksField::from_canonical_usize(self.base),
+                GoldilocksField::ZERO,
+            ],
+        )?;
         let sum = &local_wires[Self::WIRE_SUM];
         let limbs = local_wires[self.limbs()].to_vec();
         let computed_sum = goldilocks_extension_chip.reduce_extension(ctx, &base, &limbs)?;
@@ -44,7 +52,7 @@ impl<F: PrimeField> CustomGateConstrainer<F> for BaseSumGateConstrainer {
         for limb in limbs {
             constraints.push({
                 let mut acc = goldilocks_extension_chip.one_extension(ctx)?;
-                (0..2).for_each(|i| {
+                (0..self.base).for_each(|i| {
                     // We update our accumulator as:
                     // acc' = acc (x - i)
                     //      = acc x + (-i) acc
@@ -78,6 +86,21 @@ mod tests {
             BaseSumGate::<2>::new_from_config::<F>(&CircuitConfig::standard_recursion_config());
         let halo2_gate = BaseSumGateConstrainer {
             num_limbs: plonky2_gate.num_limbs,
+            base: 2,
+        };
+        test_custom_gate(plonky2_gate, halo2_gate, 17);
+    }
+
+    #[test]
+    fn test_base_sum_gate_base_4() {
+        let config = CircuitConfig {
+            num_routed_wires: 20,
+            ..CircuitConfig::standard_recursion_config()
+        };
+        let plonky2_gate = BaseSumGate::<4>::new_from_config::<F>(&config);
+        let halo2_gate = BaseSumGateConstrainer {
+            num_limbs: plonky2_gate.num_limbs,
+            base: 4,
         };
         test_custom_gate(plonky2_gate, halo2_gate, 17);
     }
@@ -3,7 +3,11 @@ use std::ops::Range;
 use halo2_proofs::halo2curves::ff::PrimeField;
 use crate::plonky2_verifier::context::RegionCtx;
 use itertools::Itertools;
-use plonky2::field::{goldilocks_field::GoldilocksField, types::Field};
+use plonky2::field::{
+    goldilocks_field::GoldilocksField,
+    interpolation::barycentric_weights,
+    types::{Field, PrimeField64},
+};
 
 use crate::plonky2_verifier::chip::{
     goldilocks_chip::GoldilocksChipConfig,
@@ -15,7 +19,8 @@ use crate::plonky2_verifier::chip::{
 
 use super::CustomGateConstrainer;
 
-/// A gate which takes a single constant parameter and outputs that value.
+/// Interpolates the values on a coset of the subgroup of size `2^subgroup_bits` and evaluates the
+/// interpolant at a point, with intermediate values keeping the constraints of degree `degree`.
 #[derive(Clone, Debug)]
 pub struct CosetInterpolationGateConstrainer {
     pub subgroup_bits: usize,
@@ -24,35 +29,66 @@ pub struct CosetInterpolationGateConstrainer {
 }
 
 impl CosetInterpolationGateConstrainer {
-    // pub fn new(subgroup_bits: usize) -> Self {
-    //     Self::with_max_degree(subgroup_bits, 1 << subgroup_bits)
-    // }
+    /// Shape of plonky2's `CosetInterpolationGate::new`, a single constraint of full degree.
+    pub fn new(subgroup_bits: usize) -> Self {
+        Self::with_max_degree(subgroup_bits, 1 << subgroup_bits)
+    }
 
-    // pub(crate) fn with_max_degree(subgroup_bits: usize, max_degree: usize) -> Self {
-    //     assert!(max_degree > 1, "need at least quadratic constraints");
+    /// Shape of plonky2's `CosetInterpolationGate::with_max_degree`, as used by the FRI verifier
+    /// for an arity of `2^subgroup_bits`.
+    pub fn with_max_degree(subgroup_bits: usize, max_degree: usize) -> Self {
+        assert!(max_degree > 1, "need at least quadratic constraints");
 
-    //     let n_points = 1 << subgroup_bits;
+        let n_points = 1 << subgroup_bits;
 
-    //     // Number of intermediate values required to compute interpolation with degree bound
-    //     let n_intermediates = (n_points - 2) / (max_degree - 1);
+        // Number of intermediate values required to compute interpolation with degree bound
+        let n_intermediates = (n_points - 2) / (max_degree - 1);
 
-    //     // Find minimum degree such that (n_points - 2) / (degree - 1) < n_intermediates + 1
-    //     // Minimizing the degree this way allows the gate to be in a larger selector group
-    //     let degree = (n_points - 2) / (n_intermediates + 1) + 2;
+        // Find minimum degree such that (n_points - 2) / (degree - 1) < n_intermediates + 1
+        let degree = (n_points - 2) / (n_intermediates + 1) + 2;
 
-    //     let barycentric_weights = barycentric_weights(
-    //         &<GoldilocksField as plonky2::field::types::Field>::two_adic_subgroup(subgroup_bits)
-    //             .into_iter()
-    //             .map(|x| (x, F::ZERO))
-    //             .collect::<Vec<_>>(),
-    //     );
+        Self::with_degree(subgroup_bits, degree)
+    }
 
-    //     Self {
-    //         subgroup_bits,
-    //         degree,
-    //         barycentric_weights,
-    //     }
-    // }
+    /// Gate of the given degree, with the barycentric weights of the subgroup of its size.
+    pub fn with_degree(subgroup_bits: usize, degree: usize) -> Self {
+        let barycentric_weights = barycentric_weights(
+            &GoldilocksField::two_adic_subgroup(subgroup_bits)
+                .into_iter()
+                .map(|x| (x, GoldilocksField::ZERO))
+                .collect_vec(),
+        );
+        Self {
+            subgroup_bits,
+            degree,
+            barycentric_weights,
+        }
+    }
+
+    /// Gate of the given degree with the weights of the id of the plonky2 gate, in the order of
+    /// its value wires, which differs between plonky2 versions. `None` if they are not the weights
+    /// of the subgroup of size `2^subgroup_bits` in some order.
+    pub fn with_weights(
+        subgroup_bits: usize,
+        degree: usize,
+        barycentric_weights: Vec<GoldilocksField>,
+    ) -> Option<Self> {
+        let gate = Self::with_degree(subgroup_bits, degree);
+        let sorted = |weights: &[GoldilocksField]| {
+            weights.iter().map(|w| w.to_canonical_u64()).sorted().collect_vec()
+        };
+        (sorted(&barycentric_weights) == sorted(&gate.barycentric_weights)).then_some(Self {
+            barycentric_weights,
+            ..gate
+        })
+    }
+
+    /// Points of the subgroup in the order of `barycentric_weights`: the weight of `x` in a
+    /// subgroup of size `n` is `1 / prod_{y != x} (x - y) = x / n`.
+    fn domain(&self) -> Vec<GoldilocksField> {
+        let n = GoldilocksField::from_canonical_usize(self.num_points());
+        self.barycentric_weights.iter().map(|w| *w * n).collect()
+    }
 
     fn num_points(&self) -> usize {
         1 << self.subgroup_bits
@@ -170,12 +206,7 @@ impl<F: PrimeField> CustomGateConstrainer<F> for CosetInterpolationGateConstrain
                 .to_ext_array(),
         );
 
-        let domain = <GoldilocksField as plonky2::field::types::Field>::two_adic_subgroup(
-            self.subgroup_bits,
-        )
-        .iter()
-        .map(|x| GoldilocksField::from_canonical_u64(x.0))
-        .collect_vec();
+        let domain = self.domain();
 
         let values: Vec<
             crate::plonky2_verifier::chip::goldilocks_extension_algebra_chip::AssignedExtensionAlgebra<_>,
@@ -296,3 +327,56 @@ fn partial_interpolate_ext_algebra_target<F: PrimeField>(
             },
         )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::CosetInterpolationGateConstrainer;
+    use crate::plonky2_verifier::chip::plonk::gates::gate_test::test_custom_gate;
+    use plonky2::{
+        field::{goldilocks_field::GoldilocksField, types::Field},
+        gates::coset_interpolation::CosetInterpolationGate,
+    };
+
+    #[test]
+    fn test_coset_interpolation_gate() {
+        let mut shapes = vec![];
+        for subgroup_bits in 1..=4 {
+            for max_degree in [2, 4, 8, 16] {
+                let halo2_gate =
+                    CosetInterpolationGateConstrainer::with_max_degree(subgroup_bits, max_degree);
+                if !shapes.contains(&(subgroup_bits, halo2_gate.degree)) {
+                    shapes.push((subgroup_bits, halo2_gate.degree));
+                }
+            }
+        }
+        for (subgroup_bits, degree) in shapes {
+            let mut plonky2_gate = CosetInterpolationGate::<GoldilocksField, 2>::new(subgroup_bits);
+            plonky2_gate.degree = degree;
+            let halo2_gate = CosetInterpolationGateConstrainer::with_degree(subgroup_bits, degree);
+            assert_eq!(
+                halo2_gate.barycentric_weights,
+                plonky2_gate.barycentric_weights
+            );
+            assert_eq!(
+                halo2_gate.domain(),
+                GoldilocksField::two_adic_subgroup(subgroup_bits)
+            );
+            test_custom_gate(plonky2_gate, halo2_gate, 17);
+        }
+    }
+
+    #[test]
+    fn test_coset_interpolation_gate_weights_order() {
+        let gate = CosetInterpolationGateConstrainer::with_degree(4, 6);
+        let mut weights = gate.barycentric_weights.clone();
+        weights.reverse();
+        let reordered = CosetInterpolationGateConstrainer::with_weights(4, 6, weights).unwrap();
+        let mut domain = gate.domain();
+        domain.reverse();
+        assert_eq!(reordered.domain(), domain);
+
+        let mut weights = gate.barycentric_weights.clone();
+        weights[0] = weights[1];
+        assert!(CosetInterpolationGateConstrainer::with_weights(4, 6, weights).is_none());
+    }
+}
@@ -14,7 +14,7 @@ use plonky2::{
     },
     gates::gate::Gate,
     hash::hash_types::HashOut,
-    plonk::vars::EvaluationVars,
+    plonk::{circuit_data::CircuitConfig, vars::EvaluationVars},
 };
 
 use super::CustomGateConstrainer;
@@ -83,6 +83,7 @@ impl<'a, Gate: CustomGateConstrainer<Fr>> Circuit<Fr> for TestCircuit<'a, Gate>
     type Config = GoldilocksChipConfig<Fr>;
 
     type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
 
     fn without_witnesses(&self) -> Self {
         todo!()
@@ -151,6 +152,16 @@ impl<'a, Gate: CustomGateConstrainer<Fr>> Circuit<Fr> for TestCircuit<'a, Gate>
     }
 }
 
+/// A config narrower than `standard_recursion_config`, so that the gates sized from it get other
+/// numbers of operations than the ones of the standard config.
+pub fn narrow_config() -> CircuitConfig {
+    CircuitConfig {
+        num_wires: 60,
+        num_routed_wires: 28,
+        ..CircuitConfig::standard_recursion_config()
+    }
+}
+
 pub fn test_custom_gate<PG: Gate<F, D>, HG: CustomGateConstrainer<Fr>>(
     plonky2_gate: PG,
     halo2_gate: HG,
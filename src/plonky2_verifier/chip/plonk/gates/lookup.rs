@@ -0,0 +1,68 @@
+use crate::plonky2_verifier::context::RegionCtx;
+use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
+
+use crate::plonky2_verifier::{
+    chip::goldilocks_chip::GoldilocksChipConfig,
+    types::{
+        assigned::{AssignedExtensionFieldValue, AssignedHashValues},
+        common_data::CircuitConfig,
+    },
+};
+
+use super::CustomGateConstrainer;
+
+/// Looks `(input, output)` pairs up in a lookup table. The gate has no constraints of its own,
+/// the pairs are checked by the lookup terms of the vanishing polynomial.
+#[derive(Clone, Debug)]
+pub struct LookupGateConstrainer {
+    pub num_slots: usize,
+}
+
+impl LookupGateConstrainer {
+    /// Number of lookups per row, as in plonky2's `LookupGate::num_slots`.
+    pub fn num_slots(config: &CircuitConfig) -> usize {
+        let wires_per_lookup = 2;
+        config.num_routed_wires / wires_per_lookup
+    }
+
+    pub fn wire_ith_looking_inp(i: usize) -> usize {
+        2 * i
+    }
+
+    pub fn wire_ith_looking_out(i: usize) -> usize {
+        2 * i + 1
+    }
+}
+
+impl<F: PrimeField> CustomGateConstrainer<F> for LookupGateConstrainer {
+    fn eval_unfiltered_constraint(
+        &self,
+        _ctx: &mut RegionCtx<'_, F>,
+        _goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        _local_constants: &[AssignedExtensionFieldValue<F, 2>],
+        _local_wires: &[AssignedExtensionFieldValue<F, 2>],
+        _public_inputs_hash: &AssignedHashValues<F>,
+    ) -> Result<Vec<AssignedExtensionFieldValue<F, 2>>, Error> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::LookupGateConstrainer;
+    use crate::plonky2_verifier::chip::plonk::gates::gate_test::test_custom_gate;
+    use plonky2::{gates::lookup::LookupGate, plonk::circuit_data::CircuitConfig};
+
+    #[test]
+    fn test_lookup_gate() {
+        let config = CircuitConfig::standard_recursion_config();
+        let lut = Arc::new((0..16u16).map(|i| (i, i * i)).collect::<Vec<_>>());
+        let plonky2_gate = LookupGate::new_from_table(&config, lut);
+        let halo2_gate = LookupGateConstrainer {
+            num_slots: config.num_routed_wires / 2,
+        };
+        test_custom_gate(plonky2_gate, halo2_gate, 17);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::plonky2_verifier::context::RegionCtx;
+use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
+
+use crate::plonky2_verifier::{
+    chip::goldilocks_chip::GoldilocksChipConfig,
+    types::{
+        assigned::{AssignedExtensionFieldValue, AssignedHashValues},
+        common_data::CircuitConfig,
+    },
+};
+
+use super::CustomGateConstrainer;
+
+/// Holds the entries of a lookup table along with their multiplicities. The gate has no
+/// constraints of its own, the table is checked by the lookup terms of the vanishing polynomial.
+#[derive(Clone, Debug)]
+pub struct LookupTableGateConstrainer {
+    pub num_slots: usize,
+    /// First row of the table in the trace, the table is laid out bottom up.
+    pub last_lut_row: usize,
+}
+
+impl LookupTableGateConstrainer {
+    /// Number of table entries per row, as in plonky2's `LookupTableGate::num_slots`.
+    pub fn num_slots(config: &CircuitConfig) -> usize {
+        let wires_per_entry = 3;
+        config.num_routed_wires / wires_per_entry
+    }
+
+    pub fn wire_ith_looked_inp(i: usize) -> usize {
+        3 * i
+    }
+
+    pub fn wire_ith_looked_out(i: usize) -> usize {
+        3 * i + 1
+    }
+
+    pub fn wire_ith_multiplicity(i: usize) -> usize {
+        3 * i + 2
+    }
+}
+
+impl<F: PrimeField> CustomGateConstrainer<F> for LookupTableGateConstrainer {
+    fn eval_unfiltered_constraint(
+        &self,
+        _ctx: &mut RegionCtx<'_, F>,
+        _goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        _local_constants: &[AssignedExtensionFieldValue<F, 2>],
+        _local_wires: &[AssignedExtensionFieldValue<F, 2>],
+        _public_inputs_hash: &AssignedHashValues<F>,
+    ) -> Result<Vec<AssignedExtensionFieldValue<F, 2>>, Error> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::LookupTableGateConstrainer;
+    use crate::plonky2_verifier::chip::plonk::gates::gate_test::test_custom_gate;
+    use plonky2::{gates::lookup_table::LookupTableGate, plonk::circuit_data::CircuitConfig};
+
+    #[test]
+    fn test_lookup_table_gate() {
+        let config = CircuitConfig::standard_recursion_config();
+        let lut = Arc::new((0..16u16).map(|i| (i, i * i)).collect::<Vec<_>>());
+        let plonky2_gate = LookupTableGate::new_from_table(&config, lut, 100);
+        let halo2_gate = LookupTableGateConstrainer {
+            num_slots: config.num_routed_wires / 3,
+            last_lut_row: 100,
+        };
+        test_custom_gate(plonky2_gate, halo2_gate, 17);
+    }
+}
@@ -1,27 +1,13 @@
 use std::ops::Range;
 
 use crate::plonky2_verifier::context::RegionCtx;
+use crate::plonky2_verifier::error::VerifierError;
 use halo2_proofs::halo2curves::ff::PrimeField;
 use halo2_proofs::plonk::Error;
 use plonky2::field::types::Field;
 use plonky2::{field::goldilocks_field::GoldilocksField, gates::gate::GateRef};
 
-use self::arithmetic_extension::ArithmeticExtensionGateConstrainer;
-use self::base_sum::BaseSumGateConstrainer;
-use self::comparison_gate::ComparisonGateContainer;
-use self::coset_interpolation_gate::CosetInterpolationGateConstrainer;
-use self::multiplication_extension::MulExtensionGateConstrainer;
-use self::poseidon::PoseidonGateConstrainer;
-use self::poseidon_mds::PoseidonMDSGateConstrainer;
-use self::random_access::RandomAccessGateConstrainer;
-use self::reducing::ReducingGateConstrainer;
-use self::reducing_extension::ReducingExtensionGateConstrainer;
-use self::u32_add_many::U32AddManyGateConstrainer;
-use self::u32_arithmetic::U32ArithmeticGateConstrainer;
-use self::{
-    arithmetic::ArithmeticGateConstrainer, constant::ConstantGateConstrainer,
-    noop::NoopGateConstrainer, public_input::PublicInputGateConstrainer,
-};
+use self::registry::GateRegistry;
 
 use crate::plonky2_verifier::chip::goldilocks_chip::GoldilocksChipConfig;
 use crate::plonky2_verifier::chip::goldilocks_extension_algebra_chip::{
@@ -39,6 +25,8 @@ pub mod base_sum;
 pub mod comparison_gate;
 pub mod constant;
 pub mod coset_interpolation_gate;
+pub mod lookup;
+pub mod lookup_table;
 pub mod multiplication_extension;
 pub mod noop;
 pub mod poseidon;
@@ -47,6 +35,7 @@ pub mod public_input;
 pub mod random_access;
 pub mod reducing;
 pub mod reducing_extension;
+pub mod registry;
 pub mod u32_add_many;
 pub mod u32_arithmetic;
 
@@ -56,6 +45,12 @@ pub mod gate_test;
 pub trait CustomGateConstrainer<F: PrimeField>:
     CustomGateConstrainerClone<F> + Send + Sync
 {
+    /// Name of the gate in cost profiles.
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
     fn get_local_ext_algebra(
         &self,
         local_wires: &[AssignedExtensionFieldValue<F, 2>],
@@ -91,7 +86,8 @@ pub trait CustomGateConstrainer<F: PrimeField>:
 
     /// In Plonky2, each custom gate's constraint is multiplied by filtering polynomial
     /// `j`th gate's constraint is filtered by f_j(x) = \prod_{k=0, k \neq j}^{n-1}(f(x) - k) where
-    /// f(g^i) = j if jth gate is used in ith row
+    /// f(g^i) = j if jth gate is used in ith row. The selectors, and the lookup selectors that
+    /// follow them, are stripped from the constants the gate sees.
     fn eval_filtered_constraint(
         &self,
         ctx: &mut RegionCtx<'_, F>,
@@ -103,6 +99,7 @@ pub trait CustomGateConstrainer<F: PrimeField>:
         selector_index: usize,
         group_range: Range<usize>,
         num_selectors: usize,
+        num_lookup_selectors: usize,
         combined_gate_constraints: &mut [AssignedExtensionFieldValue<F, 2>],
     ) -> Result<(), Error> {
         let goldilocks_extension_chip = self.goldilocks_extension_chip(goldilocks_chip_config);
@@ -125,7 +122,7 @@ pub trait CustomGateConstrainer<F: PrimeField>:
             .collect::<Result<Vec<AssignedExtensionFieldValue<F, 2>>, Error>>()?;
         let filter = goldilocks_extension_chip.mul_many_extension(ctx, terms)?;
 
-        local_constants = &local_constants[num_selectors..];
+        local_constants = &local_constants[num_selectors + num_lookup_selectors..];
         let gate_constraints = self.eval_unfiltered_constraint(
             ctx,
             goldilocks_chip_config,
@@ -143,188 +140,12 @@ pub trait CustomGateConstrainer<F: PrimeField>:
 #[derive(Clone)]
 pub struct CustomGateRef<F: PrimeField>(pub Box<dyn CustomGateConstrainer<F>>);
 
-impl<F: PrimeField> From<&GateRef<GoldilocksField, 2>> for CustomGateRef<F> {
-    fn from(value: &GateRef<GoldilocksField, 2>) -> Self {
-        match value.0.id().as_str().trim_end() {
-            "ArithmeticGate { num_ops: 20 }" => Self(Box::new(ArithmeticGateConstrainer {
-                num_ops: value.0.num_ops(),
-            })),
-            "PublicInputGate" => Self(Box::new(PublicInputGateConstrainer)),
-            "NoopGate" => Self(Box::new(NoopGateConstrainer)),
-            "ConstantGate { num_consts: 2 }" => Self(Box::new(ConstantGateConstrainer {
-                num_consts: value.0.num_constants(),
-            })),
-            "BaseSumGate { num_limbs: 63 } + Base: 2" => {
-                Self(Box::new(BaseSumGateConstrainer { num_limbs: 63 }))
-            },
-            "PoseidonGate(PhantomData<plonky2_field::goldilocks_field::GoldilocksField>)<WIDTH=12>" => {
-                Self(Box::new(PoseidonGateConstrainer))
-            },
-            "PoseidonMdsGate(PhantomData<plonky2_field::goldilocks_field::GoldilocksField>)<WIDTH=12>" => {
-                Self(Box::new(PoseidonMDSGateConstrainer))
-            },
-            "RandomAccessGate { bits: 1, num_copies: 20, num_extra_constants: 0, _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>" => {
-                Self(Box::new(RandomAccessGateConstrainer {
-                    bits: 1,
-                    num_copies: 20,
-                    num_extra_constants: 0,
-                }))
-            },
-            "RandomAccessGate { bits: 4, num_copies: 4, num_extra_constants: 2, _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>" => {
-                Self(Box::new(RandomAccessGateConstrainer {
-                    bits: 4,
-                    num_copies: 4,
-                    num_extra_constants: 2,
-                }))
-            },
-            "ReducingExtensionGate { num_coeffs: 32 }" => {
-                Self(Box::new(ReducingExtensionGateConstrainer {
-                    num_coeffs: 32,
-                }))
-            },
-            "ReducingGate { num_coeffs: 43 }" => {
-                Self(Box::new(ReducingGateConstrainer {
-                    num_coeffs: 43,
-                }))
-            },
-            "ArithmeticExtensionGate { num_ops: 10 }" => {
-                Self(Box::new(ArithmeticExtensionGateConstrainer {
-                    num_ops: 10
-                }))
-            },
-            "MulExtensionGate { num_ops: 13 }" => {
-                Self(Box::new(MulExtensionGateConstrainer {
-                    num_ops: 13
-                }))
-            },
-            "BaseSumGate { num_limbs: 4 } + Base: 2" => {
-                Self(Box::new(BaseSumGateConstrainer {
-                    num_limbs: 4
-                }))
-            },
-            "PoseidonMdsGate(PhantomData)<WIDTH=12>" => {
-                Self(Box::new(PoseidonMDSGateConstrainer))
-            },
-            "PoseidonGate(PhantomData)<WIDTH=12>" => {
-                Self(Box::new(PoseidonGateConstrainer))
-            },
-            "RandomAccessGate { bits: 1, num_copies: 20, num_extra_constants: 0, _phantom: PhantomData }<D=2>" => {
-                Self(Box::new(RandomAccessGateConstrainer {
-                    bits: 1,
-                    num_copies: 20,
-                    num_extra_constants: 0,
-                }))
-            },
-            "RandomAccessGate { bits: 4, num_copies: 4, num_extra_constants: 2, _phantom: PhantomData }<D=2>" => {
-                Self(Box::new(RandomAccessGateConstrainer {
-                    bits: 4,
-                    num_copies: 4,
-                    num_extra_constants: 2,
-                }))
-            },
-            "BaseSumGate { num_limbs: 32 } + Base: 2" => {
-                Self(Box::new(BaseSumGateConstrainer {
-                    num_limbs: 32
-                }))
-            },
-            "ComparisonGate { num_bits: 32, num_chunks: 16, _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>" => {
-                Self(Box::new(ComparisonGateContainer {
-                    num_bits: 32,
-                    num_chunks: 16,
-                }))
-            },
-            "ComparisonGate { num_bits: 10, num_chunks: 5, _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>" => {
-                Self(Box::new(ComparisonGateContainer {
-                    num_bits: 10,
-                    num_chunks: 5,
-                }))
-            }
-            "U32AddManyGate { num_addends: 2, num_ops: 5, _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }" => {
-                Self(Box::new(U32AddManyGateConstrainer {
-                    num_addends: 2,
-                    num_ops: 5,
-                }))
-            },
-            "U32AddManyGate { num_addends: 3, num_ops: 5, _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }" => {
-                Self(Box::new(U32AddManyGateConstrainer {
-                    num_addends: 3,
-                    num_ops: 5,
-                }))
-            },
-            "U32ArithmeticGate { num_ops: 3, _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }" => {
-                Self(Box::new(U32ArithmeticGateConstrainer {
-                    num_ops: 3,
-                }))
-            },
-            "CosetInterpolationGate { subgroup_bits: 4, degree: 6, barycentric_weights: [17293822565076172801, 18374686475376656385, 18446744069413535745, 281474976645120, 17592186044416, 256, 18446744000695107601, 18446744065119617025, 1152921504338411520, 72057594037927936, 1048576, 18446462594437939201, 18446726477228539905, 18446744069414584065, 68719476720, 4294967296], _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>" => {
-                Self(Box::new(CosetInterpolationGateConstrainer {
-                    subgroup_bits: 4,
-                    degree: 6,
-                    barycentric_weights: vec![
-                        GoldilocksField::from_canonical_u64(17293822565076172801),
-                        GoldilocksField::from_canonical_u64(18374686475376656385),
-                        GoldilocksField::from_canonical_u64(18446744069413535745),
-                        GoldilocksField::from_canonical_u64(281474976645120),
-                        GoldilocksField::from_canonical_u64(17592186044416),
-                        GoldilocksField::from_canonical_u64(256),
-                        GoldilocksField::from_canonical_u64(18446744000695107601),
-                        GoldilocksField::from_canonical_u64(18446744065119617025),
-                        GoldilocksField::from_canonical_u64(1152921504338411520),
-                        GoldilocksField::from_canonical_u64(72057594037927936),
-                        GoldilocksField::from_canonical_u64(1048576),
-                        GoldilocksField::from_canonical_u64(18446462594437939201),
-                        GoldilocksField::from_canonical_u64(18446726477228539905),
-                        GoldilocksField::from_canonical_u64(18446744069414584065),
-                        GoldilocksField::from_canonical_u64(68719476720),
-                        GoldilocksField::from_canonical_u64(4294967296),
-                    ],
-                }))
-            },
-            "CosetInterpolationGate { subgroup_bits: 1, degree: 2, barycentric_weights: [9223372034707292161, 9223372034707292160], _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>" => {
-                Self(Box::new(CosetInterpolationGateConstrainer {
-                    subgroup_bits: 1,
-                    degree: 2,
-                    barycentric_weights: vec![
-                        GoldilocksField::from_canonical_u64(9223372034707292161),
-                        GoldilocksField::from_canonical_u64(9223372034707292160)
-                    ],
-                }))
-            },
-            "CosetInterpolationGate { subgroup_bits: 4, degree: 6, barycentric_weights: [17293822565076172801, 256, 1048576, 4294967296, 17592186044416, 72057594037927936, 68719476720, 281474976645120, 1152921504338411520, 18446744069414584065, 18446744069413535745, 18446744065119617025, 18446726477228539905, 18374686475376656385, 18446744000695107601, 18446462594437939201], _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>" => {
-                Self(Box::new(CosetInterpolationGateConstrainer {
-                    subgroup_bits: 4,
-                    degree: 6,
-                    barycentric_weights: vec![
-                        GoldilocksField::from_canonical_u64(17293822565076172801), 
-                        GoldilocksField::from_canonical_u64(256), 
-                        GoldilocksField::from_canonical_u64(1048576), 
-                        GoldilocksField::from_canonical_u64(4294967296), 
-                        GoldilocksField::from_canonical_u64(17592186044416), 
-                        GoldilocksField::from_canonical_u64(72057594037927936), 
-                        GoldilocksField::from_canonical_u64(68719476720), 
-                        GoldilocksField::from_canonical_u64(281474976645120), 
-                        GoldilocksField::from_canonical_u64(1152921504338411520), 
-                        GoldilocksField::from_canonical_u64(18446744069414584065), 
-                        GoldilocksField::from_canonical_u64(18446744069413535745), 
-                        GoldilocksField::from_canonical_u64(18446744065119617025), 
-                        GoldilocksField::from_canonical_u64(18446726477228539905), 
-                        GoldilocksField::from_canonical_u64(18374686475376656385), 
-                        GoldilocksField::from_canonical_u64(18446744000695107601), 
-                        GoldilocksField::from_canonical_u64(18446462594437939201)
-                    ]
-                }))
-            },
-            "U32AddManyGate { num_addends: 4, num_ops: 5, _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }" => {
-                Self(Box::new(U32AddManyGateConstrainer {
-                    num_addends: 4,
-                    num_ops: 5,
-                }))
-            }
-            s => {
-                println!("{s}");
-                unimplemented!()
-            }
-        }
+impl<F: PrimeField> TryFrom<&GateRef<GoldilocksField, 2>> for CustomGateRef<F> {
+    type Error = VerifierError;
+
+    /// Looks the gate up in the default `GateRegistry`.
+    fn try_from(value: &GateRef<GoldilocksField, 2>) -> Result<Self, Self::Error> {
+        GateRegistry::default().constrainer(value)
     }
 }
 
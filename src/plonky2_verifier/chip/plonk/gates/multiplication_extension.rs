@@ -74,7 +74,7 @@ impl<F: PrimeField> CustomGateConstrainer<F> for MulExtensionGateConstrainer {
 #[cfg(test)]
 mod tests {
     use super::MulExtensionGateConstrainer;
-    use crate::plonky2_verifier::chip::plonk::gates::gate_test::test_custom_gate;
+    use crate::plonky2_verifier::chip::plonk::gates::gate_test::{narrow_config, test_custom_gate};
     use plonky2::{
         gates::multiplication_extension::MulExtensionGate, plonk::circuit_data::CircuitConfig,
     };
@@ -88,4 +88,14 @@ mod tests {
         };
         test_custom_gate(plonky2_gate, halo2_gate, 17);
     }
+
+    #[test]
+    fn test_mul_extension_gate_narrow_config() {
+        let plonky2_gate = MulExtensionGate::new_from_config(&narrow_config());
+        assert_eq!(plonky2_gate.num_ops, 4);
+        let halo2_gate = MulExtensionGateConstrainer {
+            num_ops: plonky2_gate.num_ops,
+        };
+        test_custom_gate(plonky2_gate, halo2_gate, 17);
+    }
 }
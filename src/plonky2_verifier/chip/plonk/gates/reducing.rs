@@ -100,4 +100,13 @@ mod tests {
         };
         test_custom_gate(plonky2_gate, halo2_gate, 17);
     }
+
+    #[test]
+    fn test_reducing_gate_max_coeffs() {
+        let plonky2_gate = ReducingGate::<2>::new(ReducingGate::<2>::max_coeffs_len(80, 80));
+        let halo2_gate = ReducingGateConstrainer {
+            num_coeffs: plonky2_gate.num_coeffs,
+        };
+        test_custom_gate(plonky2_gate, halo2_gate, 17);
+    }
 }
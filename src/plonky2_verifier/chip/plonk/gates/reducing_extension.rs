@@ -102,4 +102,14 @@ mod tests {
         };
         test_custom_gate(plonky2_gate, halo2_gate, 17);
     }
+
+    #[test]
+    fn test_reducing_extension_gate_max_coeffs() {
+        let plonky2_gate =
+            ReducingExtensionGate::<2>::new(ReducingExtensionGate::<2>::max_coeffs_len(80, 80));
+        let halo2_gate = ReducingExtensionGateConstrainer {
+            num_coeffs: plonky2_gate.num_coeffs,
+        };
+        test_custom_gate(plonky2_gate, halo2_gate, 17);
+    }
 }
@@ -0,0 +1,520 @@
+//! Lookup of the constrainer of a plonky2 gate.
+//!
+//! Gates are identified by the type name at the start of `Gate::id()`, e.g. `ArithmeticGate` in
+//! `ArithmeticGate { num_ops: 20 }`. Their parameters are parsed from the rest of the id into
+//! `GateParams` and handed to the parser registered for that name, so the exact `Debug` output of
+//! plonky2 (field order, `PhantomData` paths, spacing) does not matter.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use halo2_proofs::halo2curves::ff::PrimeField;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::Field;
+use plonky2::gates::gate::GateRef;
+
+use crate::plonky2_verifier::chip::native_chip::arithmetic_chip::GOLDILOCKS_MODULUS;
+use crate::plonky2_verifier::error::VerifierError;
+
+use super::arithmetic::ArithmeticGateConstrainer;
+use super::arithmetic_extension::ArithmeticExtensionGateConstrainer;
+use super::base_sum::BaseSumGateConstrainer;
+use super::comparison_gate::ComparisonGateContainer;
+use super::constant::ConstantGateConstrainer;
+use super::coset_interpolation_gate::CosetInterpolationGateConstrainer;
+use super::lookup::LookupGateConstrainer;
+use super::lookup_table::LookupTableGateConstrainer;
+use super::multiplication_extension::MulExtensionGateConstrainer;
+use super::noop::NoopGateConstrainer;
+use super::poseidon::PoseidonGateConstrainer;
+use super::poseidon_mds::PoseidonMDSGateConstrainer;
+use super::public_input::PublicInputGateConstrainer;
+use super::random_access::RandomAccessGateConstrainer;
+use super::reducing::ReducingGateConstrainer;
+use super::reducing_extension::ReducingExtensionGateConstrainer;
+use super::u32_add_many::U32AddManyGateConstrainer;
+use super::u32_arithmetic::U32ArithmeticGateConstrainer;
+use super::CustomGateRef;
+
+/// Name and parameters of a plonky2 gate, parsed from its id.
+///
+/// Named fields (`num_ops: 20`), suffixes such as `+ Base: 2` and const generics such as
+/// `<WIDTH=12>` all end up as `(key, value)` pairs; unnamed tuple fields are skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GateParams {
+    pub id: String,
+    pub name: String,
+    params: Vec<(String, String)>,
+}
+
+impl GateParams {
+    pub fn parse(id: &str) -> Result<Self, VerifierError> {
+        let id = id.trim();
+        let name_len = id
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(id.len());
+        if name_len == 0 {
+            return Err(VerifierError::UnsupportedGate(id.to_string()));
+        }
+        let mut params = vec![];
+        let mut rest = id[name_len..].trim_start();
+        while let Some(open) = rest.chars().next() {
+            match open {
+                '{' | '(' | '<' => {
+                    let close = matching_bracket(rest)
+                        .ok_or_else(|| unsupported(id, "unbalanced brackets"))?;
+                    // tuple fields have no names, e.g. `PoseidonGate(PhantomData<F>)`
+                    if open != '(' {
+                        parse_fields(&rest[1..close], &mut params);
+                    }
+                    rest = rest[close + 1..].trim_start();
+                }
+                '+' => {
+                    parse_fields(&rest[1..], &mut params);
+                    rest = "";
+                }
+                _ => return Err(unsupported(id, "unexpected characters")),
+            }
+        }
+        Ok(Self {
+            id: id.to_string(),
+            name: id[..name_len].to_string(),
+            params,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn usize(&self, key: &str) -> Result<usize, VerifierError> {
+        let value = self
+            .get(key)
+            .ok_or_else(|| unsupported(&self.id, &format!("missing {key}")))?;
+        value
+            .parse()
+            .map_err(|_| unsupported(&self.id, &format!("{key} is not an integer")))
+    }
+
+    /// Parses a list of canonical Goldilocks elements, e.g. `[1, 2, 3]`.
+    pub fn goldilocks_list(&self, key: &str) -> Result<Vec<GoldilocksField>, VerifierError> {
+        let value = self
+            .get(key)
+            .ok_or_else(|| unsupported(&self.id, &format!("missing {key}")))?;
+        let list = value
+            .strip_prefix('[')
+            .and_then(|v| v.strip_suffix(']'))
+            .ok_or_else(|| unsupported(&self.id, &format!("{key} is not a list")))?;
+        list.split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(|e| {
+                e.parse::<u64>()
+                    .ok()
+                    .filter(|e| *e < GOLDILOCKS_MODULUS)
+                    .map(GoldilocksField::from_canonical_u64)
+                    .ok_or_else(|| unsupported(&self.id, &format!("{key} has invalid element {e}")))
+            })
+            .collect()
+    }
+
+    /// Fails unless `key` is absent or equal to `expected`, e.g. the const generic `D=2`.
+    pub fn expect(&self, key: &str, expected: &str) -> Result<(), VerifierError> {
+        match self.get(key) {
+            Some(value) if value != expected => Err(unsupported(
+                &self.id,
+                &format!("{key} = {value}, only {expected} is supported"),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn unsupported(id: &str, reason: &str) -> VerifierError {
+    VerifierError::UnsupportedGate(format!("{id}: {reason}"))
+}
+
+/// Returns the index of the bracket closing the one `s` starts with.
+fn matching_bracket(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' | '(' | '[' | '<' => depth += 1,
+            '}' | ')' | ']' | '>' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits `key: value` or `key=value` pairs separated by top level commas.
+fn parse_fields(s: &str, params: &mut Vec<(String, String)>) {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut fields = vec![];
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' | '(' | '[' | '<' => depth += 1,
+            '}' | ')' | ']' | '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                fields.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(&s[start..]);
+    for field in fields {
+        if let Some(split) = field.find([':', '=']) {
+            let key = field[..split].trim();
+            let value = field[split + 1..].trim();
+            if !key.is_empty() && !key.contains(|c: char| c.is_whitespace()) {
+                params.push((key.to_string(), value.to_string()));
+            }
+        }
+    }
+}
+
+pub type GateParser<F> =
+    Arc<dyn Fn(&GateParams) -> Result<CustomGateRef<F>, VerifierError> + Send + Sync>;
+
+/// Maps plonky2 gate names to the parsers building their constrainers.
+///
+/// `GateRegistry::default()` knows the gates of this crate, downstream crates `register` their
+/// own gates (or override the built in ones) and pass the registry to `CommonData::from_plonky2`.
+#[derive(Clone)]
+pub struct GateRegistry<F: PrimeField> {
+    parsers: HashMap<String, GateParser<F>>,
+}
+
+impl<F: PrimeField> GateRegistry<F> {
+    /// A registry without any gate.
+    pub fn empty() -> Self {
+        Self {
+            parsers: HashMap::new(),
+        }
+    }
+
+    pub fn register(
+        &mut self,
+        name: &str,
+        parser: impl Fn(&GateParams) -> Result<CustomGateRef<F>, VerifierError> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.parsers.insert(name.to_string(), Arc::new(parser));
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.parsers.contains_key(name)
+    }
+
+    pub fn parse(&self, id: &str) -> Result<CustomGateRef<F>, VerifierError> {
+        let params = GateParams::parse(id)?;
+        let parser = self
+            .parsers
+            .get(&params.name)
+            .ok_or_else(|| VerifierError::UnsupportedGate(params.id.clone()))?;
+        parser(&params)
+    }
+
+    pub fn constrainer(
+        &self,
+        gate: &GateRef<GoldilocksField, 2>,
+    ) -> Result<CustomGateRef<F>, VerifierError> {
+        self.parse(&gate.0.id())
+    }
+}
+
+impl<F: PrimeField> Default for GateRegistry<F> {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register("NoopGate", |_| {
+                Ok(CustomGateRef(Box::new(NoopGateConstrainer)))
+            })
+            .register("PublicInputGate", |_| {
+                Ok(CustomGateRef(Box::new(PublicInputGateConstrainer)))
+            })
+            .register("ArithmeticGate", |p| {
+                Ok(CustomGateRef(Box::new(ArithmeticGateConstrainer {
+                    num_ops: p.usize("num_ops")?,
+                })))
+            })
+            .register("ArithmeticExtensionGate", |p| {
+                Ok(CustomGateRef(Box::new(
+                    ArithmeticExtensionGateConstrainer {
+                        num_ops: p.usize("num_ops")?,
+                    },
+                )))
+            })
+            .register("MulExtensionGate", |p| {
+                Ok(CustomGateRef(Box::new(MulExtensionGateConstrainer {
+                    num_ops: p.usize("num_ops")?,
+                })))
+            })
+            .register("ConstantGate", |p| {
+                Ok(CustomGateRef(Box::new(ConstantGateConstrainer {
+                    num_consts: p.usize("num_consts")?,
+                })))
+            })
+            .register("BaseSumGate", |p| {
+                Ok(CustomGateRef(Box::new(BaseSumGateConstrainer {
+                    num_limbs: p.usize("num_limbs")?,
+                    base: p.usize("Base")?,
+                })))
+            })
+            .register("ReducingGate", |p| {
+                Ok(CustomGateRef(Box::new(ReducingGateConstrainer {
+                    num_coeffs: p.usize("num_coeffs")?,
+                })))
+            })
+            .register("ReducingExtensionGate", |p| {
+                Ok(CustomGateRef(Box::new(ReducingExtensionGateConstrainer {
+                    num_coeffs: p.usize("num_coeffs")?,
+                })))
+            })
+            .register("PoseidonGate", |p| {
+                p.expect("WIDTH", "12")?;
+                Ok(CustomGateRef(Box::new(PoseidonGateConstrainer)))
+            })
+            .register("PoseidonMdsGate", |p| {
+                p.expect("WIDTH", "12")?;
+                Ok(CustomGateRef(Box::new(PoseidonMDSGateConstrainer)))
+            })
+            .register("RandomAccessGate", |p| {
+                p.expect("D", "2")?;
+                Ok(CustomGateRef(Box::new(RandomAccessGateConstrainer {
+                    bits: p.usize("bits")?,
+                    num_copies: p.usize("num_copies")?,
+                    num_extra_constants: p.usize("num_extra_constants")?,
+                })))
+            })
+            .register("ComparisonGate", |p| {
+                p.expect("D", "2")?;
+                Ok(CustomGateRef(Box::new(ComparisonGateContainer {
+                    num_bits: p.usize("num_bits")?,
+                    num_chunks: p.usize("num_chunks")?,
+                })))
+            })
+            .register("U32AddManyGate", |p| {
+                Ok(CustomGateRef(Box::new(U32AddManyGateConstrainer {
+                    num_addends: p.usize("num_addends")?,
+                    num_ops: p.usize("num_ops")?,
+                })))
+            })
+            .register("U32ArithmeticGate", |p| {
+                Ok(CustomGateRef(Box::new(U32ArithmeticGateConstrainer {
+                    num_ops: p.usize("num_ops")?,
+                })))
+            })
+            .register("CosetInterpolationGate", |p| {
+                p.expect("D", "2")?;
+                let subgroup_bits = p.usize("subgroup_bits")?;
+                let degree = p.usize("degree")?;
+                if subgroup_bits == 0
+                    || subgroup_bits > GoldilocksField::TWO_ADICITY
+                    || degree < 2
+                    || degree > 1 << subgroup_bits
+                {
+                    return Err(unsupported(&p.id, "invalid shape"));
+                }
+                // the order of the weights in the id is the order of the value wires
+                let constrainer = match p.get("barycentric_weights") {
+                    Some(_) => CosetInterpolationGateConstrainer::with_weights(
+                        subgroup_bits,
+                        degree,
+                        p.goldilocks_list("barycentric_weights")?,
+                    )
+                    .ok_or_else(|| unsupported(&p.id, "barycentric weights of another subgroup"))?,
+                    None => CosetInterpolationGateConstrainer::with_degree(subgroup_bits, degree),
+                };
+                Ok(CustomGateRef(Box::new(constrainer)))
+            })
+            // the tables themselves come from `CommonCircuitData::luts`, ids only carry their hash
+            .register("LookupGate", |p| {
+                Ok(CustomGateRef(Box::new(LookupGateConstrainer {
+                    num_slots: p.usize("num_slots")?,
+                })))
+            })
+            .register("LookupTableGate", |p| {
+                Ok(CustomGateRef(Box::new(LookupTableGateConstrainer {
+                    num_slots: p.usize("num_slots")?,
+                    last_lut_row: p.usize("last_lut_row")?,
+                })))
+            });
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use itertools::Itertools;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::gates::{
+        arithmetic_base::ArithmeticGate, arithmetic_extension::ArithmeticExtensionGate,
+        base_sum::BaseSumGate, constant::ConstantGate, coset_interpolation::CosetInterpolationGate,
+        gate::GateRef, lookup::LookupGate, lookup_table::LookupTableGate,
+        multiplication_extension::MulExtensionGate, reducing::ReducingGate,
+        reducing_extension::ReducingExtensionGate,
+    };
+    use plonky2::plonk::circuit_data::CircuitConfig;
+
+    use super::{GateParams, GateRegistry};
+    use crate::plonky2_verifier::chip::plonk::gates::{noop::NoopGateConstrainer, CustomGateRef};
+    use crate::plonky2_verifier::error::VerifierError;
+
+    #[test]
+    fn test_parse_gate_params() {
+        let params = GateParams::parse(
+            "RandomAccessGate { bits: 4, num_copies: 4, num_extra_constants: 2, _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>",
+        )
+        .unwrap();
+        assert_eq!(params.name, "RandomAccessGate");
+        assert_eq!(params.usize("bits").unwrap(), 4);
+        assert_eq!(params.usize("num_extra_constants").unwrap(), 2);
+        assert_eq!(params.get("D"), Some("2"));
+
+        let params = GateParams::parse("BaseSumGate { num_limbs: 63 } + Base: 2").unwrap();
+        assert_eq!(params.usize("num_limbs").unwrap(), 63);
+        assert_eq!(params.get("Base"), Some("2"));
+
+        let params = GateParams::parse("PoseidonGate(PhantomData)<WIDTH=12>").unwrap();
+        assert_eq!(params.name, "PoseidonGate");
+        assert_eq!(params.get("WIDTH"), Some("12"));
+
+        let params = GateParams::parse(
+            "CosetInterpolationGate { subgroup_bits: 1, degree: 2, barycentric_weights: [9223372034707292161, 9223372034707292160], _phantom: PhantomData }<D=2>",
+        )
+        .unwrap();
+        assert_eq!(
+            params.goldilocks_list("barycentric_weights").unwrap().len(),
+            2
+        );
+
+        assert_eq!(GateParams::parse("NoopGate").unwrap().name, "NoopGate");
+        assert!(GateParams::parse("{ num_ops: 1 }").is_err());
+    }
+
+    #[test]
+    fn test_gate_registry() {
+        let mut registry = GateRegistry::<Fr>::default();
+        // field order and `PhantomData` paths do not matter
+        assert!(registry
+            .parse("U32AddManyGate { num_ops: 5, num_addends: 3, _phantom: PhantomData }")
+            .is_ok());
+        assert!(matches!(
+            registry.parse("PoseidonGate(PhantomData)<WIDTH=8>"),
+            Err(VerifierError::UnsupportedGate(_))
+        ));
+        assert!(matches!(
+            registry.parse("MyGate { size: 3 }"),
+            Err(VerifierError::UnsupportedGate(_))
+        ));
+        registry.register("MyGate", |p| {
+            p.usize("size")?;
+            Ok(CustomGateRef(Box::new(NoopGateConstrainer)))
+        });
+        assert!(registry.parse("MyGate { size: 3 }").is_ok());
+    }
+
+    #[test]
+    fn test_gate_registry_any_config() {
+        let config = CircuitConfig {
+            num_wires: 60,
+            num_routed_wires: 28,
+            num_constants: 3,
+            ..CircuitConfig::standard_recursion_config()
+        };
+        let gates: Vec<GateRef<GoldilocksField, 2>> = vec![
+            GateRef::new(ArithmeticGate::new_from_config(&config)),
+            GateRef::new(ArithmeticExtensionGate::new_from_config(&config)),
+            GateRef::new(MulExtensionGate::new_from_config(&config)),
+            GateRef::new(ReducingGate::new(ReducingGate::<2>::max_coeffs_len(
+                config.num_wires,
+                config.num_routed_wires,
+            ))),
+            GateRef::new(ReducingExtensionGate::new(
+                ReducingExtensionGate::<2>::max_coeffs_len(
+                    config.num_wires,
+                    config.num_routed_wires,
+                ),
+            )),
+            GateRef::new(ConstantGate::new(config.num_constants)),
+            GateRef::new(BaseSumGate::<4>::new_from_config::<GoldilocksField>(
+                &config,
+            )),
+        ];
+        let registry = GateRegistry::<Fr>::default();
+        for gate in gates.iter() {
+            assert!(registry.constrainer(gate).is_ok(), "{}", gate.0.id());
+        }
+    }
+
+    #[test]
+    fn test_gate_registry_lookup() {
+        let config = CircuitConfig::standard_recursion_config();
+        let lut = Arc::new(
+            (0..256u16)
+                .map(|i| (i, i.reverse_bits() >> 8))
+                .collect_vec(),
+        );
+        let gates: Vec<GateRef<GoldilocksField, 2>> = vec![
+            GateRef::new(LookupGate::new_from_table(&config, lut.clone())),
+            GateRef::new(LookupTableGate::new_from_table(&config, lut, 1000)),
+        ];
+        let registry = GateRegistry::<Fr>::default();
+        for gate in gates.iter() {
+            assert!(registry.constrainer(gate).is_ok(), "{}", gate.0.id());
+        }
+    }
+
+    #[test]
+    fn test_gate_registry_coset_interpolation() {
+        let registry = GateRegistry::<Fr>::default();
+        for subgroup_bits in 1..=4 {
+            let gate =
+                GateRef::<GoldilocksField, 2>::new(CosetInterpolationGate::new(subgroup_bits));
+            assert!(registry.constrainer(&gate).is_ok(), "{}", gate.0.id());
+        }
+        // the subgroup in the order `[-1, 1]`
+        assert!(registry
+            .parse(
+                "CosetInterpolationGate { subgroup_bits: 1, degree: 2, barycentric_weights: [9223372034707292160, 9223372034707292161], _phantom: PhantomData }<D=2>",
+            )
+            .is_ok());
+        assert!(matches!(
+            registry.parse(
+                "CosetInterpolationGate { subgroup_bits: 1, degree: 2, barycentric_weights: [9223372034707292161, 9223372034707292161], _phantom: PhantomData }<D=2>",
+            ),
+            Err(VerifierError::UnsupportedGate(_))
+        ));
+        assert!(matches!(
+            registry.parse("CosetInterpolationGate { subgroup_bits: 2, degree: 5 }<D=2>"),
+            Err(VerifierError::UnsupportedGate(_))
+        ));
+    }
+
+    /// Ids of `CosetInterpolationGate` from plonky2 versions ordering the subgroup differently.
+    #[test]
+    fn test_gate_registry_coset_interpolation_orderings() {
+        let registry = GateRegistry::<Fr>::default();
+        let ids = [
+            "CosetInterpolationGate { subgroup_bits: 4, degree: 6, barycentric_weights: [17293822565076172801, 18374686475376656385, 18446744069413535745, 281474976645120, 17592186044416, 256, 18446744000695107601, 18446744065119617025, 1152921504338411520, 72057594037927936, 1048576, 18446462594437939201, 18446726477228539905, 18446744069414584065, 68719476720, 4294967296], _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>",
+            "CosetInterpolationGate { subgroup_bits: 4, degree: 6, barycentric_weights: [17293822565076172801, 256, 1048576, 4294967296, 17592186044416, 72057594037927936, 68719476720, 281474976645120, 1152921504338411520, 18446744069414584065, 18446744069413535745, 18446744065119617025, 18446726477228539905, 18374686475376656385, 18446744000695107601, 18446462594437939201], _phantom: PhantomData<plonky2_field::goldilocks_field::GoldilocksField> }<D=2>",
+        ];
+        for id in ids {
+            assert!(registry.parse(id).is_ok(), "{id}");
+        }
+    }
+}
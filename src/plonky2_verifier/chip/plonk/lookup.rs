@@ -0,0 +1,319 @@
+use std::cmp::min;
+
+use crate::plonky2_verifier::context::RegionCtx;
+use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
+use halo2wrong_maingate::AssignedValue;
+use plonky2::field::{goldilocks_field::GoldilocksField, types::Field};
+
+use crate::plonky2_verifier::{
+    chip::{
+        goldilocks_extension_chip::GoldilocksExtensionChip,
+        hasher_chip::AlgebraicHasherChip,
+        plonk::{
+            gates::{lookup::LookupGateConstrainer, lookup_table::LookupTableGateConstrainer},
+            plonk_verifier_chip::PlonkVerifierChip,
+        },
+    },
+    types::{assigned::AssignedExtensionFieldValue, common_data::CommonData},
+};
+
+/// Number of lookup challenges drawn per challenge round.
+pub const NUM_COINS_LOOKUP: usize = 4;
+
+/// Position of each lookup challenge in the deltas of a challenge round.
+#[derive(Clone, Copy, Debug)]
+pub enum LookupChallenges {
+    /// Combines the inputs and outputs of the lookups and of the table entries.
+    ChallengeA = 0,
+    /// Combines the inputs and outputs of the table entries when checking the table itself.
+    ChallengeB = 1,
+    /// Point at which the logarithmic derivatives are evaluated.
+    ChallengeAlpha = 2,
+    /// Point at which the table polynomial is evaluated.
+    ChallengeDelta = 3,
+}
+
+/// Position of each lookup selector after the gate selectors in the constants.
+#[derive(Clone, Copy, Debug)]
+pub enum LookupSelectors {
+    /// Rows of the RE and Sum transitions, i.e. of the `LookupTableGate`s.
+    TransSre = 0,
+    /// Rows of the LDC transitions, i.e. of the `LookupGate`s.
+    TransLdc = 1,
+    /// First row of the RE and Sum polynomials.
+    InitSre = 2,
+    /// Last row of the LDC polynomials.
+    LastLdc = 3,
+    /// Rows where each table starts, one selector per table from here on.
+    StartEnd = 4,
+}
+
+impl<F: PrimeField, H: AlgebraicHasherChip<F>> PlonkVerifierChip<F, H> {
+    /// Lookup terms of the vanishing polynomial for one challenge round: the boundary constraints
+    /// of the RE, Sum and LDC polynomials, the final value of RE for every table and their
+    /// transitions, in the order of plonky2's `check_lookup_constraints`.
+    pub(super) fn check_lookup_constraints(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        common_data: &CommonData<F>,
+        local_wires: &[AssignedExtensionFieldValue<F, 2>],
+        local_lookup_zs: &[AssignedExtensionFieldValue<F, 2>],
+        next_lookup_zs: &[AssignedExtensionFieldValue<F, 2>],
+        lookup_selectors: &[AssignedExtensionFieldValue<F, 2>],
+        deltas: &[AssignedValue<F>],
+    ) -> Result<Vec<AssignedExtensionFieldValue<F, 2>>, Error> {
+        let goldilocks_extension_chip = GoldilocksExtensionChip::new(&self.goldilocks_chip_config);
+        let num_lu_slots = LookupGateConstrainer::num_slots(&common_data.config);
+        let num_lut_slots = LookupTableGateConstrainer::num_slots(&common_data.config);
+        let lu_degree = common_data.quotient_degree_factor - 1;
+        let num_sldc_polys = local_lookup_zs.len() - 1;
+        let lut_degree = num_lut_slots.div_ceil(num_sldc_polys);
+
+        let mut constraints = Vec::with_capacity(4 + common_data.luts.len() + 2 * num_sldc_polys);
+
+        // RE is the first polynomial stored.
+        let z_re = &local_lookup_zs[0];
+        let next_z_re = &next_lookup_zs[0];
+
+        // Partial Sums and LDCs are both stored in the remaining SLDC polynomials.
+        let z_x_lookup_sldcs = &local_lookup_zs[1..num_sldc_polys + 1];
+        let z_gx_lookup_sldcs = &next_lookup_zs[1..num_sldc_polys + 1];
+
+        let challenge_a = goldilocks_extension_chip
+            .convert_to_extension(ctx, &deltas[LookupChallenges::ChallengeA as usize])?;
+        let challenge_b = goldilocks_extension_chip
+            .convert_to_extension(ctx, &deltas[LookupChallenges::ChallengeB as usize])?;
+        let challenge_alpha = goldilocks_extension_chip
+            .convert_to_extension(ctx, &deltas[LookupChallenges::ChallengeAlpha as usize])?;
+        let challenge_delta = goldilocks_extension_chip
+            .convert_to_extension(ctx, &deltas[LookupChallenges::ChallengeDelta as usize])?;
+
+        // `input + challenge * output` of every slot
+        let combos = |ctx: &mut RegionCtx<'_, F>,
+                      challenge: &AssignedExtensionFieldValue<F, 2>,
+                      wires: Vec<(usize, usize)>| {
+            wires
+                .into_iter()
+                .map(|(input, output)| {
+                    goldilocks_extension_chip.mul_add_extension(
+                        ctx,
+                        challenge,
+                        &local_wires[output],
+                        &local_wires[input],
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        };
+        let looked_wires = (0..num_lut_slots)
+            .map(|s| {
+                (
+                    LookupTableGateConstrainer::wire_ith_looked_inp(s),
+                    LookupTableGateConstrainer::wire_ith_looked_out(s),
+                )
+            })
+            .collect::<Vec<_>>();
+        let looking_wires = (0..num_lu_slots)
+            .map(|s| {
+                (
+                    LookupGateConstrainer::wire_ith_looking_inp(s),
+                    LookupGateConstrainer::wire_ith_looking_out(s),
+                )
+            })
+            .collect::<Vec<_>>();
+        // Combos of the SLDC polynomials.
+        let current_looked_combos = combos(ctx, &challenge_a, looked_wires.clone())?;
+        let current_looking_combos = combos(ctx, &challenge_a, looking_wires)?;
+        // Combos checking that the table is correct.
+        let current_lookup_combos = combos(ctx, &challenge_b, looked_wires)?;
+
+        let selector = |selector: LookupSelectors| &lookup_selectors[selector as usize];
+
+        // Check last LDC constraint.
+        constraints.push(goldilocks_extension_chip.mul_extension(
+            ctx,
+            selector(LookupSelectors::LastLdc),
+            &z_x_lookup_sldcs[num_sldc_polys - 1],
+        )?);
+
+        // Check initial Sum constraint.
+        constraints.push(goldilocks_extension_chip.mul_extension(
+            ctx,
+            selector(LookupSelectors::InitSre),
+            &z_x_lookup_sldcs[0],
+        )?);
+
+        // Check initial RE constraint.
+        constraints.push(goldilocks_extension_chip.mul_extension(
+            ctx,
+            selector(LookupSelectors::InitSre),
+            z_re,
+        )?);
+
+        // Check final RE constraints for each different LUT.
+        for r in LookupSelectors::StartEnd as usize..common_data.num_lookup_selectors {
+            let lut = &common_data.luts[r - LookupSelectors::StartEnd as usize];
+            let lut_row_number = lut.len().div_ceil(num_lut_slots);
+            let cur_function_eval =
+                self.get_lut_poly(ctx, lut, deltas, num_lut_slots * lut_row_number)?;
+            let cur_function_eval =
+                goldilocks_extension_chip.convert_to_extension(ctx, &cur_function_eval)?;
+            let diff = goldilocks_extension_chip.sub_extension(ctx, z_re, &cur_function_eval)?;
+            constraints.push(goldilocks_extension_chip.mul_extension(
+                ctx,
+                &lookup_selectors[r],
+                &diff,
+            )?);
+        }
+
+        // Check RE row transition constraint.
+        let mut cur_sum = next_z_re.clone();
+        for elt in current_lookup_combos.iter() {
+            cur_sum = goldilocks_extension_chip.mul_add_extension(
+                ctx,
+                &cur_sum,
+                &challenge_delta,
+                elt,
+            )?;
+        }
+        let unfiltered_re_line = goldilocks_extension_chip.sub_extension(ctx, z_re, &cur_sum)?;
+        constraints.push(goldilocks_extension_chip.mul_extension(
+            ctx,
+            selector(LookupSelectors::TransSre),
+            &unfiltered_re_line,
+        )?);
+
+        for poly in 0..num_sldc_polys {
+            // `alpha - combo` of the slots of the current polynomial, for Sum and for LDC.
+            let lut_terms = (poly * lut_degree..min((poly + 1) * lut_degree, num_lut_slots))
+                .map(|i| {
+                    goldilocks_extension_chip.sub_extension(
+                        ctx,
+                        &challenge_alpha,
+                        &current_looked_combos[i],
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let lu_terms = (poly * lu_degree..min((poly + 1) * lu_degree, num_lu_slots))
+                .map(|i| {
+                    goldilocks_extension_chip.sub_extension(
+                        ctx,
+                        &challenge_alpha,
+                        &current_looking_combos[i],
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            // prod(alpha - combo) for Sum and for LDC.
+            let lut_prod = goldilocks_extension_chip.mul_many_extension(ctx, lut_terms.clone())?;
+            let lu_prod = goldilocks_extension_chip.mul_many_extension(ctx, lu_terms.clone())?;
+
+            // prod_{j != i}(alpha - combo_j)
+            let prod_without = |ctx: &mut RegionCtx<'_, F>,
+                                terms: &[AssignedExtensionFieldValue<F, 2>],
+                                i: usize| {
+                let others = terms
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, term)| term.clone())
+                    .collect();
+                goldilocks_extension_chip.mul_many_extension(ctx, others)
+            };
+
+            // sum_i(prod_{j != i}(alpha - combo_j)) for LDC.
+            let mut lu_sum_prods = goldilocks_extension_chip.zero_extension(ctx)?;
+            for i in 0..lu_terms.len() {
+                let prod_i = prod_without(ctx, &lu_terms, i)?;
+                lu_sum_prods =
+                    goldilocks_extension_chip.add_extension(ctx, &lu_sum_prods, &prod_i)?;
+            }
+
+            // sum_i(mul_i * prod_{j != i}(alpha - combo_j)) for Sum.
+            let mut lut_sum_prods_with_mul = goldilocks_extension_chip.zero_extension(ctx)?;
+            for i in 0..lut_terms.len() {
+                let prod_i = prod_without(ctx, &lut_terms, i)?;
+                let multiplicity = &local_wires
+                    [LookupTableGateConstrainer::wire_ith_multiplicity(poly * lut_degree + i)];
+                lut_sum_prods_with_mul = goldilocks_extension_chip.mul_add_extension(
+                    ctx,
+                    multiplicity,
+                    &prod_i,
+                    &lut_sum_prods_with_mul,
+                )?;
+            }
+
+            // The previous element is the previous poly of the current row or the last poly of the
+            // next row.
+            let prev = if poly == 0 {
+                &z_gx_lookup_sldcs[num_sldc_polys - 1]
+            } else {
+                &z_x_lookup_sldcs[poly - 1]
+            };
+            let z_diff =
+                goldilocks_extension_chip.sub_extension(ctx, &z_x_lookup_sldcs[poly], prev)?;
+
+            // Check Sum row and col transitions. It's the same constraint, with a row transition
+            // happening for slot == 0.
+            let unfiltered_sum_transition = goldilocks_extension_chip.mul_sub_extension(
+                ctx,
+                &lut_prod,
+                &z_diff,
+                &lut_sum_prods_with_mul,
+            )?;
+            constraints.push(goldilocks_extension_chip.mul_extension(
+                ctx,
+                selector(LookupSelectors::TransSre),
+                &unfiltered_sum_transition,
+            )?);
+
+            // Check LDC row and col transitions. It's the same constraint, with a row transition
+            // happening for slot == 0.
+            let unfiltered_ldc_transition = goldilocks_extension_chip.mul_add_extension(
+                ctx,
+                &lu_prod,
+                &z_diff,
+                &lu_sum_prods,
+            )?;
+            constraints.push(goldilocks_extension_chip.mul_extension(
+                ctx,
+                selector(LookupSelectors::TransLdc),
+                &unfiltered_ldc_transition,
+            )?);
+        }
+
+        Ok(constraints)
+    }
+
+    /// Evaluates at `delta` the polynomial of degree `degree - 1` whose coefficients, from the
+    /// leading one down, are the `input + b * output` of the entries of `lut`, then zeros.
+    fn get_lut_poly(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        lut: &[(u16, u16)],
+        deltas: &[AssignedValue<F>],
+        degree: usize,
+    ) -> Result<AssignedValue<F>, Error> {
+        let goldilocks_chip = self.goldilocks_chip();
+        let b = &deltas[LookupChallenges::ChallengeB as usize];
+        let delta = &deltas[LookupChallenges::ChallengeDelta as usize];
+        // Horner's rule on the inputs and on the outputs, combined with `b` at the end.
+        let mut inputs = goldilocks_chip.assign_constant(ctx, GoldilocksField::ZERO)?;
+        let mut outputs = inputs.clone();
+        for k in 0..degree {
+            let (input, output) = lut.get(k).copied().unwrap_or_default();
+            inputs = goldilocks_chip.mul_add_constant(
+                ctx,
+                &inputs,
+                delta,
+                GoldilocksField::from_canonical_u16(input),
+            )?;
+            outputs = goldilocks_chip.mul_add_constant(
+                ctx,
+                &outputs,
+                delta,
+                GoldilocksField::from_canonical_u16(output),
+            )?;
+        }
+        goldilocks_chip.mul_add(ctx, b, &outputs, &inputs)
+    }
+}
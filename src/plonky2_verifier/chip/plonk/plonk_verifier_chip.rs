@@ -4,14 +4,14 @@ use crate::plonky2_verifier::{
         fri_chip::FriVerifierChip,
         goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
         goldilocks_extension_chip::GoldilocksExtensionChip,
-        public_inputs_hasher_chip::PublicInputsHasherChip,
+        hasher_chip::{AlgebraicHasherChip, HasherChip},
+        plonk::lookup::NUM_COINS_LOOKUP,
         transcript_chip::TranscriptChip,
     },
     types::{
         assigned::{
-            AssignedExtensionFieldValue, AssignedFriChallenges, AssignedFriProofValues,
-            AssignedHashValues, AssignedProofChallenges, AssignedProofValues,
-            AssignedVerificationKeyValues,
+            AssignedExtensionFieldValue, AssignedHashValues, AssignedProofChallenges,
+            AssignedProofValues, AssignedVerificationKeyValues,
         },
         common_data::CommonData,
         fri::FriInstanceInfo,
@@ -19,19 +19,26 @@ use crate::plonky2_verifier::{
 };
 use halo2_proofs::{halo2curves::ff::PrimeField, plonk::*};
 use halo2wrong_maingate::AssignedValue;
-use plonky2::field::{
-    goldilocks_field::GoldilocksField,
-    types::{Field, PrimeField64},
+use plonky2::{
+    field::{
+        goldilocks_field::GoldilocksField,
+        types::{Field, PrimeField64},
+    },
+    hash::poseidon::PoseidonHash,
 };
+use std::marker::PhantomData;
 
-pub struct PlonkVerifierChip<F: PrimeField> {
+/// Verifies plonky2 proofs whose Merkle trees and transcript use the hasher `H`.
+pub struct PlonkVerifierChip<F: PrimeField, H: AlgebraicHasherChip<F>> {
     pub goldilocks_chip_config: GoldilocksChipConfig<F>,
+    _marker: PhantomData<H>,
 }
 
-impl<F: PrimeField> PlonkVerifierChip<F> {
+impl<F: PrimeField, H: AlgebraicHasherChip<F>> PlonkVerifierChip<F, H> {
     pub fn construct(goldilocks_chip_config: &GoldilocksChipConfig<F>) -> Self {
         Self {
             goldilocks_chip_config: goldilocks_chip_config.clone(),
+            _marker: PhantomData,
         }
     }
 
@@ -44,12 +51,26 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
         ctx: &mut RegionCtx<'_, F>,
         public_inputs: &Vec<AssignedValue<F>>,
     ) -> Result<AssignedHashValues<F>, Error> {
-        let mut public_inputs_hasher_chip =
-            PublicInputsHasherChip::<F>::new(ctx, &self.goldilocks_chip_config)?;
-        let outputs = public_inputs_hasher_chip.hash(ctx, public_inputs.clone(), 4)?;
-        Ok(AssignedHashValues {
-            elements: outputs.try_into().unwrap(),
-        })
+        // public inputs are hashed with the inner hasher, which is `PoseidonHash` for both
+        // `PoseidonGoldilocksConfig` and `Bn254PoseidonGoldilocksConfig`
+        PoseidonHash::hash_no_pad(ctx, &self.goldilocks_chip_config, public_inputs)
+    }
+
+    /// In-circuit `VerificationKeyValues::digest`, binding a verification key assigned as
+    /// witness to the instances it is checked against.
+    pub fn get_verification_key_digest(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        vk: &AssignedVerificationKeyValues<F>,
+    ) -> Result<AssignedHashValues<F>, Error> {
+        let inputs = vk
+            .constants_sigmas_cap
+            .0
+            .iter()
+            .chain([&vk.circuit_digest])
+            .flat_map(|hash| hash.elements.iter().cloned())
+            .collect::<Vec<_>>();
+        PoseidonHash::hash_no_pad(ctx, &self.goldilocks_chip_config, &inputs)
     }
 
     pub fn get_challenges(
@@ -61,7 +82,7 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
         assigned_proof: &AssignedProofValues<F, 2>,
         num_challenges: usize,
     ) -> Result<AssignedProofChallenges<F, 2>, Error> {
-        let mut transcript_chip = TranscriptChip::<F>::new(ctx, &self.goldilocks_chip_config)?;
+        let mut transcript_chip = TranscriptChip::<F, H>::new(ctx, &self.goldilocks_chip_config)?;
         for e in circuit_digest.elements.iter() {
             transcript_chip.write_scalar(ctx, &e)?;
         }
@@ -75,13 +96,7 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
             plonk_zs_partial_products_cap,
             quotient_polys_cap,
             openings,
-            opening_proof:
-                AssignedFriProofValues {
-                    commit_phase_merkle_cap_values,
-                    final_poly,
-                    pow_witness,
-                    ..
-                },
+            opening_proof,
         } = assigned_proof;
         for hash in wires_cap.0.iter() {
             for e in hash.elements.iter() {
@@ -91,6 +106,16 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
         let plonk_betas = transcript_chip.squeeze(ctx, num_challenges)?;
         let plonk_gammas = transcript_chip.squeeze(ctx, num_challenges)?;
 
+        // The lookup argument reuses the betas and gammas as its first deltas.
+        let plonk_deltas = if common_data.has_lookup() {
+            let num_additional_challenges = (NUM_COINS_LOOKUP - 2) * num_challenges;
+            let additional = transcript_chip.squeeze(ctx, num_additional_challenges)?;
+            [plonk_betas.clone(), plonk_gammas.clone(), additional].concat()
+        } else {
+            vec![]
+        };
+
+        // `plonk_zs_partial_products_cap` also commits to the lookup polynomials.
         for hash in plonk_zs_partial_products_cap.0.iter() {
             for e in hash.elements.iter() {
                 transcript_chip.write_scalar(ctx, &e)?;
@@ -113,43 +138,19 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
             }
         }
 
-        // Scaling factor to combine polynomials.
-        let fri_alpha =
-            AssignedExtensionFieldValue(transcript_chip.squeeze(ctx, 2)?.try_into().unwrap());
-
-        // Recover the random betas used in the FRI reductions.
-        let fri_betas = commit_phase_merkle_cap_values
-            .iter()
-            .map(|cap| {
-                transcript_chip.write_cap(ctx, cap)?;
-                let fri_beta = transcript_chip.squeeze(ctx, 2)?;
-                Ok(AssignedExtensionFieldValue(fri_beta.try_into().unwrap()))
-            })
-            .collect::<Result<Vec<AssignedExtensionFieldValue<F, 2>>, Error>>()?;
-
-        for ext in final_poly.0.iter() {
-            for e in ext.0.iter() {
-                transcript_chip.write_scalar(ctx, &e)?;
-            }
-        }
-
-        transcript_chip.write_scalar(ctx, pow_witness)?;
-        let fri_pow_response = transcript_chip.squeeze(ctx, 1)?[0].clone();
-
-        let num_fri_queries = common_data.config.fri_config.num_query_rounds;
-        let fri_query_indices = transcript_chip.squeeze(ctx, num_fri_queries)?;
+        let fri_challenges = transcript_chip.fri_challenges(
+            ctx,
+            opening_proof,
+            common_data.config.fri_config.num_query_rounds,
+        )?;
 
         Ok(AssignedProofChallenges {
             plonk_betas,
             plonk_gammas,
             plonk_alphas,
+            plonk_deltas,
             plonk_zeta: AssignedExtensionFieldValue(plonk_zeta.try_into().unwrap()),
-            fri_challenges: AssignedFriChallenges {
-                fri_alpha,
-                fri_betas,
-                fri_pow_response,
-                fri_query_indices,
-            },
+            fri_challenges,
         })
     }
 
@@ -170,44 +171,60 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
         let next_zs = &proof.openings.plonk_zs_next;
         let s_sigmas = &proof.openings.plonk_sigmas;
         let partial_products = &proof.openings.partial_products;
+        let local_lookup_zs = &proof.openings.lookup_zs;
+        let next_lookup_zs = &proof.openings.lookup_zs_next;
 
         let zeta_pow_deg = goldilocks_extension_chip.exp_power_of_2_extension(
             ctx,
             challenges.plonk_zeta.clone(),
             common_data.degree_bits(),
         )?;
-        let vanishing_poly_zeta = self.eval_vanishing_poly(
-            ctx,
-            &common_data,
-            &challenges.plonk_zeta,
-            &zeta_pow_deg,
-            local_constants,
-            local_wires,
-            public_inputs_hash,
-            local_zs,
-            next_zs,
-            partial_products,
-            s_sigmas,
-            &challenges.plonk_betas,
-            &challenges.plonk_gammas,
-            &challenges.plonk_alphas,
-        )?;
-        let quotient_polys_zeta = &proof.openings.quotient_polys;
-        let z_h_zeta = goldilocks_extension_chip.sub_extension(ctx, &zeta_pow_deg, &one)?;
-        for (i, chunk) in quotient_polys_zeta
-            .chunks(common_data.quotient_degree_factor)
-            .enumerate()
-        {
-            let recombined_quotient =
-                goldilocks_extension_chip.reduce_extension(ctx, &zeta_pow_deg, &chunk.to_vec())?;
-            let computed_vanishing_poly =
-                goldilocks_extension_chip.mul_extension(ctx, &z_h_zeta, &recombined_quotient)?;
-            goldilocks_extension_chip.assert_equal_extension(
+        let vanishing_poly_zeta = ctx.stage("eval_vanishing_poly", |ctx| {
+            self.eval_vanishing_poly(
                 ctx,
-                &vanishing_poly_zeta[i],
-                &computed_vanishing_poly,
-            )?;
-        }
+                &common_data,
+                &challenges.plonk_zeta,
+                &zeta_pow_deg,
+                local_constants,
+                local_wires,
+                public_inputs_hash,
+                local_zs,
+                next_zs,
+                local_lookup_zs,
+                next_lookup_zs,
+                partial_products,
+                s_sigmas,
+                &challenges.plonk_betas,
+                &challenges.plonk_gammas,
+                &challenges.plonk_alphas,
+                &challenges.plonk_deltas,
+            )
+        })?;
+        ctx.stage("check_quotient", |ctx| {
+            let quotient_polys_zeta = &proof.openings.quotient_polys;
+            let z_h_zeta = goldilocks_extension_chip.sub_extension(ctx, &zeta_pow_deg, &one)?;
+            for (i, chunk) in quotient_polys_zeta
+                .chunks(common_data.quotient_degree_factor)
+                .enumerate()
+            {
+                let recombined_quotient = goldilocks_extension_chip.reduce_extension(
+                    ctx,
+                    &zeta_pow_deg,
+                    &chunk.to_vec(),
+                )?;
+                let computed_vanishing_poly = goldilocks_extension_chip.mul_extension(
+                    ctx,
+                    &z_h_zeta,
+                    &recombined_quotient,
+                )?;
+                goldilocks_extension_chip.assert_equal_extension(
+                    ctx,
+                    &vanishing_poly_zeta[i],
+                    &computed_vanishing_poly,
+                )?;
+            }
+            Ok::<_, Error>(())
+        })?;
 
         let merkle_caps = &[
             vk.constants_sigmas_cap.clone(),
@@ -225,19 +242,20 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
         let offset = self
             .goldilocks_chip()
             .assign_constant(ctx, GoldilocksField::MULTIPLICATIVE_GROUP_GENERATOR)?;
-        let fri_chip = FriVerifierChip::construct(
+        let fri_chip = FriVerifierChip::<F, H>::construct(
             &self.goldilocks_chip_config,
             &offset,
             common_data.fri_params.clone(),
         );
-        fri_chip.verify_fri_proof(
-            ctx,
-            merkle_caps,
-            &challenges.fri_challenges,
-            &proof.openings.to_fri_openings(),
-            &proof.opening_proof,
-            &fri_instance_info,
-        )?;
-        Ok(())
+        ctx.stage("verify_fri_proof", |ctx| {
+            fri_chip.verify_fri_proof(
+                ctx,
+                merkle_caps,
+                &challenges.fri_challenges,
+                &proof.openings.to_fri_openings(),
+                &proof.opening_proof,
+                &fri_instance_info,
+            )
+        })
     }
 }
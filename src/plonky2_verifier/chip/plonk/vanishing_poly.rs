@@ -7,14 +7,15 @@ use plonky2::field::{goldilocks_field::GoldilocksField, types::Field};
 
 use crate::plonky2_verifier::{
     chip::goldilocks_extension_chip::GoldilocksExtensionChip,
-    chip::plonk::plonk_verifier_chip::PlonkVerifierChip,
+    chip::hasher_chip::AlgebraicHasherChip,
+    chip::plonk::{lookup::NUM_COINS_LOOKUP, plonk_verifier_chip::PlonkVerifierChip},
     types::{
         assigned::{AssignedExtensionFieldValue, AssignedHashValues},
         common_data::CommonData,
     },
 };
 
-impl<F: PrimeField> PlonkVerifierChip<F> {
+impl<F: PrimeField, H: AlgebraicHasherChip<F>> PlonkVerifierChip<F, H> {
     pub fn eval_vanishing_poly(
         &self,
         ctx: &mut RegionCtx<'_, F>,
@@ -26,26 +27,37 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
         public_inputs_hash: &AssignedHashValues<F>,
         local_zs: &[AssignedExtensionFieldValue<F, 2>],
         next_zs: &[AssignedExtensionFieldValue<F, 2>],
+        local_lookup_zs: &[AssignedExtensionFieldValue<F, 2>],
+        next_lookup_zs: &[AssignedExtensionFieldValue<F, 2>],
         partial_products: &[AssignedExtensionFieldValue<F, 2>],
         s_sigmas: &[AssignedExtensionFieldValue<F, 2>],
         betas: &[AssignedValue<F>],
         gammas: &[AssignedValue<F>],
         alphas: &[AssignedValue<F>],
+        deltas: &[AssignedValue<F>],
     ) -> Result<Vec<AssignedExtensionFieldValue<F, 2>>, Error> {
         let goldilocks_extension_chip = GoldilocksExtensionChip::new(&self.goldilocks_chip_config);
         let max_degree = common_data.quotient_degree_factor;
         let num_prods = common_data.num_partial_products;
 
-        let constraint_terms = self.eval_gate_constraints(
-            ctx,
-            common_data,
-            local_constants,
-            local_wires,
-            public_inputs_hash,
-        )?;
+        let constraint_terms = ctx.stage("gate_constraints", |ctx| {
+            self.eval_gate_constraints(
+                ctx,
+                common_data,
+                local_constants,
+                local_wires,
+                public_inputs_hash,
+            )
+        })?;
+
+        let num_selectors = common_data.selectors_info.num_selectors();
+        let lookup_selectors =
+            &local_constants[num_selectors..num_selectors + common_data.num_lookup_selectors];
 
         // The L_0(x) (Z(x) - 1) vanishing terms.
         let mut vanishing_z_1_terms = Vec::new();
+        // The terms checking the lookup constraints, if any.
+        let mut vanishing_all_lookup_terms = Vec::new();
         // The terms checking the partial products.
         let mut vanishing_partial_products_terms = Vec::new();
 
@@ -64,6 +76,23 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
             vanishing_z_1_terms
                 .push(goldilocks_extension_chip.mul_sub_extension(ctx, &l_0_x, z_x, &l_0_x)?);
 
+            if common_data.has_lookup() {
+                let lookup_polys =
+                    common_data.num_lookup_polys * i..common_data.num_lookup_polys * (i + 1);
+                let lookup_constraints = ctx.stage("lookups", |ctx| {
+                    self.check_lookup_constraints(
+                        ctx,
+                        common_data,
+                        local_wires,
+                        &local_lookup_zs[lookup_polys.clone()],
+                        &next_lookup_zs[lookup_polys],
+                        lookup_selectors,
+                        &deltas[NUM_COINS_LOOKUP * i..NUM_COINS_LOOKUP * (i + 1)],
+                    )
+                })?;
+                vanishing_all_lookup_terms.extend(lookup_constraints);
+            }
+
             let mut numerator_values = vec![];
             let mut denominator_values = vec![];
 
@@ -95,21 +124,24 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
             // The partial products considered for this iteration of `i`.
             let current_partial_products = &partial_products[i * num_prods..(i + 1) * num_prods];
             // Check the quotient partial products.
-            let partial_product_checks = self.check_partial_products(
-                ctx,
-                &numerator_values,
-                &denominator_values,
-                current_partial_products,
-                z_x,
-                z_gx,
-                max_degree,
-            )?;
+            let partial_product_checks = ctx.stage("partial_products", |ctx| {
+                self.check_partial_products(
+                    ctx,
+                    &numerator_values,
+                    &denominator_values,
+                    current_partial_products,
+                    z_x,
+                    z_gx,
+                    max_degree,
+                )
+            })?;
             vanishing_partial_products_terms.extend(partial_product_checks);
         }
 
         let vanishing_terms = [
             vanishing_z_1_terms,
             vanishing_partial_products_terms,
+            vanishing_all_lookup_terms,
             constraint_terms,
         ]
         .concat();
@@ -136,18 +168,21 @@ impl<F: PrimeField> PlonkVerifierChip<F> {
         let mut all_gate_constraints = vec![zero_extension; common_data.num_gate_constraints];
         for (i, gate) in common_data.gates.iter().enumerate() {
             let selector_index = common_data.selectors_info.selector_indices[i];
-            gate.0.eval_filtered_constraint(
-                ctx,
-                &self.goldilocks_chip_config,
-                local_constants,
-                local_wires,
-                public_inputs_hash,
-                i,
-                selector_index,
-                common_data.selectors_info.groups[selector_index].clone(),
-                common_data.selectors_info.num_selectors(),
-                &mut all_gate_constraints,
-            )?;
+            ctx.stage(&format!("gate {i} {}", gate.0.name()), |ctx| {
+                gate.0.eval_filtered_constraint(
+                    ctx,
+                    &self.goldilocks_chip_config,
+                    local_constants,
+                    local_wires,
+                    public_inputs_hash,
+                    i,
+                    selector_index,
+                    common_data.selectors_info.groups[selector_index].clone(),
+                    common_data.selectors_info.num_selectors(),
+                    common_data.num_lookup_selectors,
+                    &mut all_gate_constraints,
+                )
+            })?;
         }
         Ok(all_gate_constraints)
     }
@@ -0,0 +1,151 @@
+use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
+use halo2wrong_maingate::AssignedValue;
+
+use crate::plonky2_verifier::{
+    chip::{
+        goldilocks_chip::GoldilocksChipConfig, goldilocks_extension_chip::GoldilocksExtensionChip,
+    },
+    context::RegionCtx,
+    types::assigned::AssignedExtensionFieldValue,
+};
+
+use super::lookup::AirLookup;
+
+/// In-circuit counterpart of a starky `Stark`, evaluating its AIR constraints on the openings of
+/// a proof.
+///
+/// `eval` has to yield the same constraints, in the same order and filtered the same way, as
+/// `Stark::eval_packed_generic`, since every constraint is folded into the vanishing polynomial
+/// with powers of the STARK alphas.
+pub trait AirEvaluator<F: PrimeField> {
+    /// `Stark::COLUMNS`
+    fn num_columns(&self) -> usize;
+
+    /// `Stark::PUBLIC_INPUTS`
+    fn num_public_inputs(&self) -> usize;
+
+    /// `Stark::constraint_degree`
+    fn constraint_degree(&self) -> usize;
+
+    /// `Stark::lookups`, whose logUp constraints are added by the verifier after `eval`.
+    fn lookups(&self) -> Vec<AirLookup> {
+        vec![]
+    }
+
+    fn eval(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        vars: &AssignedStarkFrame<F>,
+        consumer: &mut ConstraintConsumerChip<F>,
+    ) -> Result<(), Error>;
+}
+
+/// The trace openings at `zeta` and `g * zeta`, along with the public inputs lifted to the
+/// extension field, like starky's `StarkFrame`.
+pub struct AssignedStarkFrame<F: PrimeField> {
+    pub local_values: Vec<AssignedExtensionFieldValue<F, 2>>,
+    pub next_values: Vec<AssignedExtensionFieldValue<F, 2>>,
+    pub public_inputs: Vec<AssignedExtensionFieldValue<F, 2>>,
+}
+
+/// Folds the AIR constraints evaluated at `zeta` into one accumulator per STARK alpha, like
+/// starky's `ConstraintConsumer`.
+pub struct ConstraintConsumerChip<F: PrimeField> {
+    goldilocks_chip_config: GoldilocksChipConfig<F>,
+    alphas: Vec<AssignedExtensionFieldValue<F, 2>>,
+    constraint_accs: Vec<AssignedExtensionFieldValue<F, 2>>,
+    /// `zeta - g^{-1}`, vanishing on the last row.
+    z_last: AssignedExtensionFieldValue<F, 2>,
+    /// The Lagrange basis polynomial of the first row at `zeta`.
+    lagrange_basis_first: AssignedExtensionFieldValue<F, 2>,
+    /// The Lagrange basis polynomial of the last row at `zeta`.
+    lagrange_basis_last: AssignedExtensionFieldValue<F, 2>,
+}
+
+impl<F: PrimeField> ConstraintConsumerChip<F> {
+    pub fn new(
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        alphas: &[AssignedValue<F>],
+        z_last: AssignedExtensionFieldValue<F, 2>,
+        lagrange_basis_first: AssignedExtensionFieldValue<F, 2>,
+        lagrange_basis_last: AssignedExtensionFieldValue<F, 2>,
+    ) -> Result<Self, Error> {
+        let goldilocks_extension_chip = GoldilocksExtensionChip::new(goldilocks_chip_config);
+        let alphas = alphas
+            .iter()
+            .map(|alpha| goldilocks_extension_chip.convert_to_extension(ctx, alpha))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let constraint_accs = alphas
+            .iter()
+            .map(|_| goldilocks_extension_chip.zero_extension(ctx))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Self {
+            goldilocks_chip_config: goldilocks_chip_config.clone(),
+            alphas,
+            constraint_accs,
+            z_last,
+            lagrange_basis_first,
+            lagrange_basis_last,
+        })
+    }
+
+    pub fn accumulators(self) -> Vec<AssignedExtensionFieldValue<F, 2>> {
+        self.constraint_accs
+    }
+
+    /// Adds a constraint that has to hold on every row.
+    pub fn constraint(
+        &mut self,
+        ctx: &mut RegionCtx<'_, F>,
+        constraint: &AssignedExtensionFieldValue<F, 2>,
+    ) -> Result<(), Error> {
+        let goldilocks_extension_chip = GoldilocksExtensionChip::new(&self.goldilocks_chip_config);
+        for (acc, alpha) in self.constraint_accs.iter_mut().zip(self.alphas.iter()) {
+            *acc = goldilocks_extension_chip.mul_add_extension(ctx, acc, alpha, constraint)?;
+        }
+        Ok(())
+    }
+
+    /// Adds a constraint that has to hold on every row but the last.
+    pub fn constraint_transition(
+        &mut self,
+        ctx: &mut RegionCtx<'_, F>,
+        constraint: &AssignedExtensionFieldValue<F, 2>,
+    ) -> Result<(), Error> {
+        let filter = self.z_last.clone();
+        self.filtered_constraint(ctx, &filter, constraint)
+    }
+
+    /// Adds a constraint that has to hold on the first row.
+    pub fn constraint_first_row(
+        &mut self,
+        ctx: &mut RegionCtx<'_, F>,
+        constraint: &AssignedExtensionFieldValue<F, 2>,
+    ) -> Result<(), Error> {
+        let filter = self.lagrange_basis_first.clone();
+        self.filtered_constraint(ctx, &filter, constraint)
+    }
+
+    /// Adds a constraint that has to hold on the last row.
+    pub fn constraint_last_row(
+        &mut self,
+        ctx: &mut RegionCtx<'_, F>,
+        constraint: &AssignedExtensionFieldValue<F, 2>,
+    ) -> Result<(), Error> {
+        let filter = self.lagrange_basis_last.clone();
+        self.filtered_constraint(ctx, &filter, constraint)
+    }
+
+    fn filtered_constraint(
+        &mut self,
+        ctx: &mut RegionCtx<'_, F>,
+        filter: &AssignedExtensionFieldValue<F, 2>,
+        constraint: &AssignedExtensionFieldValue<F, 2>,
+    ) -> Result<(), Error> {
+        let goldilocks_extension_chip = GoldilocksExtensionChip::new(&self.goldilocks_chip_config);
+        let filtered = goldilocks_extension_chip.mul_extension(ctx, filter, constraint)?;
+        self.constraint(ctx, &filtered)
+    }
+}
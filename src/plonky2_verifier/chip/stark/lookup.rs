@@ -0,0 +1,196 @@
+use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
+use halo2wrong_maingate::AssignedValue;
+use plonky2::field::{goldilocks_field::GoldilocksField, types::Field};
+
+use crate::plonky2_verifier::{
+    chip::{
+        goldilocks_chip::GoldilocksChipConfig, goldilocks_extension_chip::GoldilocksExtensionChip,
+    },
+    context::RegionCtx,
+    types::assigned::AssignedExtensionFieldValue,
+};
+
+use super::air::{AssignedStarkFrame, ConstraintConsumerChip};
+
+/// A linear combination of the trace columns of the current and the next row plus a constant,
+/// mirroring starky's `Column`.
+#[derive(Clone, Debug, Default)]
+pub struct LookupColumn {
+    pub linear_combination: Vec<(usize, GoldilocksField)>,
+    pub next_row_linear_combination: Vec<(usize, GoldilocksField)>,
+    pub constant: GoldilocksField,
+}
+
+impl LookupColumn {
+    /// The column `c` of the current row.
+    pub fn single(c: usize) -> Self {
+        Self {
+            linear_combination: vec![(c, GoldilocksField::ONE)],
+            ..Default::default()
+        }
+    }
+
+    /// The column `c` of the next row.
+    pub fn single_next_row(c: usize) -> Self {
+        Self {
+            next_row_linear_combination: vec![(c, GoldilocksField::ONE)],
+            ..Default::default()
+        }
+    }
+
+    pub fn eval<F: PrimeField>(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        goldilocks_chip_config: &GoldilocksChipConfig<F>,
+        vars: &AssignedStarkFrame<F>,
+    ) -> Result<AssignedExtensionFieldValue<F, 2>, Error> {
+        let goldilocks_extension_chip = GoldilocksExtensionChip::new(goldilocks_chip_config);
+        let terms = self
+            .linear_combination
+            .iter()
+            .map(|&(c, f)| (&vars.local_values[c], f))
+            .chain(
+                self.next_row_linear_combination
+                    .iter()
+                    .map(|&(c, f)| (&vars.next_values[c], f)),
+            )
+            .collect::<Vec<_>>();
+        let mut acc = goldilocks_extension_chip
+            .constant_extension(ctx, &[self.constant, GoldilocksField::ZERO])?;
+        for (value, coeff) in terms {
+            let term = goldilocks_extension_chip.scalar_mul(ctx, value, coeff)?;
+            acc = goldilocks_extension_chip.add_extension(ctx, &acc, &term)?;
+        }
+        Ok(acc)
+    }
+}
+
+/// A logUp lookup of `columns` into `table_column`, mirroring starky's `Lookup`.
+#[derive(Clone, Debug, Default)]
+pub struct AirLookup {
+    /// Columns whose values are looked up.
+    pub columns: Vec<LookupColumn>,
+    pub table_column: LookupColumn,
+    /// How many times each table row is looked up.
+    pub frequencies_column: LookupColumn,
+    /// Filter of every looking column, `None` if the column is looked up on every row. Only
+    /// starky's simple filters, i.e. a single linear combination, can be expressed.
+    pub filter_columns: Vec<Option<LookupColumn>>,
+}
+
+impl AirLookup {
+    /// Helper columns batching `constraint_degree - 1` looking columns each, followed by the
+    /// running sum `Z`.
+    pub fn num_helper_columns(&self, constraint_degree: usize) -> usize {
+        self.columns
+            .len()
+            .div_ceil(constraint_degree.checked_sub(1).unwrap_or(1))
+            + 1
+    }
+}
+
+/// Adds the logUp constraints of `lookups` for every lookup challenge, where `auxiliary_polys`
+/// and `auxiliary_polys_next` hold the helper columns of each lookup and challenge in turn.
+pub fn eval_lookups<F: PrimeField>(
+    ctx: &mut RegionCtx<'_, F>,
+    goldilocks_chip_config: &GoldilocksChipConfig<F>,
+    lookups: &[AirLookup],
+    constraint_degree: usize,
+    vars: &AssignedStarkFrame<F>,
+    auxiliary_polys: &[AssignedExtensionFieldValue<F, 2>],
+    auxiliary_polys_next: &[AssignedExtensionFieldValue<F, 2>],
+    lookup_challenges: &[AssignedValue<F>],
+    consumer: &mut ConstraintConsumerChip<F>,
+) -> Result<(), Error> {
+    let goldilocks_extension_chip = GoldilocksExtensionChip::new(goldilocks_chip_config);
+    let chunk_size = constraint_degree.checked_sub(1).unwrap_or(1);
+    assert!(
+        chunk_size <= 2,
+        "starky lookups support constraint degrees up to 3"
+    );
+    let mut start = 0;
+    for lookup in lookups {
+        let num_helper_columns = lookup.num_helper_columns(constraint_degree);
+        let columns = lookup
+            .columns
+            .iter()
+            .map(|column| column.eval(ctx, goldilocks_chip_config, vars))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let filters = lookup
+            .filter_columns
+            .iter()
+            .map(|filter| match filter {
+                Some(filter) => filter.eval(ctx, goldilocks_chip_config, vars),
+                None => goldilocks_extension_chip.one_extension(ctx),
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let table = lookup
+            .table_column
+            .eval(ctx, goldilocks_chip_config, vars)?;
+        let frequencies = lookup
+            .frequencies_column
+            .eval(ctx, goldilocks_chip_config, vars)?;
+        for challenge in lookup_challenges {
+            let challenge = goldilocks_extension_chip.convert_to_extension(ctx, challenge)?;
+            let helper_columns = &auxiliary_polys[start..start + num_helper_columns - 1];
+
+            // h_i * (x + f_2i) * (x + f_2i+1) = (x + f_2i) * filter_2i+1 + (x + f_2i+1) * filter_2i
+            // for a chunk of two, h_i * (x + f_2i) = filter_2i for a chunk of one
+            for ((chunk, filters), h) in columns
+                .chunks(chunk_size)
+                .zip(filters.chunks(chunk_size))
+                .zip(helper_columns)
+            {
+                let combined = chunk
+                    .iter()
+                    .map(|f| goldilocks_extension_chip.add_extension(ctx, f, &challenge))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let constraint = if combined.len() == 2 {
+                    let product =
+                        goldilocks_extension_chip.mul_extension(ctx, &combined[0], &combined[1])?;
+                    let lhs = goldilocks_extension_chip.mul_extension(ctx, &product, h)?;
+                    let rhs =
+                        goldilocks_extension_chip.mul_extension(ctx, &filters[0], &combined[1])?;
+                    let rhs = goldilocks_extension_chip.mul_add_extension(
+                        ctx,
+                        &filters[1],
+                        &combined[0],
+                        &rhs,
+                    )?;
+                    goldilocks_extension_chip.sub_extension(ctx, &lhs, &rhs)?
+                } else {
+                    let lhs = goldilocks_extension_chip.mul_extension(ctx, &combined[0], h)?;
+                    goldilocks_extension_chip.sub_extension(ctx, &lhs, &filters[0])?
+                };
+                consumer.constraint(ctx, &constraint)?;
+            }
+
+            // Z starts at zero and accumulates sum(h_i) - frequencies / (x + table) on every row.
+            let z = &auxiliary_polys[start + num_helper_columns - 1];
+            let next_z = &auxiliary_polys_next[start + num_helper_columns - 1];
+            let table_with_challenge =
+                goldilocks_extension_chip.add_extension(ctx, &table, &challenge)?;
+            let mut helpers_sum = goldilocks_extension_chip.zero_extension(ctx)?;
+            for h in helper_columns {
+                helpers_sum = goldilocks_extension_chip.add_extension(ctx, &helpers_sum, h)?;
+            }
+            let y = goldilocks_extension_chip.mul_sub_extension(
+                ctx,
+                &helpers_sum,
+                &table_with_challenge,
+                &frequencies,
+            )?;
+            consumer.constraint_first_row(ctx, z)?;
+            let z_diff = goldilocks_extension_chip.sub_extension(ctx, next_z, z)?;
+            let z_constraint = goldilocks_extension_chip.mul_sub_extension(
+                ctx,
+                &z_diff,
+                &table_with_challenge,
+                &y,
+            )?;
+            consumer.constraint(ctx, &z_constraint)?;
+            start += num_helper_columns;
+        }
+    }
+    Ok(())
+}
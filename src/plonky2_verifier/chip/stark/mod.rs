@@ -0,0 +1,3 @@
+pub mod air;
+pub mod lookup;
+pub mod stark_verifier_chip;
@@ -0,0 +1,610 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
+use halo2wrong_maingate::AssignedValue;
+use plonky2::field::{goldilocks_field::GoldilocksField, types::Field};
+
+use crate::plonky2_verifier::{
+    chip::{
+        fri_chip::FriVerifierChip,
+        goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
+        goldilocks_extension_chip::GoldilocksExtensionChip,
+        hasher_chip::AlgebraicHasherChip,
+        transcript_chip::TranscriptChip,
+    },
+    context::RegionCtx,
+    types::{
+        assigned::{
+            AssignedExtensionFieldValue, AssignedStarkProofChallenges, AssignedStarkProofValues,
+        },
+        stark::StarkCommonData,
+    },
+};
+
+use super::{
+    air::{AirEvaluator, AssignedStarkFrame, ConstraintConsumerChip},
+    lookup::eval_lookups,
+};
+
+/// Verifies starky proofs whose Merkle trees and transcript use the hasher `H`, checking the
+/// AIR constraints with a user-supplied `AirEvaluator`.
+pub struct StarkVerifierChip<F: PrimeField, H: AlgebraicHasherChip<F>> {
+    pub goldilocks_chip_config: GoldilocksChipConfig<F>,
+    _marker: PhantomData<H>,
+}
+
+impl<F: PrimeField, H: AlgebraicHasherChip<F>> StarkVerifierChip<F, H> {
+    pub fn construct(goldilocks_chip_config: &GoldilocksChipConfig<F>) -> Self {
+        Self {
+            goldilocks_chip_config: goldilocks_chip_config.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn goldilocks_chip(&self) -> GoldilocksChip<F> {
+        GoldilocksChip::<F>::new(&self.goldilocks_chip_config)
+    }
+
+    pub fn verify_proof(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        air: &impl AirEvaluator<F>,
+        proof: &AssignedStarkProofValues<F, 2>,
+        public_inputs: &[AssignedValue<F>],
+        common_data: &StarkCommonData,
+    ) -> Result<(), Error> {
+        if public_inputs.len() != common_data.num_public_inputs {
+            log::error!(
+                "expected {} public inputs, got {}",
+                common_data.num_public_inputs,
+                public_inputs.len()
+            );
+            return Err(Error::Synthesis);
+        }
+        let challenges = ctx.stage("get_challenges", |ctx| {
+            self.get_challenges(ctx, public_inputs, common_data, proof)
+        })?;
+        ctx.stage("verify_proof_with_challenges", |ctx| {
+            self.verify_proof_with_challenges(
+                ctx,
+                air,
+                proof,
+                public_inputs,
+                &challenges,
+                common_data,
+            )
+        })
+    }
+
+    /// Replays starky's challenger: the public inputs and the trace cap, then the lookup
+    /// challenges and the auxiliary cap if any, the alphas, the quotient cap, zeta, the openings
+    /// and the FRI challenges.
+    pub fn get_challenges(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        public_inputs: &[AssignedValue<F>],
+        common_data: &StarkCommonData,
+        proof: &AssignedStarkProofValues<F, 2>,
+    ) -> Result<AssignedStarkProofChallenges<F, 2>, Error> {
+        let mut transcript_chip = TranscriptChip::<F, H>::new(ctx, &self.goldilocks_chip_config)?;
+        for e in public_inputs.iter() {
+            transcript_chip.write_scalar(ctx, e)?;
+        }
+        transcript_chip.write_cap(ctx, &proof.trace_cap)?;
+
+        // starky squeezes a `(beta, gamma)` pair per challenge round and uses the betas as the
+        // logUp challenges.
+        let lookup_challenges = match &proof.auxiliary_polys_cap {
+            Some(auxiliary_polys_cap) => {
+                let pairs = transcript_chip.squeeze(ctx, 2 * common_data.num_challenges)?;
+                transcript_chip.write_cap(ctx, auxiliary_polys_cap)?;
+                pairs.into_iter().step_by(2).collect()
+            }
+            None => vec![],
+        };
+        let stark_alphas = transcript_chip.squeeze(ctx, common_data.num_challenges)?;
+
+        transcript_chip.write_cap(ctx, &proof.quotient_polys_cap)?;
+        let stark_zeta = transcript_chip.squeeze(ctx, 2)?;
+
+        for batch in proof.openings.to_fri_openings().batches {
+            for ext in batch.values {
+                transcript_chip.write_extension(ctx, &ext)?;
+            }
+        }
+        let fri_challenges = transcript_chip.fri_challenges(
+            ctx,
+            &proof.opening_proof,
+            common_data.fri_params.config.num_query_rounds,
+        )?;
+
+        Ok(AssignedStarkProofChallenges {
+            lookup_challenges,
+            stark_alphas,
+            stark_zeta: AssignedExtensionFieldValue(stark_zeta.try_into().unwrap()),
+            fri_challenges,
+        })
+    }
+
+    pub fn verify_proof_with_challenges(
+        &self,
+        ctx: &mut RegionCtx<'_, F>,
+        air: &impl AirEvaluator<F>,
+        proof: &AssignedStarkProofValues<F, 2>,
+        public_inputs: &[AssignedValue<F>],
+        challenges: &AssignedStarkProofChallenges<F, 2>,
+        common_data: &StarkCommonData,
+    ) -> Result<(), Error> {
+        let goldilocks_extension_chip = GoldilocksExtensionChip::new(&self.goldilocks_chip_config);
+        let degree_bits = common_data.degree_bits();
+        let zeta = &challenges.stark_zeta;
+        let one = goldilocks_extension_chip.one_extension(ctx)?;
+
+        let zeta_pow_deg =
+            goldilocks_extension_chip.exp_power_of_2_extension(ctx, zeta.clone(), degree_bits)?;
+        let z_h_zeta = goldilocks_extension_chip.sub_extension(ctx, &zeta_pow_deg, &one)?;
+
+        let g = GoldilocksField::primitive_root_of_unity(degree_bits);
+        let zeta_next = goldilocks_extension_chip.scalar_mul(ctx, zeta, g)?;
+
+        // L_first(x) = Z_H(x) / (n * (x - 1)) and L_last(x) = Z_H(x) / (n * (g * x - 1))
+        let n = GoldilocksField::from_canonical_usize(1 << degree_bits);
+        let zeta_minus_one = goldilocks_extension_chip.sub_extension(ctx, zeta, &one)?;
+        let denominator = goldilocks_extension_chip.scalar_mul(ctx, &zeta_minus_one, n)?;
+        let lagrange_basis_first =
+            goldilocks_extension_chip.div_extension(ctx, &z_h_zeta, &denominator)?;
+        let zeta_next_minus_one = goldilocks_extension_chip.sub_extension(ctx, &zeta_next, &one)?;
+        let denominator = goldilocks_extension_chip.scalar_mul(ctx, &zeta_next_minus_one, n)?;
+        let lagrange_basis_last =
+            goldilocks_extension_chip.div_extension(ctx, &z_h_zeta, &denominator)?;
+        let g_inv = goldilocks_extension_chip
+            .constant_extension(ctx, &[g.inverse(), GoldilocksField::ZERO])?;
+        let z_last = goldilocks_extension_chip.sub_extension(ctx, zeta, &g_inv)?;
+
+        let vars = AssignedStarkFrame {
+            local_values: proof.openings.local_values.clone(),
+            next_values: proof.openings.next_values.clone(),
+            public_inputs: public_inputs
+                .iter()
+                .map(|pi| goldilocks_extension_chip.convert_to_extension(ctx, pi))
+                .collect::<Result<Vec<_>, Error>>()?,
+        };
+        let mut consumer = ConstraintConsumerChip::new(
+            ctx,
+            &self.goldilocks_chip_config,
+            &challenges.stark_alphas,
+            z_last,
+            lagrange_basis_first,
+            lagrange_basis_last,
+        )?;
+        ctx.stage("eval_air", |ctx| {
+            air.eval(ctx, &self.goldilocks_chip_config, &vars, &mut consumer)
+        })?;
+        if common_data.uses_lookups() {
+            ctx.stage("eval_lookups", |ctx| {
+                eval_lookups(
+                    ctx,
+                    &self.goldilocks_chip_config,
+                    &air.lookups(),
+                    common_data.constraint_degree,
+                    &vars,
+                    &proof.openings.auxiliary_polys,
+                    &proof.openings.auxiliary_polys_next,
+                    &challenges.lookup_challenges,
+                    &mut consumer,
+                )
+            })?;
+        }
+        let vanishing_polys_zeta = consumer.accumulators();
+
+        ctx.stage("check_quotient", |ctx| {
+            for (i, chunk) in proof
+                .openings
+                .quotient_polys
+                .chunks(common_data.quotient_degree_factor())
+                .enumerate()
+            {
+                let recombined_quotient = goldilocks_extension_chip.reduce_extension(
+                    ctx,
+                    &zeta_pow_deg,
+                    &chunk.to_vec(),
+                )?;
+                let computed_vanishing_poly = goldilocks_extension_chip.mul_extension(
+                    ctx,
+                    &z_h_zeta,
+                    &recombined_quotient,
+                )?;
+                goldilocks_extension_chip.assert_equal_extension(
+                    ctx,
+                    &vanishing_polys_zeta[i],
+                    &computed_vanishing_poly,
+                )?;
+            }
+            Ok::<_, Error>(())
+        })?;
+
+        let merkle_caps = [
+            Some(proof.trace_cap.clone()),
+            proof.auxiliary_polys_cap.clone(),
+            Some(proof.quotient_polys_cap.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        let fri_instance_info = common_data.fri_instance(zeta, &zeta_next);
+        let offset = self
+            .goldilocks_chip()
+            .assign_constant(ctx, GoldilocksField::MULTIPLICATIVE_GROUP_GENERATOR)?;
+        let fri_chip = FriVerifierChip::<F, H>::construct(
+            &self.goldilocks_chip_config,
+            &offset,
+            common_data.fri_params.clone(),
+        );
+        ctx.stage("verify_fri_proof", |ctx| {
+            fri_chip.verify_fri_proof(
+                ctx,
+                &merkle_caps,
+                &challenges.fri_challenges,
+                &proof.openings.to_fri_openings(),
+                &proof.opening_proof,
+                &fri_instance_info,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Layouter, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use plonky2::{
+        field::{
+            extension::{Extendable, FieldExtension},
+            goldilocks_field::GoldilocksField,
+            packed::PackedField,
+            polynomial::PolynomialValues,
+            types::Field,
+        },
+        hash::hash_types::RichField,
+        iop::ext_target::ExtensionTarget,
+        plonk::circuit_builder::CircuitBuilder,
+        util::timing::TimingTree,
+    };
+    use starky::{
+        config::StarkConfig,
+        constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer},
+        evaluation_frame::{StarkEvaluationFrame, StarkFrame},
+        lookup::{Column, Lookup},
+        prover::prove,
+        stark::Stark,
+        util::trace_rows_to_poly_values,
+        verifier::verify_stark_proof,
+    };
+
+    use crate::plonky2_verifier::{
+        bn245_poseidon::plonky2_config::{Bn254PoseidonGoldilocksConfig, Bn254PoseidonHash},
+        chip::{
+            goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
+            goldilocks_extension_chip::GoldilocksExtensionChip,
+            native_chip::{all_chip::AllChipConfig, utils::goldilocks_to_fe},
+            stark::{
+                air::{AirEvaluator, AssignedStarkFrame, ConstraintConsumerChip},
+                lookup::{AirLookup, LookupColumn},
+            },
+        },
+        context::RegionCtx,
+        types::stark::{StarkCommonData, StarkProofValues},
+    };
+
+    use super::StarkVerifierChip;
+
+    const COLUMNS: usize = 5;
+    const PUBLIC_INPUTS: usize = 3;
+
+    /// Computes a Fibonacci sequence with the state `[x0, x1, i, j, frequencies]`, where the
+    /// counter `i` is the lookup table and its reverse `j` is looked up in it.
+    #[derive(Copy, Clone)]
+    struct FibonacciStark<F: RichField + Extendable<D>, const D: usize> {
+        num_rows: usize,
+        _phantom: PhantomData<F>,
+    }
+
+    impl<F: RichField + Extendable<D>, const D: usize> FibonacciStark<F, D> {
+        const PI_INDEX_X0: usize = 0;
+        const PI_INDEX_X1: usize = 1;
+        const PI_INDEX_RES: usize = 2;
+
+        fn new(num_rows: usize) -> Self {
+            Self {
+                num_rows,
+                _phantom: PhantomData,
+            }
+        }
+
+        fn generate_trace(&self, x0: F, x1: F) -> Vec<PolynomialValues<F>> {
+            let n = self.num_rows;
+            let trace_rows = (0..n)
+                .scan([x0, x1], |state, i| {
+                    let row = [
+                        state[0],
+                        state[1],
+                        F::from_canonical_usize(i),
+                        F::from_canonical_usize(n - 1 - i),
+                        F::ONE,
+                    ];
+                    *state = [state[1], state[0] + state[1]];
+                    Some(row)
+                })
+                .collect::<Vec<_>>();
+            trace_rows_to_poly_values(trace_rows)
+        }
+
+        /// `x1` of the last row.
+        fn result(&self, x0: F, x1: F) -> F {
+            (1..self.num_rows).fold([x0, x1], |[a, b], _| [b, a + b])[1]
+        }
+    }
+
+    impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FibonacciStark<F, D> {
+        const COLUMNS: usize = COLUMNS;
+        const PUBLIC_INPUTS: usize = PUBLIC_INPUTS;
+
+        type EvaluationFrame<FE, P, const D2: usize> =
+            StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+
+        type EvaluationFrameTarget =
+            StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+        fn eval_packed_generic<FE, P, const D2: usize>(
+            &self,
+            vars: &Self::EvaluationFrame<FE, P, D2>,
+            yield_constr: &mut ConstraintConsumer<P>,
+        ) where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>,
+        {
+            let local_values = vars.get_local_values();
+            let next_values = vars.get_next_values();
+            let public_inputs = vars.get_public_inputs();
+
+            yield_constr.constraint_first_row(local_values[0] - public_inputs[Self::PI_INDEX_X0]);
+            yield_constr.constraint_first_row(local_values[1] - public_inputs[Self::PI_INDEX_X1]);
+            yield_constr.constraint_last_row(local_values[1] - public_inputs[Self::PI_INDEX_RES]);
+            yield_constr.constraint_first_row(local_values[2]);
+
+            // x0' <- x1, x1' <- x0 + x1, i' <- i + 1
+            yield_constr.constraint_transition(next_values[0] - local_values[1]);
+            yield_constr.constraint_transition(next_values[1] - local_values[0] - local_values[1]);
+            yield_constr.constraint_transition(next_values[2] - local_values[2] - P::ONES);
+        }
+
+        fn eval_ext_circuit(
+            &self,
+            builder: &mut CircuitBuilder<F, D>,
+            vars: &Self::EvaluationFrameTarget,
+            yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        ) {
+            let local_values = vars.get_local_values();
+            let next_values = vars.get_next_values();
+            let public_inputs = vars.get_public_inputs();
+            let one = builder.one_extension();
+
+            let x0 = builder.sub_extension(local_values[0], public_inputs[Self::PI_INDEX_X0]);
+            yield_constr.constraint_first_row(builder, x0);
+            let x1 = builder.sub_extension(local_values[1], public_inputs[Self::PI_INDEX_X1]);
+            yield_constr.constraint_first_row(builder, x1);
+            let res = builder.sub_extension(local_values[1], public_inputs[Self::PI_INDEX_RES]);
+            yield_constr.constraint_last_row(builder, res);
+            yield_constr.constraint_first_row(builder, local_values[2]);
+
+            let x0_next = builder.sub_extension(next_values[0], local_values[1]);
+            yield_constr.constraint_transition(builder, x0_next);
+            let sum = builder.add_extension(local_values[0], local_values[1]);
+            let x1_next = builder.sub_extension(next_values[1], sum);
+            yield_constr.constraint_transition(builder, x1_next);
+            let i_plus_one = builder.add_extension(local_values[2], one);
+            let i_next = builder.sub_extension(next_values[2], i_plus_one);
+            yield_constr.constraint_transition(builder, i_next);
+        }
+
+        fn constraint_degree(&self) -> usize {
+            2
+        }
+
+        fn lookups(&self) -> Vec<Lookup<F>> {
+            vec![Lookup {
+                columns: vec![Column::single(3)],
+                table_column: Column::single(2),
+                frequencies_column: Column::single(4),
+                filter_columns: vec![None],
+            }]
+        }
+    }
+
+    impl AirEvaluator<Fr> for FibonacciStark<GoldilocksField, 2> {
+        fn num_columns(&self) -> usize {
+            COLUMNS
+        }
+
+        fn num_public_inputs(&self) -> usize {
+            PUBLIC_INPUTS
+        }
+
+        fn constraint_degree(&self) -> usize {
+            2
+        }
+
+        fn lookups(&self) -> Vec<AirLookup> {
+            vec![AirLookup {
+                columns: vec![LookupColumn::single(3)],
+                table_column: LookupColumn::single(2),
+                frequencies_column: LookupColumn::single(4),
+                filter_columns: vec![None],
+            }]
+        }
+
+        fn eval(
+            &self,
+            ctx: &mut RegionCtx<'_, Fr>,
+            goldilocks_chip_config: &GoldilocksChipConfig<Fr>,
+            vars: &AssignedStarkFrame<Fr>,
+            consumer: &mut ConstraintConsumerChip<Fr>,
+        ) -> Result<(), Error> {
+            let goldilocks_extension_chip = GoldilocksExtensionChip::new(goldilocks_chip_config);
+            let local_values = &vars.local_values;
+            let next_values = &vars.next_values;
+            let public_inputs = &vars.public_inputs;
+            let one = goldilocks_extension_chip.one_extension(ctx)?;
+
+            let x0 = goldilocks_extension_chip.sub_extension(
+                ctx,
+                &local_values[0],
+                &public_inputs[Self::PI_INDEX_X0],
+            )?;
+            consumer.constraint_first_row(ctx, &x0)?;
+            let x1 = goldilocks_extension_chip.sub_extension(
+                ctx,
+                &local_values[1],
+                &public_inputs[Self::PI_INDEX_X1],
+            )?;
+            consumer.constraint_first_row(ctx, &x1)?;
+            let res = goldilocks_extension_chip.sub_extension(
+                ctx,
+                &local_values[1],
+                &public_inputs[Self::PI_INDEX_RES],
+            )?;
+            consumer.constraint_last_row(ctx, &res)?;
+            consumer.constraint_first_row(ctx, &local_values[2])?;
+
+            let x0_next =
+                goldilocks_extension_chip.sub_extension(ctx, &next_values[0], &local_values[1])?;
+            consumer.constraint_transition(ctx, &x0_next)?;
+            let sum =
+                goldilocks_extension_chip.add_extension(ctx, &local_values[0], &local_values[1])?;
+            let x1_next = goldilocks_extension_chip.sub_extension(ctx, &next_values[1], &sum)?;
+            consumer.constraint_transition(ctx, &x1_next)?;
+            let i_plus_one =
+                goldilocks_extension_chip.add_extension(ctx, &local_values[2], &one)?;
+            let i_next =
+                goldilocks_extension_chip.sub_extension(ctx, &next_values[2], &i_plus_one)?;
+            consumer.constraint_transition(ctx, &i_next)
+        }
+    }
+
+    #[derive(Clone)]
+    struct StarkVerifierCircuit {
+        stark: FibonacciStark<GoldilocksField, 2>,
+        proof: StarkProofValues<Fr, 2>,
+        public_inputs: Vec<GoldilocksField>,
+        common_data: StarkCommonData,
+    }
+
+    impl StarkVerifierCircuit {
+        fn new(num_rows: usize) -> Self {
+            type F = GoldilocksField;
+            let config = StarkConfig::standard_fast_config();
+            let stark = FibonacciStark::<F, 2>::new(num_rows);
+            let public_inputs = [F::ZERO, F::ONE, stark.result(F::ZERO, F::ONE)];
+            let trace = stark.generate_trace(public_inputs[0], public_inputs[1]);
+            let proof = prove::<F, Bn254PoseidonGoldilocksConfig, _, 2>(
+                stark,
+                &config,
+                trace,
+                &public_inputs,
+                &mut TimingTree::default(),
+            )
+            .unwrap();
+            verify_stark_proof(stark, proof.clone(), &config).unwrap();
+
+            let degree_bits = proof.proof.recover_degree_bits(&config);
+            Self {
+                stark,
+                proof: StarkProofValues::from(proof.proof),
+                public_inputs: proof.public_inputs,
+                common_data: StarkCommonData::new::<Fr>(&stark, &config, degree_bits).unwrap(),
+            }
+        }
+    }
+
+    impl Circuit<Fr> for StarkVerifierCircuit {
+        type Config = GoldilocksChipConfig<Fr>;
+        type FloorPlanner = V1;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let all_chip = AllChipConfig::<Fr>::configure(meta);
+            GoldilocksChip::configure(&all_chip)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let goldilocks_chip = GoldilocksChip::new(&config);
+            goldilocks_chip.load_table(&mut layouter)?;
+            layouter.assign_region(
+                || "stark verifier",
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let public_inputs = self
+                        .public_inputs
+                        .iter()
+                        .map(|x| {
+                            goldilocks_chip.assign_value(ctx, Value::known(goldilocks_to_fe(*x)))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+                    let proof = StarkProofValues::assign(&config, ctx, &self.proof)?;
+                    StarkVerifierChip::<Fr, Bn254PoseidonHash>::construct(&config).verify_proof(
+                        ctx,
+                        &self.stark,
+                        &proof,
+                        &public_inputs,
+                        &self.common_data,
+                    )
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_stark_verifier_mock() {
+        const DEGREE: u32 = 18;
+        let circuit = StarkVerifierCircuit::new(1 << 5);
+        assert!(circuit.common_data.uses_lookups());
+        MockProver::run(DEGREE, &circuit, vec![vec![]])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn test_stark_verifier_wrong_public_input() {
+        const DEGREE: u32 = 18;
+        let mut circuit = StarkVerifierCircuit::new(1 << 5);
+        circuit.public_inputs[FibonacciStark::<GoldilocksField, 2>::PI_INDEX_RES] +=
+            GoldilocksField::ONE;
+        let mock_prover = MockProver::run(DEGREE, &circuit, vec![vec![]]).unwrap();
+        assert!(mock_prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_stark_verifier_wrong_number_of_public_inputs() {
+        const DEGREE: u32 = 18;
+        let mut circuit = StarkVerifierCircuit::new(1 << 5);
+        circuit.public_inputs.pop();
+        assert!(MockProver::run(DEGREE, &circuit, vec![vec![]]).is_err());
+    }
+}
@@ -1,25 +1,28 @@
 use crate::plonky2_verifier::{
-    chip::hasher_chip::HasherChip,
+    chip::hasher_chip::{AlgebraicHasherChip, SpongeChip},
     context::RegionCtx,
-    types::assigned::{AssignedExtensionFieldValue, AssignedHashValues, AssignedMerkleCapValues},
+    types::assigned::{
+        AssignedExtensionFieldValue, AssignedFriChallenges, AssignedFriProofValues,
+        AssignedHashValues, AssignedMerkleCapValues,
+    },
 };
 use halo2_proofs::{halo2curves::ff::PrimeField, plonk::Error};
 use halo2wrong_maingate::AssignedValue;
 
 use super::goldilocks_chip::GoldilocksChipConfig;
 
-pub struct TranscriptChip<N: PrimeField> {
-    hasher_chip: HasherChip<N>,
+pub struct TranscriptChip<N: PrimeField, H: AlgebraicHasherChip<N>> {
+    sponge_chip: SpongeChip<N, H>,
 }
 
-impl<N: PrimeField> TranscriptChip<N> {
+impl<N: PrimeField, H: AlgebraicHasherChip<N>> TranscriptChip<N, H> {
     /// Constructs the transcript chip
     pub fn new(
         ctx: &mut RegionCtx<'_, N>,
         goldilocks_chip_config: &GoldilocksChipConfig<N>,
     ) -> Result<Self, Error> {
-        let hasher_chip = HasherChip::new(ctx, goldilocks_chip_config)?;
-        Ok(Self { hasher_chip })
+        let sponge_chip = SpongeChip::new(ctx, goldilocks_chip_config)?;
+        Ok(Self { sponge_chip })
     }
 
     /// Write scalar to the transcript
@@ -28,7 +31,7 @@ impl<N: PrimeField> TranscriptChip<N> {
         ctx: &mut RegionCtx<'_, N>,
         scalar: &AssignedValue<N>,
     ) -> Result<(), Error> {
-        self.hasher_chip.update(ctx, scalar)
+        self.sponge_chip.absorb(ctx, scalar)
     }
 
     pub fn write_extension<const D: usize>(
@@ -70,6 +73,45 @@ impl<N: PrimeField> TranscriptChip<N> {
         ctx: &mut RegionCtx<'_, N>,
         num_outputs: usize,
     ) -> Result<Vec<AssignedValue<N>>, Error> {
-        self.hasher_chip.squeeze(ctx, num_outputs)
+        self.sponge_chip.squeeze(ctx, num_outputs)
+    }
+
+    /// Squeezes the FRI challenges after the openings have been written, like plonky2's
+    /// `Challenger::fri_challenges`.
+    pub fn fri_challenges(
+        &mut self,
+        ctx: &mut RegionCtx<'_, N>,
+        fri_proof: &AssignedFriProofValues<N, 2>,
+        num_query_rounds: usize,
+    ) -> Result<AssignedFriChallenges<N, 2>, Error> {
+        // Scaling factor to combine polynomials.
+        let fri_alpha = AssignedExtensionFieldValue(self.squeeze(ctx, 2)?.try_into().unwrap());
+
+        // Recover the random betas used in the FRI reductions.
+        let fri_betas = fri_proof
+            .commit_phase_merkle_cap_values
+            .iter()
+            .map(|cap| {
+                self.write_cap(ctx, cap)?;
+                let fri_beta = self.squeeze(ctx, 2)?;
+                Ok(AssignedExtensionFieldValue(fri_beta.try_into().unwrap()))
+            })
+            .collect::<Result<Vec<AssignedExtensionFieldValue<N, 2>>, Error>>()?;
+
+        for ext in fri_proof.final_poly.0.iter() {
+            self.write_extension(ctx, ext)?;
+        }
+
+        self.write_scalar(ctx, &fri_proof.pow_witness)?;
+        let fri_pow_response = self.squeeze(ctx, 1)?[0].clone();
+
+        let fri_query_indices = self.squeeze(ctx, num_query_rounds)?;
+
+        Ok(AssignedFriChallenges {
+            fri_alpha,
+            fri_betas,
+            fri_pow_response,
+            fri_query_indices,
+        })
     }
 }
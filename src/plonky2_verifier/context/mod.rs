@@ -8,11 +8,15 @@ use halo2_proofs::{
 use halo2wrong_maingate::fe_to_big;
 use num_bigint::BigUint;
 
+use super::profile::{CostProfile, Profiler};
+
 #[derive(Debug)]
 pub struct RegionCtx<'a, F: PrimeField> {
     region: Region<'a, F>,
     offset: usize,
     contants: HashMap<BigUint, AssignedCell<F, F>>,
+    witnessed: bool,
+    profiler: Profiler,
 }
 
 impl<'a, F: PrimeField> RegionCtx<'a, F> {
@@ -21,9 +25,48 @@ impl<'a, F: PrimeField> RegionCtx<'a, F> {
             region,
             offset,
             contants: HashMap::new(),
+            witnessed: true,
+            profiler: Profiler::default(),
         }
     }
 
+    /// Makes `witness` return `Value::unknown()`, e.g. when synthesizing for keygen.
+    pub fn set_witnessed(&mut self, witnessed: bool) {
+        self.witnessed = witnessed
+    }
+
+    /// Wraps a witness value, `Value::unknown()` if the circuit has no witnesses.
+    pub fn witness(&self, value: F) -> Value<F> {
+        if self.witnessed {
+            Value::known(value)
+        } else {
+            Value::unknown()
+        }
+    }
+
+    /// Charges the rows and lookups used by `f` to the stage `name`, nested in the current stage.
+    pub fn stage<T, E>(
+        &mut self,
+        name: &str,
+        f: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        self.profiler.enter(name, self.offset);
+        let result = f(self);
+        self.profiler.exit(self.offset);
+        result
+    }
+
+    /// Records `lookups` lookups into a lookup table at the current row. Every assignment of
+    /// cells constrained by a lookup records them, see `assign_q_and_r` and the Keccak chip.
+    pub fn record_lookups(&mut self, lookups: usize) {
+        self.profiler.record_lookups(lookups)
+    }
+
+    /// Returns the cost of the stages entered so far and resets the profiler.
+    pub fn take_profile(&mut self) -> CostProfile {
+        self.profiler.finish(self.offset)
+    }
+
     pub fn offset(&self) -> usize {
         self.offset
     }
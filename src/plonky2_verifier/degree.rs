@@ -0,0 +1,206 @@
+//! Selection of the halo2 degree `k` for the `Verifier` circuit.
+//!
+//! The circuit is synthesized once without witnesses against `RowCounter`, an `Assignment`
+//! backend that only records which rows every region touches. The smallest `k` is the one
+//! whose usable rows (`2^k` minus the blinding rows) fit all of them.
+
+use std::collections::BTreeMap;
+
+use halo2_proofs::circuit::{SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::plonk::{
+    Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error, Fixed,
+    FloorPlanner, Instance, Selector,
+};
+use serde::Serialize;
+
+use super::error::VerifierError;
+
+/// Rows used by every region of the circuit, in order of first assignment.
+#[derive(Clone, Debug, Serialize)]
+pub struct RowBudget {
+    /// `(region name, rows spanned)`, regions with the same name are merged.
+    pub regions: Vec<(String, usize)>,
+    /// Rows used by all regions, including constants and lookup tables.
+    pub used_rows: usize,
+    /// Rows reserved by halo2 for blinding factors and `l_last`.
+    pub blinding_rows: usize,
+    /// Smallest `k` such that `used_rows + blinding_rows <= 2^k`.
+    pub minimum_k: u32,
+}
+
+impl RowBudget {
+    pub fn usable_rows(&self, k: u32) -> usize {
+        (1usize << k).saturating_sub(self.blinding_rows)
+    }
+}
+
+#[derive(Default)]
+pub(super) struct RowCounter {
+    /// `(name, first row, last row)` of every region entered so far.
+    regions: Vec<(String, usize, usize)>,
+    current: Option<usize>,
+    used_rows: usize,
+}
+
+impl RowCounter {
+    fn use_row(&mut self, row: usize) {
+        self.used_rows = self.used_rows.max(row + 1);
+        if let Some(index) = self.current {
+            let (_, first, last) = &mut self.regions[index];
+            *first = (*first).min(row);
+            *last = (*last).max(row);
+        }
+    }
+}
+
+impl<F: Field> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.regions.push((name_fn().into(), usize::MAX, 0));
+        self.current = Some(self.regions.len() - 1);
+    }
+
+    fn annotate_column<A, AR>(&mut self, _: A, _: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+    }
+
+    fn exit_region(&mut self) {
+        self.current = None;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// Synthesizes `circuit`, e.g. a `Verifier`, without witnesses and reports the rows it needs.
+pub fn row_budget<C: Circuit<Fr>>(circuit: &C) -> Result<RowBudget, VerifierError> {
+    let mut cs = ConstraintSystem::<Fr>::default();
+    let config = C::configure_with_params(&mut cs, circuit.params());
+    let mut counter = RowCounter::default();
+    SimpleFloorPlanner::synthesize(
+        &mut counter,
+        &circuit.without_witnesses(),
+        config,
+        cs.constants().clone(),
+    )
+    .map_err(VerifierError::Synthesis)?;
+
+    let mut regions: Vec<(String, usize)> = vec![];
+    let mut index = BTreeMap::new();
+    for (name, first, last) in counter.regions {
+        if first > last {
+            continue;
+        }
+        let rows = last - first + 1;
+        match index.get(&name) {
+            Some(&i) => regions[i].1 += rows,
+            None => {
+                index.insert(name.clone(), regions.len());
+                regions.push((name, rows));
+            }
+        }
+    }
+    let blinding_rows = cs.blinding_factors() + 1;
+    let total_rows = (counter.used_rows + blinding_rows).max(cs.minimum_rows());
+    let minimum_k = total_rows.next_power_of_two().trailing_zeros();
+    Ok(RowBudget {
+        regions,
+        used_rows: counter.used_rows,
+        blinding_rows,
+        minimum_k,
+    })
+}
+
+/// Returns `degree` if given, after checking that the circuit fits, or the smallest `k` otherwise.
+pub fn select_degree<C: Circuit<Fr>>(
+    circuit: &C,
+    degree: Option<u32>,
+) -> Result<u32, VerifierError> {
+    let required = row_budget(circuit)?.minimum_k;
+    match degree {
+        Some(requested) if requested < required => Err(VerifierError::DegreeTooSmall {
+            required,
+            requested,
+        }),
+        Some(requested) => Ok(requested),
+        None => Ok(required),
+    }
+}
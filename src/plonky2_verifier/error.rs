@@ -0,0 +1,141 @@
+use std::fmt;
+
+use halo2_proofs::dev::{FailureLocation, VerifyFailure};
+
+/// Errors returned by the public entry points of the verifier.
+#[derive(Debug)]
+pub enum VerifierError {
+    /// The inner plonky2 circuit uses a gate that has no halo2 constrainer.
+    UnsupportedGate(String),
+    /// The inner plonky2 circuit uses FRI parameters the verifier circuit cannot check.
+    UnsupportedConfig(String),
+    /// The requested halo2 degree is smaller than the rows the verifier circuit needs.
+    DegreeTooSmall {
+        required: u32,
+        requested: u32,
+    },
+    /// `MockProver` could not synthesize the verifier circuit, e.g. `k` is too small.
+    Synthesis(halo2_proofs::plonk::Error),
+    /// The verifier circuit is not satisfied by the given plonky2 proof.
+    ConstraintFailure {
+        region: Option<String>,
+        row: usize,
+        reason: String,
+        num_failures: usize,
+    },
+    /// The trusted setup file is malformed, has an unexpected hash or is too small.
+    InvalidParams(String),
+    /// The plonky2 proof or stored keys do not belong to the expected inner circuit.
+    ShapeMismatch(String),
+    /// `keygen_vk` or `keygen_pk` failed.
+    Keygen(halo2_proofs::plonk::Error),
+    /// `create_proof` failed or the created proof did not verify.
+    Proving(halo2_proofs::plonk::Error),
+    /// The halo2 proof does not verify against the given verifying key and instances.
+    ProofRejected(halo2_proofs::plonk::Error),
+    /// A stored proof artifact is malformed or was produced for another verifying key.
+    InvalidArtifact(String),
+    /// Rendering the Solidity verifier failed.
+    Solidity(String),
+    /// The EVM verifier reverted or returned an unexpected output.
+    EvmRejected(String),
+    Io(std::io::Error),
+}
+
+impl VerifierError {
+    /// Builds a `ConstraintFailure` from the failures reported by `MockProver::verify`,
+    /// keeping the location of the first one.
+    pub(crate) fn from_verify_failures(failures: Vec<VerifyFailure>) -> Self {
+        let num_failures = failures.len();
+        let first = &failures[0];
+        let location = match first {
+            VerifyFailure::ConstraintNotSatisfied { location, .. } => Some(location),
+            VerifyFailure::Lookup { location, .. } => Some(location),
+            VerifyFailure::Permutation { location, .. } => Some(location),
+            _ => None,
+        };
+        let (region, row) = match location {
+            Some(FailureLocation::InRegion { region, offset }) => {
+                (Some(region.to_string()), *offset)
+            }
+            Some(FailureLocation::OutsideRegion { row }) => (None, *row),
+            None => match first {
+                VerifyFailure::CellNotAssigned { region, offset, .. } => {
+                    (Some(region.to_string()), *offset as usize)
+                }
+                _ => (None, 0),
+            },
+        };
+        VerifierError::ConstraintFailure {
+            region,
+            row,
+            reason: first.to_string(),
+            num_failures,
+        }
+    }
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifierError::UnsupportedGate(id) => write!(f, "unsupported plonky2 gate: {id}"),
+            VerifierError::UnsupportedConfig(e) => write!(f, "unsupported circuit config: {e}"),
+            VerifierError::DegreeTooSmall {
+                required,
+                requested,
+            } => {
+                write!(
+                    f,
+                    "k = {requested} is too small, the circuit needs k >= {required}"
+                )
+            }
+            VerifierError::Synthesis(e) => write!(f, "circuit synthesis failed: {e}"),
+            VerifierError::ConstraintFailure {
+                region,
+                row,
+                reason,
+                num_failures,
+            } => {
+                match region {
+                    Some(region) => write!(f, "constraint failure in {region} at row {row}")?,
+                    None => write!(f, "constraint failure at row {row}")?,
+                }
+                write!(f, " ({num_failures} in total): {reason}")
+            }
+            VerifierError::InvalidParams(e) => write!(f, "invalid KZG parameters: {e}"),
+            VerifierError::ShapeMismatch(e) => write!(f, "shape mismatch: {e}"),
+            VerifierError::Keygen(e) => write!(f, "key generation failed: {e}"),
+            VerifierError::Proving(e) => write!(f, "proving failed: {e}"),
+            VerifierError::ProofRejected(e) => write!(f, "halo2 proof rejected: {e}"),
+            VerifierError::InvalidArtifact(e) => write!(f, "invalid proof artifact: {e}"),
+            VerifierError::Solidity(e) => write!(f, "solidity generation failed: {e}"),
+            VerifierError::EvmRejected(e) => write!(f, "EVM verifier rejected the proof: {e}"),
+            VerifierError::Io(e) => write!(f, "io error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for VerifierError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VerifierError::Synthesis(e)
+            | VerifierError::Keygen(e)
+            | VerifierError::Proving(e)
+            | VerifierError::ProofRejected(e) => Some(e),
+            VerifierError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for VerifierError {
+    fn from(value: std::io::Error) -> Self {
+        VerifierError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for VerifierError {
+    fn from(value: serde_json::Error) -> Self {
+        VerifierError::Io(value.into())
+    }
+}
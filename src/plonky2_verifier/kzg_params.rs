@@ -0,0 +1,361 @@
+//! Loading of KZG parameters from a trusted setup file.
+//!
+//! The verifier never generates its own setup: `ParamsKZG::setup` samples the toxic waste
+//! locally, so a Solidity verifier built on top of it is unsound. Instead the parameters are
+//! read either from a file in halo2's raw format or from a perpetual powers of tau `.ptau`
+//! file (snarkjs format), checked against an expected SHA-256 digest and downsized to `k`.
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine, G2};
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::halo2curves::group::Group;
+use halo2_proofs::halo2curves::serde::SerdeObject;
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use sha2::{Digest, Sha256};
+
+use super::error::VerifierError;
+
+/// Size in bytes of a serialized BN254 base field element in a `.ptau` file.
+const PTAU_N8: usize = 32;
+
+/// Encoding of a trusted setup file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamsFormat {
+    /// `ParamsKZG::write` output.
+    Halo2,
+    /// snarkjs perpetual powers of tau file.
+    Ptau,
+}
+
+impl ParamsFormat {
+    /// Guesses the format from the file extension, defaulting to `Halo2`.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("ptau") => ParamsFormat::Ptau,
+            _ => ParamsFormat::Halo2,
+        }
+    }
+}
+
+/// Returns the hex encoded SHA-256 digest of the file at `path`.
+pub fn sha256_file(path: impl AsRef<Path>) -> Result<String, VerifierError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Reads KZG parameters for circuits of degree `k` from a trusted setup file.
+/// `expected_sha256` is the hex encoded SHA-256 digest of the whole file as published by the ceremony.
+pub fn load_params(
+    path: impl AsRef<Path>,
+    format: ParamsFormat,
+    k: u32,
+    expected_sha256: &str,
+) -> Result<ParamsKZG<Bn256>, VerifierError> {
+    let path = path.as_ref();
+    let digest = sha256_file(path)?;
+    if !digest.eq_ignore_ascii_case(expected_sha256.trim_start_matches("0x")) {
+        return Err(VerifierError::InvalidParams(format!(
+            "sha256 of {} is {digest}, expected {expected_sha256}",
+            path.display()
+        )));
+    }
+    let mut reader = BufReader::new(File::open(path)?);
+    match format {
+        ParamsFormat::Halo2 => {
+            let mut params = ParamsKZG::<Bn256>::read(&mut reader)?;
+            downsize(&mut params, k)?;
+            Ok(params)
+        }
+        ParamsFormat::Ptau => read_ptau(&mut reader, k),
+    }
+}
+
+/// Writes `params` in halo2's raw format, e.g. to cache the conversion of a `.ptau` file.
+pub fn save_params(path: impl AsRef<Path>, params: &ParamsKZG<Bn256>) -> Result<(), VerifierError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    params.write(&mut writer)?;
+    Ok(())
+}
+
+/// Downsizes `params` to `k`, failing if the setup is too small.
+pub fn downsize(params: &mut ParamsKZG<Bn256>, k: u32) -> Result<(), VerifierError> {
+    if params.k() < k {
+        return Err(VerifierError::InvalidParams(format!(
+            "setup supports k <= {}, but k = {k} is required",
+            params.k()
+        )));
+    }
+    if params.k() > k {
+        params.downsize(k);
+    }
+    Ok(())
+}
+
+/// Returns `params` downsized to `k` without cloning when it already has the right size.
+pub fn params_for_k(
+    params: &ParamsKZG<Bn256>,
+    k: u32,
+) -> Result<Cow<'_, ParamsKZG<Bn256>>, VerifierError> {
+    if params.k() == k {
+        return Ok(Cow::Borrowed(params));
+    }
+    let mut params = params.clone();
+    downsize(&mut params, k)?;
+    Ok(Cow::Owned(params))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, VerifierError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, VerifierError> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Reads a base field element stored in Montgomery form, little endian.
+fn read_fq<R: Read>(reader: &mut R) -> Result<Fq, VerifierError> {
+    let mut buf = [0u8; PTAU_N8];
+    reader.read_exact(&mut buf)?;
+    Option::from(Fq::from_raw_bytes(&buf))
+        .ok_or_else(|| VerifierError::InvalidParams("non canonical field element".to_string()))
+}
+
+fn read_g1<R: Read>(reader: &mut R) -> Result<G1Affine, VerifierError> {
+    let x = read_fq(reader)?;
+    let y = read_fq(reader)?;
+    let point = G1Affine { x, y };
+    if !bool::from(point.is_on_curve()) {
+        return Err(VerifierError::InvalidParams(
+            "G1 point is not on the curve".to_string(),
+        ));
+    }
+    Ok(point)
+}
+
+fn read_g2<R: Read>(reader: &mut R) -> Result<G2Affine, VerifierError> {
+    let x = Fq2 {
+        c0: read_fq(reader)?,
+        c1: read_fq(reader)?,
+    };
+    let y = Fq2 {
+        c0: read_fq(reader)?,
+        c1: read_fq(reader)?,
+    };
+    let point = G2Affine { x, y };
+    if !bool::from(point.is_on_curve()) {
+        return Err(VerifierError::InvalidParams(
+            "G2 point is not on the curve".to_string(),
+        ));
+    }
+    // unlike G1, the curve of G2 has a cofactor: `[r]P` is the identity only in the subgroup
+    if !bool::from((G2::from(point) * -Fr::ONE + point).is_identity()) {
+        return Err(VerifierError::InvalidParams(
+            "G2 point is not in the prime order subgroup".to_string(),
+        ));
+    }
+    Ok(point)
+}
+
+/// Parses a snarkjs `.ptau` file: a `ptau` magic, a version, and a list of sections
+/// `(type: u32, size: u64, data)`. Section 1 is the header, 2 holds `[tau^i]_1` and 3 holds `[tau^i]_2`.
+fn read_ptau<R: Read + Seek>(reader: &mut R, k: u32) -> Result<ParamsKZG<Bn256>, VerifierError> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"ptau" {
+        return Err(VerifierError::InvalidParams(
+            "missing ptau magic".to_string(),
+        ));
+    }
+    let _version = read_u32(reader)?;
+    let num_sections = read_u32(reader)?;
+    let mut sections = vec![];
+    for _ in 0..num_sections {
+        let section_type = read_u32(reader)?;
+        let size = read_u64(reader)?;
+        let offset = reader.stream_position()?;
+        sections.push((section_type, offset));
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    let section = |section_type: u32| {
+        sections
+            .iter()
+            .find(|(t, _)| *t == section_type)
+            .map(|(_, offset)| *offset)
+            .ok_or_else(|| {
+                VerifierError::InvalidParams(format!("missing ptau section {section_type}"))
+            })
+    };
+
+    reader.seek(SeekFrom::Start(section(1)?))?;
+    let n8 = read_u32(reader)? as usize;
+    if n8 != PTAU_N8 {
+        return Err(VerifierError::InvalidParams(format!(
+            "unsupported field size {n8}, expected BN254"
+        )));
+    }
+    reader.seek(SeekFrom::Current(n8 as i64))?;
+    let power = read_u32(reader)?;
+    if power < k {
+        return Err(VerifierError::InvalidParams(format!(
+            "setup supports k <= {power}, but k = {k} is required"
+        )));
+    }
+
+    reader.seek(SeekFrom::Start(section(2)?))?;
+    let g = (0..1usize << k)
+        .map(|_| read_g1(reader))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    reader.seek(SeekFrom::Start(section(3)?))?;
+    let g2 = read_g2(reader)?;
+    let s_g2 = read_g2(reader)?;
+
+    // `from_parts` only uses `self` for its type, any params of the right curve will do.
+    let empty = ParamsKZG::<Bn256>::unsafe_setup_with_s(0, Default::default());
+    Ok(empty.from_parts(k, g, None, g2, s_g2))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use halo2_proofs::arithmetic::CurveAffine;
+    use halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine};
+    use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+    use halo2_proofs::halo2curves::group::Curve;
+    use halo2_proofs::poly::commitment::Params;
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use num_bigint::BigUint;
+
+    use super::{load_params, save_params, sha256_file, ParamsFormat, PTAU_N8};
+    use crate::plonky2_verifier::error::VerifierError;
+
+    fn modulus() -> BigUint {
+        BigUint::parse_bytes(Fq::MODULUS[2..].as_bytes(), 16).unwrap()
+    }
+
+    /// `x` in Montgomery form, little endian, as snarkjs stores base field elements.
+    fn montgomery_bytes(x: Fq) -> Vec<u8> {
+        let x = BigUint::from_bytes_le(x.to_repr().as_ref());
+        let mut bytes = ((x << 256) % modulus()).to_bytes_le();
+        bytes.resize(PTAU_N8, 0);
+        bytes
+    }
+
+    /// Writes a `.ptau` file of `2^power` powers of `tau` in G1 and the G2 points `g2`, with the
+    /// sections out of order.
+    fn write_ptau(path: &Path, power: u32, tau: Fr, g2: [G2Affine; 2]) {
+        let mut header = PTAU_N8.to_le_bytes()[..4].to_vec();
+        header.extend(modulus().to_bytes_le());
+        header.extend(power.to_le_bytes());
+        header.extend(power.to_le_bytes());
+        let tau_g1 = (0..1u64 << power)
+            .flat_map(|i| {
+                let point = (G1Affine::generator() * tau.pow_vartime([i])).to_affine();
+                [point.x, point.y].map(montgomery_bytes).concat()
+            })
+            .collect::<Vec<_>>();
+        let tau_g2 = g2
+            .iter()
+            .flat_map(|point| {
+                [point.x.c0, point.x.c1, point.y.c0, point.y.c1]
+                    .map(montgomery_bytes)
+                    .concat()
+            })
+            .collect::<Vec<_>>();
+
+        let mut file = std::fs::File::create(path).unwrap();
+        file.write_all(b"ptau").unwrap();
+        file.write_all(&1u32.to_le_bytes()).unwrap();
+        file.write_all(&3u32.to_le_bytes()).unwrap();
+        for (section_type, data) in [(1u32, header), (3, tau_g2), (2, tau_g1)] {
+            file.write_all(&section_type.to_le_bytes()).unwrap();
+            file.write_all(&(data.len() as u64).to_le_bytes()).unwrap();
+            file.write_all(&data).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_load_ptau_params() {
+        let path = std::env::temp_dir().join("plonky2_verifier_test_powers_k4.ptau");
+        let tau = Fr::from(5);
+        let g2 = [
+            G2Affine::generator(),
+            (G2Affine::generator() * tau).to_affine(),
+        ];
+        write_ptau(&path, 4, tau, g2);
+        let digest = sha256_file(&path).unwrap();
+
+        let loaded = load_params(&path, ParamsFormat::Ptau, 3, &digest).unwrap();
+        assert_eq!(loaded.k(), 3);
+        let powers = (0..8)
+            .map(|i| (G1Affine::generator() * tau.pow_vartime([i])).to_affine())
+            .collect::<Vec<_>>();
+        assert_eq!(loaded.get_g(), &powers[..]);
+        assert_eq!(loaded.g2(), g2[0]);
+        assert_eq!(loaded.s_g2(), g2[1]);
+        assert!(matches!(
+            load_params(&path, ParamsFormat::Ptau, 5, &digest),
+            Err(VerifierError::InvalidParams(_))
+        ));
+
+        // a point of the G2 curve outside of the prime order subgroup
+        let outside = (1u64..)
+            .find_map(|i| {
+                let x = Fq2::new(Fq::from(i), Fq::ONE);
+                Option::<Fq2>::from((x.square() * x + G2Affine::b()).sqrt())
+                    .map(|y| G2Affine { x, y })
+            })
+            .unwrap();
+        assert!(bool::from(outside.is_on_curve()));
+        write_ptau(&path, 4, tau, [g2[0], outside]);
+        let digest = sha256_file(&path).unwrap();
+        assert!(matches!(
+            load_params(&path, ParamsFormat::Ptau, 3, &digest),
+            Err(VerifierError::InvalidParams(_))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_load_halo2_params() {
+        let path = std::env::temp_dir().join("plonky2_verifier_test_params_k8.srs");
+        let params = ParamsKZG::<Bn256>::setup(8, rand::thread_rng());
+        save_params(&path, &params).unwrap();
+        let digest = sha256_file(&path).unwrap();
+
+        let loaded = load_params(&path, ParamsFormat::Halo2, 6, &digest).unwrap();
+        assert_eq!(loaded.k(), 6);
+        assert_eq!(loaded.s_g2(), params.s_g2());
+
+        let tampered = "00".repeat(32);
+        assert!(matches!(
+            load_params(&path, ParamsFormat::Halo2, 6, &tampered),
+            Err(VerifierError::InvalidParams(_))
+        ));
+        assert!(matches!(
+            load_params(&path, ParamsFormat::Halo2, 9, &digest),
+            Err(VerifierError::InvalidParams(_))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+}
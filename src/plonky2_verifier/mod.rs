@@ -1,6 +1,13 @@
+pub mod artifact;
+pub mod batch_verifier;
 pub mod bn245_poseidon;
 pub mod chip;
 pub mod context;
+pub mod degree;
+pub mod error;
+pub mod kzg_params;
+pub mod profile;
 pub mod types;
 pub mod verifier_api;
 pub mod verifier_circuit;
+pub mod verifier_setup;
@@ -0,0 +1,344 @@
+//! Cost of the `Verifier` circuit broken down by verification stage.
+//!
+//! `RegionCtx` keeps a `Profiler` that chips feed through `RegionCtx::stage`: a stage is charged
+//! the rows its closure advances the offset by and the table lookups it records, nested
+//! stages are charged to their parents as well. Stages are identified by their `/` separated path,
+//! so repeated stages (e.g. one per FRI query) are merged and counted in `calls`.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner};
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error, FloorPlanner};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::plonk::config::GenericConfig;
+use serde::{Deserialize, Serialize};
+
+use super::chip::goldilocks_chip::GoldilocksChipConfig;
+use super::chip::hasher_chip::AlgebraicHasherChip;
+use super::chip::native_chip::all_chip::OptionalChips;
+use super::degree::RowCounter;
+use super::error::VerifierError;
+use super::verifier_circuit::Verifier;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageCost {
+    /// `/` separated names of the enclosing stages and of this one.
+    pub path: String,
+    pub depth: usize,
+    pub calls: usize,
+    pub rows: usize,
+    pub lookups: usize,
+}
+
+impl StageCost {
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostProfile {
+    /// Stages in order of first entry, parents before their children.
+    pub stages: Vec<StageCost>,
+    /// Rows of the verification region.
+    pub rows: usize,
+    /// Lookups into the 16 bit range table and, when configured, the Keccak table.
+    pub lookups: usize,
+}
+
+impl CostProfile {
+    pub fn stage(&self, path: &str) -> Option<&StageCost> {
+        self.stages.iter().find(|stage| stage.path == path)
+    }
+
+    pub fn to_json(&self) -> Result<String, VerifierError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, VerifierError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Renders the stages as an indented table with their share of the region rows.
+    pub fn to_table(&self) -> String {
+        let mut table = format!(
+            "{:<48} {:>8} {:>10} {:>7} {:>10}\n",
+            "stage", "calls", "rows", "%", "lookups"
+        );
+        for stage in self.stages.iter() {
+            let name = format!("{}{}", "  ".repeat(stage.depth), stage.name());
+            let _ = writeln!(
+                table,
+                "{:<48} {:>8} {:>10} {:>7.2} {:>10}",
+                name,
+                stage.calls,
+                stage.rows,
+                percent(stage.rows, self.rows),
+                stage.lookups
+            );
+        }
+        let _ = writeln!(
+            table,
+            "{:<48} {:>8} {:>10} {:>7.2} {:>10}",
+            "total", "", self.rows, 100.0, self.lookups
+        );
+        table
+    }
+
+    /// Renders the row and lookup deltas of every stage relative to `baseline`, e.g. a profile
+    /// saved before changing the inner `CircuitConfig`. Stages missing on one side count as zero.
+    pub fn compare(&self, baseline: &CostProfile) -> String {
+        let mut paths = baseline
+            .stages
+            .iter()
+            .map(|stage| stage.path.clone())
+            .collect::<Vec<_>>();
+        for stage in self.stages.iter() {
+            if baseline.stage(&stage.path).is_none() {
+                paths.push(stage.path.clone());
+            }
+        }
+        let costs = |profile: &CostProfile, path: &str| {
+            profile
+                .stage(path)
+                .map(|stage| (stage.rows, stage.lookups))
+                .unwrap_or_default()
+        };
+        let mut table = format!(
+            "{:<64} {:>10} {:>10} {:>10} {:>10}\n",
+            "stage", "rows", "Δ rows", "lookups", "Δ lookups"
+        );
+        let mut row = |path: &str,
+                       (old_rows, old_lookups): (usize, usize),
+                       (rows, lookups): (usize, usize)| {
+            let _ = writeln!(
+                table,
+                "{:<64} {:>10} {:>+10} {:>10} {:>+10}",
+                path,
+                rows,
+                rows as i64 - old_rows as i64,
+                lookups,
+                lookups as i64 - old_lookups as i64
+            );
+        };
+        for path in paths.iter() {
+            row(path, costs(baseline, path), costs(self, path));
+        }
+        row(
+            "total",
+            (baseline.rows, baseline.lookups),
+            (self.rows, self.lookups),
+        );
+        table
+    }
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        100.0 * part as f64 / total as f64
+    }
+}
+
+/// Stage bookkeeping of a `RegionCtx`.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    stages: Vec<StageCost>,
+    index: HashMap<String, usize>,
+    /// `(stage index, offset, lookups)` at the entry of every open stage.
+    open: Vec<(usize, usize, usize)>,
+    lookups: usize,
+}
+
+impl Profiler {
+    pub fn enter(&mut self, name: &str, offset: usize) {
+        let path = match self.open.last() {
+            Some(&(parent, _, _)) => format!("{}/{name}", self.stages[parent].path),
+            None => name.to_string(),
+        };
+        let depth = self.open.len();
+        let stages = &mut self.stages;
+        let index = *self.index.entry(path.clone()).or_insert_with(|| {
+            stages.push(StageCost {
+                path,
+                depth,
+                ..Default::default()
+            });
+            stages.len() - 1
+        });
+        self.stages[index].calls += 1;
+        self.open.push((index, offset, self.lookups));
+    }
+
+    pub fn exit(&mut self, offset: usize) {
+        let (index, start, lookups) = self.open.pop().expect("no open stage");
+        let stage = &mut self.stages[index];
+        stage.rows += offset.saturating_sub(start);
+        stage.lookups += self.lookups - lookups;
+    }
+
+    pub fn record_lookups(&mut self, lookups: usize) {
+        self.lookups += lookups;
+    }
+
+    /// Returns the profile of a region whose last offset is `rows`, closing any open stage.
+    pub fn finish(&mut self, rows: usize) -> CostProfile {
+        while !self.open.is_empty() {
+            self.exit(rows);
+        }
+        self.index.clear();
+        CostProfile {
+            stages: std::mem::take(&mut self.stages),
+            rows,
+            lookups: std::mem::take(&mut self.lookups),
+        }
+    }
+}
+
+/// Runs `Verifier::synthesize_with_profile` and keeps the profile of the last pass.
+struct ProfiledVerifier<'a, C> {
+    verifier: &'a Verifier<C>,
+    profile: RefCell<CostProfile>,
+}
+
+impl<C: GenericConfig<2, F = GoldilocksField>> Circuit<Fr> for ProfiledVerifier<'_, C>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    type Config = GoldilocksChipConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = OptionalChips;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            verifier: self.verifier,
+            profile: RefCell::default(),
+        }
+    }
+
+    fn params(&self) -> Self::Params {
+        self.verifier.params()
+    }
+
+    fn configure_with_params(
+        meta: &mut ConstraintSystem<Fr>,
+        params: Self::Params,
+    ) -> Self::Config {
+        Verifier::<C>::configure_with_params(meta, params)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Verifier::<C>::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        *self.profile.borrow_mut() = self.verifier.synthesize_with_profile(config, layouter)?;
+        Ok(())
+    }
+}
+
+/// Synthesizes `circuit` without witnesses and reports the cost of every verification stage.
+pub fn cost_profile<C: GenericConfig<2, F = GoldilocksField>>(
+    circuit: &Verifier<C>,
+) -> Result<CostProfile, VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let mut cs = ConstraintSystem::<Fr>::default();
+    let config = Verifier::<C>::configure_with_params(&mut cs, circuit.params());
+    let without_witnesses = circuit.without_witnesses();
+    let profiled = ProfiledVerifier {
+        verifier: &without_witnesses,
+        profile: RefCell::default(),
+    };
+    SimpleFloorPlanner::synthesize(
+        &mut RowCounter::default(),
+        &profiled,
+        config,
+        cs.constants().clone(),
+    )
+    .map_err(VerifierError::Synthesis)?;
+    Ok(profiled.profile.into_inner())
+}
+
+/// Sums the costs of stages with the same name, wherever they are nested, e.g. the rows spent in
+/// every `merkle_proof` across FRI queries and initial trees.
+pub fn costs_by_name(profile: &CostProfile) -> BTreeMap<String, (usize, usize)> {
+    let mut costs = BTreeMap::<String, (usize, usize)>::new();
+    for stage in profile.stages.iter() {
+        // nested stages with the same name are already included in their ancestor
+        let nested = stage
+            .path
+            .split('/')
+            .rev()
+            .skip(1)
+            .any(|ancestor| ancestor == stage.name());
+        if nested {
+            continue;
+        }
+        let entry = costs.entry(stage.name().to_string()).or_default();
+        entry.0 += stage.rows;
+        entry.1 += stage.lookups;
+    }
+    costs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{costs_by_name, CostProfile, Profiler};
+
+    #[test]
+    fn test_profiler_nesting() {
+        let mut profiler = Profiler::default();
+        profiler.enter("fri", 10);
+        for query in 0..2 {
+            let start = 10 + query * 30;
+            profiler.enter("query", start);
+            profiler.enter("merkle_proof", start);
+            profiler.record_lookups(9);
+            profiler.exit(start + 20);
+            profiler.record_lookups(18);
+            profiler.exit(start + 30);
+        }
+        profiler.exit(70);
+        profiler.enter("merkle_proof", 70);
+        profiler.record_lookups(9);
+        profiler.exit(75);
+        let profile = profiler.finish(80);
+
+        assert_eq!(profile.rows, 80);
+        assert_eq!(profile.lookups, 63);
+        let paths = profile
+            .stages
+            .iter()
+            .map(|stage| {
+                (
+                    stage.path.as_str(),
+                    stage.depth,
+                    stage.calls,
+                    stage.rows,
+                    stage.lookups,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                ("fri", 0, 1, 60, 54),
+                ("fri/query", 1, 2, 60, 54),
+                ("fri/query/merkle_proof", 2, 2, 40, 18),
+                ("merkle_proof", 0, 1, 5, 9),
+            ]
+        );
+        assert_eq!(costs_by_name(&profile)["merkle_proof"], (45, 27));
+
+        let json = profile.to_json().unwrap();
+        assert_eq!(CostProfile::from_json(&json).unwrap(), profile);
+        let comparison = profile.compare(&CostProfile::default());
+        assert!(comparison.contains("fri/query/merkle_proof"));
+    }
+}
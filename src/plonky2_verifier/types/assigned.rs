@@ -20,6 +20,8 @@ pub struct AssignedOpeningSetValues<F: PrimeField, const D: usize> {
     pub plonk_zs_next: Vec<AssignedExtensionFieldValue<F, D>>,
     pub partial_products: Vec<AssignedExtensionFieldValue<F, D>>,
     pub quotient_polys: Vec<AssignedExtensionFieldValue<F, D>>,
+    pub lookup_zs: Vec<AssignedExtensionFieldValue<F, D>>,
+    pub lookup_zs_next: Vec<AssignedExtensionFieldValue<F, D>>,
 }
 
 impl<F: PrimeField, const D: usize> AssignedOpeningSetValues<F, D> {
@@ -32,11 +34,16 @@ impl<F: PrimeField, const D: usize> AssignedOpeningSetValues<F, D> {
                 self.plonk_zs.as_slice(),
                 self.partial_products.as_slice(),
                 self.quotient_polys.as_slice(),
+                self.lookup_zs.as_slice(),
             ]
             .concat(),
         };
         let zeta_next_batch = AssignedFriOpeningBatch {
-            values: self.plonk_zs_next.clone(),
+            values: [
+                self.plonk_zs_next.as_slice(),
+                self.lookup_zs_next.as_slice(),
+            ]
+            .concat(),
         };
         AssignedFriOpenings {
             batches: vec![zeta_batch, zeta_next_batch],
@@ -137,6 +144,56 @@ pub struct AssignedProofChallenges<F: PrimeField, const D: usize> {
     pub plonk_betas: Vec<AssignedValue<F>>,
     pub plonk_gammas: Vec<AssignedValue<F>>,
     pub plonk_alphas: Vec<AssignedValue<F>>,
+    /// `NUM_COINS_LOOKUP` lookup challenges per challenge round, empty without lookups.
+    pub plonk_deltas: Vec<AssignedValue<F>>,
     pub plonk_zeta: AssignedExtensionFieldValue<F, D>,
     pub fri_challenges: AssignedFriChallenges<F, D>,
 }
+
+pub struct AssignedStarkOpeningSetValues<F: PrimeField, const D: usize> {
+    pub local_values: Vec<AssignedExtensionFieldValue<F, D>>,
+    pub next_values: Vec<AssignedExtensionFieldValue<F, D>>,
+    pub auxiliary_polys: Vec<AssignedExtensionFieldValue<F, D>>,
+    pub auxiliary_polys_next: Vec<AssignedExtensionFieldValue<F, D>>,
+    pub quotient_polys: Vec<AssignedExtensionFieldValue<F, D>>,
+}
+
+impl<F: PrimeField, const D: usize> AssignedStarkOpeningSetValues<F, D> {
+    pub(crate) fn to_fri_openings(&self) -> AssignedFriOpenings<F, D> {
+        let zeta_batch = AssignedFriOpeningBatch {
+            values: [
+                self.local_values.as_slice(),
+                self.auxiliary_polys.as_slice(),
+                self.quotient_polys.as_slice(),
+            ]
+            .concat(),
+        };
+        let zeta_next_batch = AssignedFriOpeningBatch {
+            values: [
+                self.next_values.as_slice(),
+                self.auxiliary_polys_next.as_slice(),
+            ]
+            .concat(),
+        };
+        AssignedFriOpenings {
+            batches: vec![zeta_batch, zeta_next_batch],
+        }
+    }
+}
+
+pub struct AssignedStarkProofValues<F: PrimeField, const D: usize> {
+    pub trace_cap: AssignedMerkleCapValues<F>,
+    pub auxiliary_polys_cap: Option<AssignedMerkleCapValues<F>>,
+    pub quotient_polys_cap: AssignedMerkleCapValues<F>,
+
+    pub openings: AssignedStarkOpeningSetValues<F, D>,
+    pub opening_proof: AssignedFriProofValues<F, D>,
+}
+
+pub struct AssignedStarkProofChallenges<F: PrimeField, const D: usize> {
+    /// One logUp challenge per challenge round, empty without lookups.
+    pub lookup_challenges: Vec<AssignedValue<F>>,
+    pub stark_alphas: Vec<AssignedValue<F>>,
+    pub stark_zeta: AssignedExtensionFieldValue<F, D>,
+    pub fri_challenges: AssignedFriChallenges<F, D>,
+}
@@ -1,10 +1,18 @@
-use std::ops::{Range, RangeFrom};
+use std::ops::Range;
 
-use crate::plonky2_verifier::{chip::plonk::gates::CustomGateRef, types::fri::FriOracleInfo};
+use crate::plonky2_verifier::{
+    chip::plonk::gates::{registry::GateRegistry, CustomGateRef},
+    error::VerifierError,
+    types::fri::FriOracleInfo,
+};
 
 use super::{fri::FriPolynomialInfo, to_goldilocks};
 use halo2_proofs::halo2curves::ff::PrimeField;
-use plonky2::{field::goldilocks_field::GoldilocksField, plonk::circuit_data::CommonCircuitData};
+use plonky2::{
+    field::{goldilocks_field::GoldilocksField, types::PrimeField64},
+    plonk::circuit_data::CommonCircuitData,
+};
+use sha2::{Digest, Sha256};
 
 #[derive(Clone, Debug, Default)]
 pub struct FriConfig {
@@ -39,6 +47,9 @@ pub struct CircuitConfig {
     pub fri_config: FriConfig,
 }
 
+/// Largest FRI folding arity, in bits, that `FriVerifierChip` interpolates.
+pub const MAX_FRI_ARITY_BITS: usize = 4;
+
 #[derive(Clone, Debug, Default)]
 pub struct FriParams {
     pub config: FriConfig,
@@ -51,6 +62,40 @@ impl FriParams {
     pub fn lde_bits(&self) -> usize {
         self.degree_bits + self.config.rate_bits
     }
+
+    pub fn total_arities(&self) -> usize {
+        self.reduction_arity_bits.iter().sum()
+    }
+
+    /// The number of coefficients of the final FRI polynomial.
+    pub fn final_poly_len(&self) -> usize {
+        1 << (self.degree_bits - self.total_arities())
+    }
+
+    /// Converts plonky2's `FriParams`, rejecting FRI arities `FriVerifierChip` can't fold.
+    pub fn from_plonky2(value: &plonky2::fri::FriParams) -> Result<Self, VerifierError> {
+        let reduction_arity_bits = &value.reduction_arity_bits;
+        if let Some(arity_bits) = reduction_arity_bits
+            .iter()
+            .find(|&&arity_bits| arity_bits == 0 || arity_bits > MAX_FRI_ARITY_BITS)
+        {
+            return Err(VerifierError::UnsupportedConfig(format!(
+                "FRI reduction arity 2^{arity_bits} in {reduction_arity_bits:?}, \
+                 arities from 2^1 to 2^{MAX_FRI_ARITY_BITS} are supported"
+            )));
+        }
+        Ok(Self {
+            config: FriConfig {
+                rate_bits: value.config.rate_bits,
+                cap_height: value.config.cap_height,
+                proof_of_work_bits: value.config.proof_of_work_bits,
+                num_query_rounds: value.config.num_query_rounds,
+            },
+            hiding: value.hiding,
+            degree_bits: value.degree_bits,
+            reduction_arity_bits: value.reduction_arity_bits.clone(),
+        })
+    }
 }
 
 #[derive(Clone, Default, Debug)]
@@ -74,6 +119,9 @@ pub struct CommonData<F: PrimeField> {
     /// The types of gates used in this circuit, along with their prefixes.
     pub gates: Vec<CustomGateRef<F>>,
 
+    /// The plonky2 ids of `gates`, which include their parameters.
+    pub gate_ids: Vec<String>,
+
     /// Information on the circuit's selector polynomials.
     pub selectors_info: SelectorsInfo,
 
@@ -93,6 +141,16 @@ pub struct CommonData<F: PrimeField> {
 
     /// The number of partial products needed to compute the `Z` polynomials.
     pub num_partial_products: usize,
+
+    /// The number of lookup polynomials per challenge: one RE polynomial followed by the SLDC
+    /// polynomials. Zero when the circuit has no lookups.
+    pub num_lookup_polys: usize,
+
+    /// The number of lookup selectors, stored in the constants after the gate selectors.
+    pub num_lookup_selectors: usize,
+
+    /// The `(input, output)` pairs of every lookup table.
+    pub luts: Vec<Vec<(u16, u16)>>,
 }
 
 /// Holds the Merkle tree index and blinding flag of a set of polynomials used in FRI.
@@ -146,8 +204,90 @@ impl<F: PrimeField> CommonData<F> {
     }
 
     /// Range of the partial products polynomials in the `zs_partial_products_commitment`.
-    pub fn partial_products_range(&self) -> RangeFrom<usize> {
-        self.config.num_challenges..
+    pub fn partial_products_range(&self) -> Range<usize> {
+        self.config.num_challenges..self.num_zs_partial_products_polys()
+    }
+
+    /// Range of the lookup polynomials in the `zs_partial_products_commitment`.
+    pub fn lookup_range(&self) -> Range<usize> {
+        self.num_zs_partial_products_polys()
+            ..self.num_zs_partial_products_polys() + self.num_all_lookup_polys()
+    }
+
+    pub fn has_lookup(&self) -> bool {
+        self.num_lookup_polys != 0
+    }
+
+    /// SHA-256 digest of everything the verifier circuit depends on besides the verification key,
+    /// so that halo2 keys can be checked against the `CommonData` they were generated for.
+    pub fn digest(&self) -> [u8; 32] {
+        let config = &self.config;
+        let fri_params = &self.fri_params;
+        let mut hasher = Sha256::new();
+        let words = [
+            config.num_wires,
+            config.num_routed_wires,
+            config.num_constants,
+            config.use_base_arithmetic_gate as usize,
+            config.security_bits,
+            config.num_challenges,
+            config.zero_knowledge as usize,
+            config.max_quotient_degree_factor,
+            config.fri_config.rate_bits,
+            config.fri_config.cap_height,
+            config.fri_config.proof_of_work_bits as usize,
+            config.fri_config.num_query_rounds,
+            fri_params.config.rate_bits,
+            fri_params.config.cap_height,
+            fri_params.config.proof_of_work_bits as usize,
+            fri_params.config.num_query_rounds,
+            fri_params.hiding as usize,
+            fri_params.degree_bits,
+            self.quotient_degree_factor,
+            self.num_gate_constraints,
+            self.num_constants,
+            self.num_public_inputs,
+            self.num_partial_products,
+            self.num_lookup_polys,
+            self.num_lookup_selectors,
+        ];
+        update_words(&mut hasher, words.map(|word| word as u64));
+        update_words(
+            &mut hasher,
+            fri_params
+                .reduction_arity_bits
+                .iter()
+                .map(|&bits| bits as u64),
+        );
+        update_words(
+            &mut hasher,
+            self.selectors_info
+                .selector_indices
+                .iter()
+                .map(|&index| index as u64),
+        );
+        update_words(
+            &mut hasher,
+            self.selectors_info
+                .groups
+                .iter()
+                .flat_map(|group| [group.start as u64, group.end as u64]),
+        );
+        update_words(&mut hasher, self.k_is.iter().map(|k| k.to_canonical_u64()));
+        update_words(&mut hasher, [self.luts.len() as u64]);
+        for lut in self.luts.iter() {
+            update_words(
+                &mut hasher,
+                lut.iter()
+                    .flat_map(|&(input, output)| [input as u64, output as u64]),
+            );
+        }
+        update_words(&mut hasher, [self.gate_ids.len() as u64]);
+        for id in self.gate_ids.iter() {
+            update_words(&mut hasher, [id.len() as u64]);
+            hasher.update(id.as_bytes());
+        }
+        hasher.finalize().into()
     }
 
     fn fri_preprocessed_polys(&self) -> Vec<FriPolynomialInfo> {
@@ -181,6 +321,19 @@ impl<F: PrimeField> CommonData<F> {
         FriPolynomialInfo::from_range(PlonkOracle::ZS_PARTIAL_PRODUCTS.index, self.zs_range())
     }
 
+    pub(crate) fn num_all_lookup_polys(&self) -> usize {
+        self.config.num_challenges * self.num_lookup_polys
+    }
+
+    fn fri_lookup_polys(&self) -> Vec<FriPolynomialInfo> {
+        FriPolynomialInfo::from_range(PlonkOracle::ZS_PARTIAL_PRODUCTS.index, self.lookup_range())
+    }
+
+    /// Polynomials opened at `g * zeta`: the `Z`s, followed by the lookup polynomials if any.
+    pub fn fri_next_batch_polys(&self) -> Vec<FriPolynomialInfo> {
+        [self.fri_zs_polys(), self.fri_lookup_polys()].concat()
+    }
+
     pub(crate) fn num_quotient_polys(&self) -> usize {
         self.config.num_challenges * self.quotient_degree_factor
     }
@@ -195,6 +348,7 @@ impl<F: PrimeField> CommonData<F> {
             self.fri_wire_polys(),
             self.fri_zs_partial_products_polys(),
             self.fri_quotient_polys(),
+            self.fri_lookup_polys(),
         ]
         .concat()
     }
@@ -209,8 +363,9 @@ impl<F: PrimeField> CommonData<F> {
                 num_polys: self.config.num_wires,
                 blinding: PlonkOracle::WIRES.blinding,
             },
+            // the lookup polynomials are committed along with the `Z`s and partial products
             FriOracleInfo {
-                num_polys: self.num_zs_partial_products_polys(),
+                num_polys: self.num_zs_partial_products_polys() + self.num_all_lookup_polys(),
                 blinding: PlonkOracle::ZS_PARTIAL_PRODUCTS.blinding,
             },
             FriOracleInfo {
@@ -221,9 +376,23 @@ impl<F: PrimeField> CommonData<F> {
     }
 }
 
-impl<F: PrimeField> From<CommonCircuitData<GoldilocksField, 2>> for CommonData<F> {
-    fn from(value: CommonCircuitData<GoldilocksField, 2>) -> Self {
-        Self {
+impl<F: PrimeField> TryFrom<CommonCircuitData<GoldilocksField, 2>> for CommonData<F> {
+    type Error = VerifierError;
+
+    fn try_from(value: CommonCircuitData<GoldilocksField, 2>) -> Result<Self, Self::Error> {
+        Self::from_plonky2(value, &GateRegistry::default())
+    }
+}
+
+impl<F: PrimeField> CommonData<F> {
+    /// Converts plonky2's `CommonCircuitData`, looking the constrainer of every gate up in
+    /// `registry`, e.g. one extended with the gates of a downstream crate.
+    pub fn from_plonky2(
+        value: CommonCircuitData<GoldilocksField, 2>,
+        registry: &GateRegistry<F>,
+    ) -> Result<Self, VerifierError> {
+        let fri_params = FriParams::from_plonky2(&value.fri_params)?;
+        Ok(Self {
             config: CircuitConfig {
                 num_wires: value.config.num_wires,
                 num_routed_wires: value.config.num_routed_wires,
@@ -243,19 +412,10 @@ impl<F: PrimeField> From<CommonCircuitData<GoldilocksField, 2>> for CommonData<F
             gates: value
                 .gates
                 .iter()
-                .map(|gate| CustomGateRef::from(gate))
-                .collect(),
-            fri_params: FriParams {
-                config: FriConfig {
-                    rate_bits: value.config.fri_config.rate_bits,
-                    cap_height: value.config.fri_config.cap_height,
-                    proof_of_work_bits: value.config.fri_config.proof_of_work_bits,
-                    num_query_rounds: value.config.fri_config.num_query_rounds,
-                },
-                hiding: value.fri_params.hiding,
-                degree_bits: value.fri_params.degree_bits,
-                reduction_arity_bits: value.fri_params.reduction_arity_bits,
-            },
+                .map(|gate| registry.constrainer(gate))
+                .collect::<Result<Vec<_>, VerifierError>>()?,
+            gate_ids: value.gates.iter().map(|gate| gate.0.id()).collect(),
+            fri_params,
             selectors_info: SelectorsInfo {
                 selector_indices: value.selectors_info.selector_indices,
                 groups: value.selectors_info.groups,
@@ -266,6 +426,18 @@ impl<F: PrimeField> From<CommonCircuitData<GoldilocksField, 2>> for CommonData<F
             num_public_inputs: value.num_public_inputs,
             k_is: value.k_is.iter().map(|e| to_goldilocks(*e)).collect(),
             num_partial_products: value.num_partial_products,
-        }
+            num_lookup_polys: value.num_lookup_polys,
+            num_lookup_selectors: value.num_lookup_selectors,
+            luts: value.luts.iter().map(|lut| lut.to_vec()).collect(),
+        })
+    }
+}
+
+/// Hashes the number of `words` followed by the words, little endian.
+fn update_words(hasher: &mut Sha256, words: impl IntoIterator<Item = u64>) {
+    let words = words.into_iter().collect::<Vec<_>>();
+    hasher.update((words.len() as u64).to_le_bytes());
+    for word in words {
+        hasher.update(word.to_le_bytes());
     }
 }
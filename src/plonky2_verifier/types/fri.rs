@@ -58,10 +58,10 @@ impl<F: PrimeField, const D: usize> FriInstanceInfo<F, D> {
             polynomials: common_data.fri_all_polys(),
         };
 
-        // The Z polynomials are also opened at g * zeta.
+        // The Z polynomials, and the lookup polynomials if any, are also opened at g * zeta.
         let zeta_next_batch = FriBatchInfo {
             point: zeta_next.clone(),
-            polynomials: common_data.fri_zs_polys(),
+            polynomials: common_data.fri_next_batch_polys(),
         };
 
         let openings = vec![zeta_batch, zeta_next_batch];
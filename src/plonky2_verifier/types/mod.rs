@@ -1,7 +1,6 @@
 use std::marker::PhantomData;
 
 use crate::plonky2_verifier::context::RegionCtx;
-use halo2_proofs::circuit::Value;
 use halo2_proofs::halo2curves::ff::PrimeField;
 use halo2_proofs::plonk::Error;
 use halo2wrong_maingate::AssignedValue;
@@ -10,11 +9,11 @@ use plonky2::field::types::Field;
 use plonky2::{
     field::goldilocks_field::GoldilocksField,
     hash::{hash_types::HashOut, merkle_tree::MerkleCap},
+    plonk::config::{GenericHashOut, Hasher},
 };
 
 use self::assigned::{AssignedExtensionFieldValue, AssignedHashValues, AssignedMerkleCapValues};
 
-use super::bn245_poseidon::plonky2_config::Bn254PoseidonHash;
 use super::chip::goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig};
 use super::chip::native_chip::utils::goldilocks_to_fe;
 
@@ -22,6 +21,7 @@ pub mod assigned;
 pub mod common_data;
 pub mod fri;
 pub mod proof;
+pub mod stark;
 pub mod verification_key;
 
 pub fn to_goldilocks(e: GoldilocksField) -> GoldilocksField {
@@ -44,7 +44,10 @@ impl<F: PrimeField> HashValues<F> {
         let elements = hash_value
             .elements
             .iter()
-            .map(|e| goldilocks_chip.assign_value(ctx, Value::known(goldilocks_to_fe(*e))))
+            .map(|e| {
+                let value = ctx.witness(goldilocks_to_fe(*e));
+                goldilocks_chip.assign_value(ctx, value)
+            })
             .collect::<Result<Vec<AssignedValue<F>>, Error>>()
             .unwrap()
             .try_into()
@@ -72,8 +75,17 @@ impl<F: PrimeField> HashValues<F> {
 
 impl<F: PrimeField> From<HashOut<GoldilocksField>> for HashValues<F> {
     fn from(value: HashOut<GoldilocksField>) -> Self {
+        Self::from_hash(value)
+    }
+}
+
+impl<F: PrimeField> HashValues<F> {
+    /// Elements of a digest of any plonky2 hasher, as observed by the challenger
+    pub fn from_hash<H: GenericHashOut<GoldilocksField>>(value: H) -> Self {
         let mut elements = [GoldilocksField::ZERO; 4];
-        for (to, from) in elements.iter_mut().zip(value.elements.iter()) {
+        let value_elements = value.to_vec();
+        assert_eq!(value_elements.len(), elements.len());
+        for (to, from) in elements.iter_mut().zip(value_elements.iter()) {
             *to = to_goldilocks(*from);
         }
         HashValues {
@@ -114,9 +126,11 @@ impl<F: PrimeField> MerkleCapValues<F> {
     }
 }
 
-impl<F: PrimeField> From<MerkleCap<GoldilocksField, Bn254PoseidonHash>> for MerkleCapValues<F> {
-    fn from(value: MerkleCap<GoldilocksField, Bn254PoseidonHash>) -> Self {
-        let cap_values = value.0.iter().map(|h| HashValues::from(*h)).collect();
+impl<F: PrimeField, H: Hasher<GoldilocksField>> From<MerkleCap<GoldilocksField, H>>
+    for MerkleCapValues<F>
+{
+    fn from(value: MerkleCap<GoldilocksField, H>) -> Self {
+        let cap_values = value.0.iter().map(|h| HashValues::from_hash(*h)).collect();
         MerkleCapValues(cap_values)
     }
 }
@@ -147,7 +161,10 @@ impl<F: PrimeField, const D: usize> ExtensionFieldValue<F, D> {
         let elements = extension_field_value
             .elements
             .iter()
-            .map(|v| goldilocks_chip.assign_value(ctx, Value::known(goldilocks_to_fe(*v))))
+            .map(|v| {
+                let value = ctx.witness(goldilocks_to_fe(*v));
+                goldilocks_chip.assign_value(ctx, value)
+            })
             .collect::<Result<Vec<AssignedValue<F>>, Error>>()?
             .try_into()
             .unwrap();
@@ -1,6 +1,3 @@
-use crate::plonky2_verifier::bn245_poseidon::plonky2_config::{
-    Bn254PoseidonGoldilocksConfig, Bn254PoseidonHash,
-};
 use crate::plonky2_verifier::chip::goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig};
 use crate::plonky2_verifier::chip::native_chip::utils::goldilocks_to_fe;
 
@@ -10,11 +7,12 @@ use super::assigned::{
     AssignedMerkleCapValues, AssignedMerkleProofValues, AssignedOpeningSetValues,
     AssignedPolynomialCoeffsExtValues,
 };
+use super::common_data::{CommonData, FriParams};
+use super::fri::FriOracleInfo;
 use super::{
     to_extension_field_values, to_goldilocks, ExtensionFieldValue, HashValues, MerkleCapValues,
 };
 use crate::plonky2_verifier::context::RegionCtx;
-use halo2_proofs::circuit::Value;
 use halo2_proofs::halo2curves::ff::PrimeField;
 use halo2_proofs::plonk::Error;
 use halo2wrong_maingate::AssignedValue;
@@ -24,6 +22,7 @@ use plonky2::field::polynomial::PolynomialCoeffs;
 use plonky2::field::types::Field;
 use plonky2::fri::proof::{FriProof, FriQueryRound};
 use plonky2::hash::merkle_proofs::MerkleProof;
+use plonky2::plonk::config::{GenericConfig, Hasher};
 use plonky2::plonk::proof::{OpeningSet, Proof};
 use plonky2::{
     field::goldilocks_field::GoldilocksField,
@@ -39,6 +38,8 @@ pub struct OpeningSetValues<F: PrimeField, const D: usize> {
     pub plonk_zs_next: Vec<ExtensionFieldValue<F, D>>,
     pub partial_products: Vec<ExtensionFieldValue<F, D>>,
     pub quotient_polys: Vec<ExtensionFieldValue<F, D>>,
+    pub lookup_zs: Vec<ExtensionFieldValue<F, D>>,
+    pub lookup_zs_next: Vec<ExtensionFieldValue<F, D>>,
 }
 
 impl<F: PrimeField> From<OpeningSet<GoldilocksField, 2>> for OpeningSetValues<F, 2> {
@@ -51,6 +52,8 @@ impl<F: PrimeField> From<OpeningSet<GoldilocksField, 2>> for OpeningSetValues<F,
             plonk_zs_next: to_extension_field_values(value.plonk_zs_next),
             partial_products: to_extension_field_values(value.partial_products),
             quotient_polys: to_extension_field_values(value.quotient_polys),
+            lookup_zs: to_extension_field_values(value.lookup_zs),
+            lookup_zs_next: to_extension_field_values(value.lookup_zs_next),
         }
     }
 }
@@ -96,6 +99,16 @@ impl<F: PrimeField, const D: usize> OpeningSetValues<F, D> {
             .iter()
             .map(|q| ExtensionFieldValue::assign(config, ctx, q))
             .collect::<Result<Vec<AssignedExtensionFieldValue<F, D>>, Error>>()?;
+        let lookup_zs = opening_set_values
+            .lookup_zs
+            .iter()
+            .map(|z| ExtensionFieldValue::assign(config, ctx, z))
+            .collect::<Result<Vec<AssignedExtensionFieldValue<F, D>>, Error>>()?;
+        let lookup_zs_next = opening_set_values
+            .lookup_zs_next
+            .iter()
+            .map(|z_next| ExtensionFieldValue::assign(config, ctx, z_next))
+            .collect::<Result<Vec<AssignedExtensionFieldValue<F, D>>, Error>>()?;
         Ok(AssignedOpeningSetValues {
             constants,
             plonk_sigmas,
@@ -104,6 +117,8 @@ impl<F: PrimeField, const D: usize> OpeningSetValues<F, D> {
             plonk_zs_next,
             partial_products,
             quotient_polys,
+            lookup_zs,
+            lookup_zs_next,
         })
     }
 }
@@ -128,12 +143,14 @@ impl<F: PrimeField> MerkleProofValues<F> {
     }
 }
 
-impl<F: PrimeField> From<MerkleProof<GoldilocksField, Bn254PoseidonHash>> for MerkleProofValues<F> {
-    fn from(value: MerkleProof<GoldilocksField, Bn254PoseidonHash>) -> Self {
+impl<F: PrimeField, H: Hasher<GoldilocksField>> From<MerkleProof<GoldilocksField, H>>
+    for MerkleProofValues<F>
+{
+    fn from(value: MerkleProof<GoldilocksField, H>) -> Self {
         let siblings = value
             .siblings
             .iter()
-            .map(|value| HashValues::from(*value))
+            .map(|value| HashValues::from_hash(*value))
             .collect();
         MerkleProofValues { siblings }
     }
@@ -144,10 +161,10 @@ pub struct FriInitialTreeProofValues<F: PrimeField> {
     pub evals_proofs: Vec<(Vec<GoldilocksField>, MerkleProofValues<F>)>,
 }
 
-impl<F: PrimeField> From<FriInitialTreeProof<GoldilocksField, Bn254PoseidonHash>>
+impl<F: PrimeField, H: Hasher<GoldilocksField>> From<FriInitialTreeProof<GoldilocksField, H>>
     for FriInitialTreeProofValues<F>
 {
-    fn from(value: FriInitialTreeProof<GoldilocksField, Bn254PoseidonHash>) -> Self {
+    fn from(value: FriInitialTreeProof<GoldilocksField, H>) -> Self {
         let evals_proofs = value
             .evals_proofs
             .iter()
@@ -196,10 +213,10 @@ impl<F: PrimeField, const D: usize> FriQueryStepValues<F, D> {
     }
 }
 
-impl<F: PrimeField> From<FriQueryStep<GoldilocksField, Bn254PoseidonHash, 2>>
+impl<F: PrimeField, H: Hasher<GoldilocksField>> From<FriQueryStep<GoldilocksField, H, 2>>
     for FriQueryStepValues<F, 2>
 {
-    fn from(value: FriQueryStep<GoldilocksField, Bn254PoseidonHash, 2>) -> Self {
+    fn from(value: FriQueryStep<GoldilocksField, H, 2>) -> Self {
         let evals_values = value
             .evals
             .iter()
@@ -219,10 +236,10 @@ pub struct FriQueryRoundValues<F: PrimeField, const D: usize> {
     pub steps: Vec<FriQueryStepValues<F, D>>,
 }
 
-impl<F: PrimeField> From<FriQueryRound<GoldilocksField, Bn254PoseidonHash, 2>>
+impl<F: PrimeField, H: Hasher<GoldilocksField>> From<FriQueryRound<GoldilocksField, H, 2>>
     for FriQueryRoundValues<F, 2>
 {
-    fn from(value: FriQueryRound<GoldilocksField, Bn254PoseidonHash, 2>) -> Self {
+    fn from(value: FriQueryRound<GoldilocksField, H, 2>) -> Self {
         Self {
             initial_trees_proof: FriInitialTreeProofValues::from(value.initial_trees_proof),
             steps: value
@@ -248,7 +265,10 @@ impl<F: PrimeField, const D: usize> FriQueryRoundValues<F, D> {
             .map(|(values, _)| {
                 values
                     .iter()
-                    .map(|v| goldilocks_chip.assign_value(ctx, Value::known(goldilocks_to_fe(*v))))
+                    .map(|v| {
+                        let value = ctx.witness(goldilocks_to_fe(*v));
+                        goldilocks_chip.assign_value(ctx, value)
+                    })
                     .collect()
             })
             .collect::<Result<Vec<Vec<AssignedValue<F>>>, Error>>()?;
@@ -322,8 +342,10 @@ pub struct FriProofValues<F: PrimeField, const D: usize> {
     pub pow_witness: GoldilocksField,
 }
 
-impl<F: PrimeField> From<FriProof<GoldilocksField, Bn254PoseidonHash, 2>> for FriProofValues<F, 2> {
-    fn from(value: FriProof<GoldilocksField, Bn254PoseidonHash, 2>) -> Self {
+impl<F: PrimeField, H: Hasher<GoldilocksField>> From<FriProof<GoldilocksField, H, 2>>
+    for FriProofValues<F, 2>
+{
+    fn from(value: FriProof<GoldilocksField, H, 2>) -> Self {
         Self {
             commit_phase_merkle_cap_values: value
                 .commit_phase_merkle_caps
@@ -363,10 +385,8 @@ impl<F: PrimeField, const D: usize> FriProofValues<F, D> {
         let final_poly =
             PolynomialCoeffsExtValues::assign(config, ctx, &fri_proof_values.final_poly)?;
         let goldilocks_chip = GoldilocksChip::new(config);
-        let pow_witness = goldilocks_chip.assign_value(
-            ctx,
-            Value::known(goldilocks_to_fe(fri_proof_values.pow_witness)),
-        )?;
+        let pow_witness = ctx.witness(goldilocks_to_fe(fri_proof_values.pow_witness));
+        let pow_witness = goldilocks_chip.assign_value(ctx, pow_witness)?;
         Ok(AssignedFriProofValues {
             commit_phase_merkle_cap_values,
             query_round_proofs,
@@ -386,10 +406,10 @@ pub struct ProofValues<F: PrimeField, const D: usize> {
     pub opening_proof: FriProofValues<F, D>,
 }
 
-impl<F: PrimeField> From<Proof<GoldilocksField, Bn254PoseidonGoldilocksConfig, 2>>
+impl<F: PrimeField, C: GenericConfig<2, F = GoldilocksField>> From<Proof<GoldilocksField, C, 2>>
     for ProofValues<F, 2>
 {
-    fn from(value: Proof<GoldilocksField, Bn254PoseidonGoldilocksConfig, 2>) -> Self {
+    fn from(value: Proof<GoldilocksField, C, 2>) -> Self {
         Self {
             wires_cap: MerkleCapValues::from(value.wires_cap),
             plonk_zs_partial_products_cap: MerkleCapValues::from(
@@ -401,3 +421,91 @@ impl<F: PrimeField> From<Proof<GoldilocksField, Bn254PoseidonGoldilocksConfig, 2
         }
     }
 }
+
+/// Size of the salt appended to the leaves of blinded oracles when `hiding` is set.
+const SALT_SIZE: usize = 4;
+
+impl<F: PrimeField> FriProofValues<F, 2> {
+    /// A zero FRI proof opening `oracles`, with the shape `fri_params` expects.
+    pub fn dummy(fri_params: &FriParams, oracles: &[FriOracleInfo]) -> Self {
+        let cap_height = fri_params.config.cap_height;
+        let cap = || MerkleCapValues(vec![HashValues::default(); 1 << cap_height]);
+        let merkle_proof = |height: usize| MerkleProofValues {
+            siblings: vec![HashValues::default(); height - cap_height],
+        };
+        let ext = |n: usize| vec![ExtensionFieldValue::default(); n];
+
+        let initial_trees_proof = FriInitialTreeProofValues {
+            evals_proofs: oracles
+                .iter()
+                .map(|oracle| {
+                    let salt = if oracle.blinding && fri_params.hiding {
+                        SALT_SIZE
+                    } else {
+                        0
+                    };
+                    (
+                        vec![GoldilocksField::ZERO; oracle.num_polys + salt],
+                        merkle_proof(fri_params.lde_bits()),
+                    )
+                })
+                .collect(),
+        };
+        let mut height = fri_params.lde_bits();
+        let steps = fri_params
+            .reduction_arity_bits
+            .iter()
+            .map(|&arity_bits| {
+                height -= arity_bits;
+                FriQueryStepValues {
+                    evals: ext(1 << arity_bits),
+                    merkle_proof: merkle_proof(height),
+                }
+            })
+            .collect_vec();
+        let query_round = FriQueryRoundValues {
+            initial_trees_proof,
+            steps,
+        };
+
+        Self {
+            commit_phase_merkle_cap_values: vec![cap(); fri_params.reduction_arity_bits.len()],
+            query_round_proofs: vec![query_round; fri_params.config.num_query_rounds],
+            final_poly: PolynomialCoeffsExtValues(ext(fri_params.final_poly_len())),
+            pow_witness: GoldilocksField::ZERO,
+        }
+    }
+}
+
+impl<F: PrimeField> ProofValues<F, 2> {
+    /// A zero proof with the shape `common_data` expects, used to synthesize the circuit
+    /// before any real proof exists.
+    pub fn dummy(common_data: &CommonData<F>) -> Self {
+        let config = &common_data.config;
+        let cap = || MerkleCapValues(vec![HashValues::default(); 1 << config.fri_config.cap_height]);
+        let ext = |n: usize| vec![ExtensionFieldValue::default(); n];
+
+        let openings = OpeningSetValues {
+            constants: ext(common_data.num_constants),
+            plonk_sigmas: ext(config.num_routed_wires),
+            wires: ext(config.num_wires),
+            plonk_zs: ext(config.num_challenges),
+            plonk_zs_next: ext(config.num_challenges),
+            partial_products: ext(config.num_challenges * common_data.num_partial_products),
+            quotient_polys: ext(common_data.num_quotient_polys()),
+            lookup_zs: ext(common_data.num_all_lookup_polys()),
+            lookup_zs_next: ext(common_data.num_all_lookup_polys()),
+        };
+
+        Self {
+            wires_cap: cap(),
+            plonk_zs_partial_products_cap: cap(),
+            quotient_polys_cap: cap(),
+            openings,
+            opening_proof: FriProofValues::dummy(
+                &common_data.fri_params,
+                &common_data.fri_oracles(),
+            ),
+        }
+    }
+}
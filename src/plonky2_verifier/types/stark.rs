@@ -0,0 +1,273 @@
+use halo2_proofs::halo2curves::ff::PrimeField;
+use halo2_proofs::plonk::Error;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::plonk::config::GenericConfig;
+use starky::config::StarkConfig;
+use starky::proof::{StarkOpeningSet, StarkProof};
+
+use crate::plonky2_verifier::chip::goldilocks_chip::GoldilocksChipConfig;
+use crate::plonky2_verifier::chip::stark::air::AirEvaluator;
+use crate::plonky2_verifier::context::RegionCtx;
+use crate::plonky2_verifier::error::VerifierError;
+
+use super::assigned::{
+    AssignedExtensionFieldValue, AssignedStarkOpeningSetValues, AssignedStarkProofValues,
+};
+use super::common_data::FriParams;
+use super::fri::{FriBatchInfo, FriInstanceInfo, FriOracleInfo, FriPolynomialInfo};
+use super::proof::FriProofValues;
+use super::{to_extension_field_values, ExtensionFieldValue, HashValues, MerkleCapValues};
+
+/// The shape of the proofs of a STARK, what `CommonData` is to plonky2 proofs.
+#[derive(Clone, Debug, Default)]
+pub struct StarkCommonData {
+    /// The number of challenge points to generate, `StarkConfig::num_challenges`.
+    pub num_challenges: usize,
+
+    pub fri_params: FriParams,
+
+    pub num_columns: usize,
+
+    pub num_public_inputs: usize,
+
+    pub constraint_degree: usize,
+
+    /// The number of lookup helper columns per challenge, zero without lookups.
+    pub num_lookup_helper_columns: usize,
+}
+
+impl StarkCommonData {
+    /// Shape of the proofs of `air` with a trace of `2^degree_bits` rows, see
+    /// `StarkProof::recover_degree_bits`.
+    pub fn new<F: PrimeField>(
+        air: &impl AirEvaluator<F>,
+        config: &StarkConfig,
+        degree_bits: usize,
+    ) -> Result<Self, VerifierError> {
+        let constraint_degree = air.constraint_degree();
+        let lookups = air.lookups();
+        if !lookups.is_empty() && !(2..=3).contains(&constraint_degree) {
+            return Err(VerifierError::UnsupportedConfig(format!(
+                "lookups in a STARK of constraint degree {constraint_degree}, \
+                 degrees 2 and 3 are supported"
+            )));
+        }
+        Ok(Self {
+            num_challenges: config.num_challenges,
+            fri_params: FriParams::from_plonky2(&config.fri_config.fri_params(degree_bits, false))?,
+            num_columns: air.num_columns(),
+            num_public_inputs: air.num_public_inputs(),
+            constraint_degree,
+            num_lookup_helper_columns: lookups
+                .iter()
+                .map(|lookup| lookup.num_helper_columns(constraint_degree))
+                .sum(),
+        })
+    }
+
+    pub const fn degree_bits(&self) -> usize {
+        self.fri_params.degree_bits
+    }
+
+    pub fn quotient_degree_factor(&self) -> usize {
+        1.max(self.constraint_degree - 1)
+    }
+
+    pub fn num_quotient_polys(&self) -> usize {
+        self.num_challenges * self.quotient_degree_factor()
+    }
+
+    pub fn uses_lookups(&self) -> bool {
+        self.num_lookup_helper_columns != 0
+    }
+
+    /// The number of polynomials committed in the auxiliary oracle, i.e. the lookup helper
+    /// columns of every challenge.
+    pub fn num_auxiliary_polys(&self) -> usize {
+        self.num_challenges * self.num_lookup_helper_columns
+    }
+
+    /// The trace, the auxiliary polynomials if any and the quotient polynomials, none of them
+    /// blinded.
+    pub fn fri_oracles(&self) -> Vec<FriOracleInfo> {
+        let mut oracles = vec![FriOracleInfo {
+            num_polys: self.num_columns,
+            blinding: false,
+        }];
+        if self.uses_lookups() {
+            oracles.push(FriOracleInfo {
+                num_polys: self.num_auxiliary_polys(),
+                blinding: false,
+            });
+        }
+        oracles.push(FriOracleInfo {
+            num_polys: self.num_quotient_polys(),
+            blinding: false,
+        });
+        oracles
+    }
+
+    /// Every polynomial is opened at `zeta`, the trace and the auxiliary polynomials also at
+    /// `g * zeta`.
+    pub fn fri_instance<F: PrimeField>(
+        &self,
+        zeta: &AssignedExtensionFieldValue<F, 2>,
+        zeta_next: &AssignedExtensionFieldValue<F, 2>,
+    ) -> FriInstanceInfo<F, 2> {
+        let oracles = self.fri_oracles();
+        let trace_polys = FriPolynomialInfo::from_range(0, 0..self.num_columns);
+        let auxiliary_polys = if self.uses_lookups() {
+            FriPolynomialInfo::from_range(1, 0..self.num_auxiliary_polys())
+        } else {
+            vec![]
+        };
+        let quotient_polys =
+            FriPolynomialInfo::from_range(oracles.len() - 1, 0..self.num_quotient_polys());
+        let zeta_batch = FriBatchInfo {
+            point: zeta.clone(),
+            polynomials: [trace_polys.clone(), auxiliary_polys.clone(), quotient_polys].concat(),
+        };
+        let zeta_next_batch = FriBatchInfo {
+            point: zeta_next.clone(),
+            polynomials: [trace_polys, auxiliary_polys].concat(),
+        };
+        FriInstanceInfo {
+            oracles,
+            batches: vec![zeta_batch, zeta_next_batch],
+        }
+    }
+}
+
+/// The openings of a starky proof, with empty auxiliary openings for STARKs without lookups.
+/// Cross-table lookups are not supported.
+#[derive(Clone, Debug, Default)]
+pub struct StarkOpeningSetValues<F: PrimeField, const D: usize> {
+    pub local_values: Vec<ExtensionFieldValue<F, D>>,
+    pub next_values: Vec<ExtensionFieldValue<F, D>>,
+    pub auxiliary_polys: Vec<ExtensionFieldValue<F, D>>,
+    pub auxiliary_polys_next: Vec<ExtensionFieldValue<F, D>>,
+    pub quotient_polys: Vec<ExtensionFieldValue<F, D>>,
+}
+
+impl<F: PrimeField> From<StarkOpeningSet<GoldilocksField, 2>> for StarkOpeningSetValues<F, 2> {
+    fn from(value: StarkOpeningSet<GoldilocksField, 2>) -> Self {
+        Self {
+            local_values: to_extension_field_values(value.local_values),
+            next_values: to_extension_field_values(value.next_values),
+            auxiliary_polys: value
+                .auxiliary_polys
+                .map(to_extension_field_values)
+                .unwrap_or_default(),
+            auxiliary_polys_next: value
+                .auxiliary_polys_next
+                .map(to_extension_field_values)
+                .unwrap_or_default(),
+            quotient_polys: value
+                .quotient_polys
+                .map(to_extension_field_values)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl<F: PrimeField, const D: usize> StarkOpeningSetValues<F, D> {
+    pub fn assign(
+        config: &GoldilocksChipConfig<F>,
+        ctx: &mut RegionCtx<'_, F>,
+        opening_set_values: &Self,
+    ) -> Result<AssignedStarkOpeningSetValues<F, D>, Error> {
+        let mut assign = |values: &Vec<ExtensionFieldValue<F, D>>| {
+            values
+                .iter()
+                .map(|v| ExtensionFieldValue::assign(config, ctx, v))
+                .collect::<Result<Vec<AssignedExtensionFieldValue<F, D>>, Error>>()
+        };
+        Ok(AssignedStarkOpeningSetValues {
+            local_values: assign(&opening_set_values.local_values)?,
+            next_values: assign(&opening_set_values.next_values)?,
+            auxiliary_polys: assign(&opening_set_values.auxiliary_polys)?,
+            auxiliary_polys_next: assign(&opening_set_values.auxiliary_polys_next)?,
+            quotient_polys: assign(&opening_set_values.quotient_polys)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StarkProofValues<F: PrimeField, const D: usize> {
+    pub trace_cap: MerkleCapValues<F>,
+    /// Commits to the lookup helper columns, `None` without lookups.
+    pub auxiliary_polys_cap: Option<MerkleCapValues<F>>,
+    pub quotient_polys_cap: MerkleCapValues<F>,
+
+    pub openings: StarkOpeningSetValues<F, D>,
+    pub opening_proof: FriProofValues<F, D>,
+}
+
+impl<F: PrimeField, C: GenericConfig<2, F = GoldilocksField>>
+    From<StarkProof<GoldilocksField, C, 2>> for StarkProofValues<F, 2>
+{
+    fn from(value: StarkProof<GoldilocksField, C, 2>) -> Self {
+        Self {
+            trace_cap: MerkleCapValues::from(value.trace_cap),
+            auxiliary_polys_cap: value.auxiliary_polys_cap.map(MerkleCapValues::from),
+            quotient_polys_cap: value
+                .quotient_polys_cap
+                .map(MerkleCapValues::from)
+                .unwrap_or_default(),
+            openings: StarkOpeningSetValues::from(value.openings),
+            opening_proof: FriProofValues::from(value.opening_proof),
+        }
+    }
+}
+
+impl<F: PrimeField> StarkProofValues<F, 2> {
+    /// A zero proof with the shape `common_data` expects, used to synthesize the circuit
+    /// before any real proof exists.
+    pub fn dummy(common_data: &StarkCommonData) -> Self {
+        let cap_height = common_data.fri_params.config.cap_height;
+        let cap = || MerkleCapValues(vec![HashValues::default(); 1 << cap_height]);
+        let ext = |n: usize| vec![ExtensionFieldValue::default(); n];
+        Self {
+            trace_cap: cap(),
+            auxiliary_polys_cap: common_data.uses_lookups().then(cap),
+            quotient_polys_cap: cap(),
+            openings: StarkOpeningSetValues {
+                local_values: ext(common_data.num_columns),
+                next_values: ext(common_data.num_columns),
+                auxiliary_polys: ext(common_data.num_auxiliary_polys()),
+                auxiliary_polys_next: ext(common_data.num_auxiliary_polys()),
+                quotient_polys: ext(common_data.num_quotient_polys()),
+            },
+            opening_proof: FriProofValues::dummy(
+                &common_data.fri_params,
+                &common_data.fri_oracles(),
+            ),
+        }
+    }
+}
+
+impl<F: PrimeField, const D: usize> StarkProofValues<F, D> {
+    pub fn assign(
+        config: &GoldilocksChipConfig<F>,
+        ctx: &mut RegionCtx<'_, F>,
+        proof_values: &Self,
+    ) -> Result<AssignedStarkProofValues<F, D>, Error> {
+        let trace_cap = MerkleCapValues::assign(config, ctx, &proof_values.trace_cap)?;
+        let auxiliary_polys_cap = proof_values
+            .auxiliary_polys_cap
+            .as_ref()
+            .map(|cap| MerkleCapValues::assign(config, ctx, cap))
+            .transpose()?;
+        let quotient_polys_cap =
+            MerkleCapValues::assign(config, ctx, &proof_values.quotient_polys_cap)?;
+        let openings = StarkOpeningSetValues::assign(config, ctx, &proof_values.openings)?;
+        let opening_proof = FriProofValues::assign(config, ctx, &proof_values.opening_proof)?;
+        Ok(AssignedStarkProofValues {
+            trace_cap,
+            auxiliary_polys_cap,
+            quotient_polys_cap,
+            openings,
+            opening_proof,
+        })
+    }
+}
@@ -1,9 +1,14 @@
-use crate::plonky2_verifier::{
-    bn245_poseidon::plonky2_config::Bn254PoseidonGoldilocksConfig,
-    types::{HashValues, MerkleCapValues},
-};
+use crate::plonky2_verifier::chip::native_chip::utils::goldilocks_to_fe;
+use crate::plonky2_verifier::types::{common_data::CommonData, HashValues, MerkleCapValues};
 use halo2_proofs::halo2curves::ff::PrimeField;
-use plonky2::plonk::circuit_data::VerifierOnlyCircuitData;
+use plonky2::{
+    field::goldilocks_field::GoldilocksField,
+    hash::poseidon::PoseidonHash,
+    plonk::{
+        circuit_data::VerifierOnlyCircuitData,
+        config::{GenericConfig, Hasher},
+    },
+};
 
 #[derive(Clone, Debug, Default)]
 pub struct VerificationKeyValues<F: PrimeField> {
@@ -11,13 +16,42 @@ pub struct VerificationKeyValues<F: PrimeField> {
     pub circuit_digest: HashValues<F>,
 }
 
-impl<F: PrimeField> From<VerifierOnlyCircuitData<Bn254PoseidonGoldilocksConfig, 2>>
+impl<F: PrimeField, C: GenericConfig<2, F = GoldilocksField>> From<VerifierOnlyCircuitData<C, 2>>
     for VerificationKeyValues<F>
 {
-    fn from(value: VerifierOnlyCircuitData<Bn254PoseidonGoldilocksConfig, 2>) -> Self {
+    fn from(value: VerifierOnlyCircuitData<C, 2>) -> Self {
         VerificationKeyValues {
             constants_sigmas_cap: MerkleCapValues::from(value.constants_sigmas_cap),
-            circuit_digest: HashValues::from(value.circuit_digest),
+            circuit_digest: HashValues::from_hash(value.circuit_digest),
+        }
+    }
+}
+
+impl<F: PrimeField> VerificationKeyValues<F> {
+    /// A zero key with the cap size `common_data` expects, enough for the keygen of a verifier
+    /// taking the key as witness.
+    pub fn dummy(common_data: &CommonData<F>) -> Self {
+        let cap_height = common_data.config.fri_config.cap_height;
+        Self {
+            constants_sigmas_cap: MerkleCapValues(vec![HashValues::default(); 1 << cap_height]),
+            circuit_digest: HashValues::default(),
         }
     }
+
+    /// `PoseidonHash` of the constants and sigmas cap followed by the circuit digest, the
+    /// instances a verifier taking the key as witness exposes after the public inputs.
+    pub fn digest(&self) -> Vec<F> {
+        let inputs = self
+            .constants_sigmas_cap
+            .0
+            .iter()
+            .chain([&self.circuit_digest])
+            .flat_map(|hash| hash.elements)
+            .collect::<Vec<_>>();
+        PoseidonHash::hash_no_pad(&inputs)
+            .elements
+            .into_iter()
+            .map(goldilocks_to_fe)
+            .collect()
+    }
 }
@@ -1,27 +1,44 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
 use std::time::Instant;
 
-use anyhow::Result;
-use itertools::Itertools;
-use super::bn245_poseidon::plonky2_config::Bn254PoseidonGoldilocksConfig;
+use super::artifact::SnarkArtifact;
+use super::batch_verifier::BatchVerifier;
+use super::bn245_poseidon::transcript::PoseidonTranscript;
+use super::chip::hasher_chip::AlgebraicHasherChip;
 use super::types::{
     common_data::CommonData, proof::ProofValues, verification_key::VerificationKeyValues,
 };
-use super::verifier_circuit::{ProofTuple, Verifier};
+use super::degree::select_degree;
+use super::error::VerifierError;
+use super::kzg_params::params_for_k;
+use super::verifier_circuit::{
+    pack_public_inputs, ProofTuple, PublicInputsMode, VerificationKeyMode, Verifier,
+};
+use super::verifier_setup::{BatchVerifierSetup, VerifierSetup};
 use crate::plonky2_verifier::chip::native_chip::test_utils::create_proof_checked;
 use crate::plonky2_verifier::chip::native_chip::utils::goldilocks_to_fe;
 use colored::Colorize;
 use halo2_proofs::dev::MockProver;
-use halo2_proofs::halo2curves::bn256::{Bn256, Fr};
-use halo2_proofs::plonk::{keygen_pk, keygen_vk};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::plonk::{
+    create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, VerifyingKey,
+};
 use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2_proofs::transcript::{TranscriptReadBuffer, TranscriptWriterBuffer};
 use halo2_solidity_verifier::compile_solidity;
 use halo2_solidity_verifier::encode_calldata;
 use halo2_solidity_verifier::BatchOpenScheme::Bdfg21;
 use halo2_solidity_verifier::Evm;
+use halo2_solidity_verifier::Keccak256Transcript;
 use halo2_solidity_verifier::SolidityGenerator;
 use log::info;
 use plonky2::field::goldilocks_field::GoldilocksField;
-use plonky2::field::types::PrimeField64;
+use plonky2::plonk::config::GenericConfig;
+use plonky2::plonk::proof::ProofWithPublicInputs;
 
 pub fn report_elapsed(now: Instant) {
     info!(
@@ -32,13 +49,14 @@ pub fn report_elapsed(now: Instant) {
     );
 }
 
-/// Public API for generating Halo2 proof for Plonky2 verifier circuit
-/// feed Plonky2 proof, `VerifierOnlyCircuitData`, `CommonCircuitData`
-/// This runs only mock prover for constraint check
-pub fn verify_inside_snark_mock(
-    degree: u32,
-    proof: ProofTuple<GoldilocksField, Bn254PoseidonGoldilocksConfig, 2>,
-) {
+/// Converts a plonky2 proof tuple into the halo2 verifier circuit and its public instances.
+fn build_verifier_circuit<C: GenericConfig<2, F = GoldilocksField>>(
+    proof: ProofTuple<GoldilocksField, C, 2>,
+    vk_mode: VerificationKeyMode,
+) -> Result<(Verifier<C>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
     let (proof_with_public_inputs, vd, cd) = proof;
     // proof_with_public_inputs -> ProofValues type
     let proof = ProofValues::<Fr, 2>::from(proof_with_public_inputs.proof);
@@ -47,226 +65,703 @@ pub fn verify_inside_snark_mock(
         .iter()
         .map(|e| goldilocks_to_fe(*e))
         .collect::<Vec<Fr>>();
-    // let instances = vec![];
     let vk = VerificationKeyValues::from(vd.clone());
-    let common_data = CommonData::from(cd);
-    let verifier_circuit = Verifier::new(proof, instances.clone(), vk, common_data);
-    let prover = MockProver::run(degree, &verifier_circuit, vec![instances.clone()]).unwrap();
-    prover.assert_satisfied();
+    let common_data = CommonData::try_from(cd)?;
+    let circuit = Verifier::new(proof, instances, vk, common_data).with_vk_mode(vk_mode);
+    let instances = circuit.instances();
+    Ok((circuit, instances))
+}
+
+fn run_mock_prover(
+    degree: u32,
+    circuit: &impl Circuit<Fr>,
+    instances: &[Fr],
+) -> Result<(), VerifierError> {
+    let prover = MockProver::run(degree, circuit, vec![instances.to_vec()])
+        .map_err(VerifierError::Synthesis)?;
+    prover.verify().map_err(VerifierError::from_verify_failures)
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Deploys the verifier and vk contracts on the bundled `Evm` and runs the verifier on `proof`.
+/// Returns the gas cost of the verification call.
+pub fn run_evm_verifier(
+    verifier_solidity: &str,
+    vk_solidity: &str,
+    proof: &[u8],
+    instances: &[Fr],
+) -> Result<u64, VerifierError> {
+    // `compile_solidity` and `Evm` panic on failure, so they are run behind `catch_unwind`.
+    let mut evm = Evm::default();
+    let (verifier_address, vk_address) = panic::catch_unwind(AssertUnwindSafe(|| {
+        let verifier_creation_code = compile_solidity(verifier_solidity);
+        let verifier_address = evm.create(verifier_creation_code);
+        let vk_creation_code = compile_solidity(vk_solidity);
+        let vk_address = evm.create(vk_creation_code);
+        (verifier_address, vk_address)
+    }))
+    .map_err(|e| VerifierError::Solidity(panic_message(e)))?;
+    let calldata = encode_calldata(Some(vk_address.into()), proof, instances);
+    let (gas_cost, output) =
+        panic::catch_unwind(AssertUnwindSafe(|| evm.call(verifier_address, calldata)))
+            .map_err(|e| VerifierError::EvmRejected(panic_message(e)))?;
+    if output != [vec![0; 31], vec![1]].concat() {
+        return Err(VerifierError::EvmRejected(format!(
+            "unexpected output 0x{}",
+            hex::encode(output)
+        )));
+    }
+    Ok(gas_cost)
+}
+
+/// Public API for generating Halo2 proof for Plonky2 verifier circuit
+/// feed Plonky2 proof, `VerifierOnlyCircuitData`, `CommonCircuitData`
+/// This runs only mock prover for constraint check
+/// `degree` overrides the smallest `k` the circuit fits in, see `degree::select_degree`
+/// Proofs of any config whose hasher has an `AlgebraicHasherChip` are accepted, e.g.
+/// `PoseidonGoldilocksConfig` proofs don't need to be wrapped first
+pub fn verify_inside_snark_mock<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proof: ProofTuple<GoldilocksField, C, 2>,
+) -> Result<(), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (verifier_circuit, instances) =
+        build_verifier_circuit(proof, VerificationKeyMode::Constant)?;
+    let degree = select_degree(&verifier_circuit, degree)?;
+    run_mock_prover(degree, &verifier_circuit, &instances)
 }
 
 /// Public API for generating Halo2 proof for Plonky2 verifier circuit
 /// feed Plonky2 proof, `VerifierOnlyCircuitData`, `CommonCircuitData`
 /// This runs real prover and generates valid SNARK proof, generates EVM verifier and runs the verifier
-pub fn verify_inside_snark(
-    degree: u32,
-    proof: ProofTuple<GoldilocksField, Bn254PoseidonGoldilocksConfig, 2>, save: Option<String>
-) {
-    let (proof_with_public_inputs, vd, cd) = proof;
-    let proof = ProofValues::<Fr, 2>::from(proof_with_public_inputs.proof);
-    let instances = proof_with_public_inputs
-        .public_inputs
-        .iter()
-        .map(|e| goldilocks_to_fe(*e))
-        .collect::<Vec<Fr>>();
-    let vk = VerificationKeyValues::from(vd.clone());
-    let common_data = CommonData::from(cd);
+/// `kzg_param` must come from a trusted setup, see `kzg_params::load_params`
+pub fn verify_inside_snark<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proof: ProofTuple<GoldilocksField, C, 2>, kzg_param: &ParamsKZG<Bn256>, save: Option<&Path>
+) -> Result<(), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    verify_inside_snark_solidity(degree, proof, kzg_param, save)?;
+    Ok(())
+}
+
+pub fn verify_inside_snark_solidity<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proof: ProofTuple<GoldilocksField, C, 2>, kzg_param: &ParamsKZG<Bn256>, save: Option<&Path>
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) = build_verifier_circuit(proof, VerificationKeyMode::Constant)?;
+    prove_on_evm(degree, circuit, instances, kzg_param, save)
+}
+
+/// Mock proves `circuit`, generates its Solidity verifier, proves it for real and runs the
+/// verifier on the bundled `Evm`.
+fn prove_on_evm(
+    degree: Option<u32>,
+    circuit: impl Circuit<Fr>,
+    instances: Vec<Fr>,
+    kzg_param: &ParamsKZG<Bn256>,
+    save: Option<&Path>,
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError> {
+    let degree = select_degree(&circuit, degree)?;
+    let kzg_param = &*params_for_k(kzg_param, degree)?;
     // runs mock prover
-    let circuit = Verifier::new(proof, instances.clone(), vk, common_data);
-    let mock_prover = MockProver::run(degree, &circuit, vec![instances.clone()]).unwrap();
-    mock_prover.assert_satisfied();
+    run_mock_prover(degree, &circuit, &instances)?;
     info!("{}", "Mock prover passes".green().bold());
     // generates halo2 solidity verifier
-    let mut rng = rand::thread_rng();
-    let param = ParamsKZG::<Bn256>::setup(degree, &mut rng);
-    let kzg_param:&ParamsKZG<Bn256> = &param;
-    let vk = keygen_vk(kzg_param, &circuit).unwrap();
-    let pk = keygen_pk(kzg_param, vk.clone(), &circuit).unwrap();
+    let keygen_circuit = circuit.without_witnesses();
+    let vk = keygen_vk(kzg_param, &keygen_circuit).map_err(VerifierError::Keygen)?;
+    let pk = keygen_pk(kzg_param, vk.clone(), &keygen_circuit).map_err(VerifierError::Keygen)?;
     let generator = SolidityGenerator::new(kzg_param, &vk, Bdfg21, instances.len());
-    let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
-    let mut evm = Evm::default();
-    let verifier_creation_code = compile_solidity(&verifier_solidity);
-    let verifier_address = evm.create(verifier_creation_code);
-    let vk_creation_code = compile_solidity(&vk_solidity);
-    let vk_address = evm.create(vk_creation_code);
+    let (verifier_solidity, vk_solidity) = generator
+        .render_separately()
+        .map_err(|e| VerifierError::Solidity(e.to_string()))?;
     // generates SNARK proof and runs EVM verifier
     info!("{}", "Starting finalization phase".blue().bold());
     let now = Instant::now();
     // add blindness
-    let proof = create_proof_checked(kzg_param, &pk, circuit.clone(), &instances, &mut rng);
+    let mut rng = rand::thread_rng();
+    let proof = create_proof_checked(kzg_param, &pk, circuit, &instances, &mut rng)?;
     info!("{}", "SNARK proof generated successfully!".green().bold());
     report_elapsed(now);
-    let calldata = encode_calldata(Some(vk_address.into()), &proof, &instances);
-    let (gas_cost, _output) = evm.call(verifier_address, calldata);
+    let gas_cost = run_evm_verifier(&verifier_solidity, &vk_solidity, &proof, &instances)?;
     info!("{}", format!("Gas cost: {}", gas_cost).yellow().bold());
 
     if let Some(save_path) = save {
         // save verifier and vk as solidity smart contract
-        std_ops::save_solidity(format!("{}_verifier.sol", save_path), &verifier_solidity);
-        std_ops::save_solidity(format!("{}_vk.sol", save_path), &vk_solidity);
+        std_ops::save_solidity(
+            std_ops::with_suffix(save_path, "_verifier.sol"),
+            &verifier_solidity,
+        )?;
+        std_ops::save_solidity(std_ops::with_suffix(save_path, "_vk.sol"), &vk_solidity)?;
     }
+
+    Ok((proof, instances))
 }
 
-pub fn verify_inside_snark_solidity(
-    degree: u32,
-    proof: ProofTuple<GoldilocksField, Bn254PoseidonGoldilocksConfig, 2>, kzg_param: &ParamsKZG<Bn256>, save: Option<String>
-) -> Result<(Vec<u8>, Vec<Fr>)> {
-    let (proof_with_public_inputs, vd, cd) = proof;
-    let proof = ProofValues::<Fr, 2>::from(proof_with_public_inputs.proof);
-    let instances = proof_with_public_inputs
-        .public_inputs
-        .iter()
-        .map(|e| goldilocks_to_fe(*e))
-        .collect::<Vec<Fr>>();
-    let vk = VerificationKeyValues::from(vd.clone());
-    let common_data = CommonData::from(cd);
-    // runs mock prover
-    let circuit = Verifier::new(proof, instances.clone(), vk, common_data);
-    let mock_prover = MockProver::run(degree, &circuit, vec![instances.clone()]).unwrap();
-    mock_prover.assert_satisfied();
-    info!("{}", "Mock prover passes".green().bold());
+/// Like `verify_inside_snark_mock`, with the plonky2 verification key as witness, see
+/// `VerificationKeyMode::Witness`
+pub fn verify_universal_inside_snark_mock<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proof: ProofTuple<GoldilocksField, C, 2>,
+) -> Result<(), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) = build_verifier_circuit(proof, VerificationKeyMode::Witness)?;
+    let degree = select_degree(&circuit, degree)?;
+    run_mock_prover(degree, &circuit, &instances)
+}
+
+/// Like `verify_inside_snark_solidity`, with the plonky2 verification key as witness. The
+/// Solidity verifier only depends on the `CommonData` of the proof, the last four instances are
+/// the `VerificationKeyValues::digest` the caller checks against the circuits it accepts.
+pub fn verify_universal_inside_snark_solidity<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proof: ProofTuple<GoldilocksField, C, 2>,
+    kzg_param: &ParamsKZG<Bn256>,
+    save: Option<&Path>,
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) = build_verifier_circuit(proof, VerificationKeyMode::Witness)?;
+    prove_on_evm(degree, circuit, instances, kzg_param, save)
+}
+
+/// Like `build_verifier_circuit`, exposing the public inputs as `public_inputs_mode` says.
+fn build_verifier_circuit_with_mode<C: GenericConfig<2, F = GoldilocksField>>(
+    proof: ProofTuple<GoldilocksField, C, 2>,
+    public_inputs_mode: PublicInputsMode,
+) -> Result<(Verifier<C>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, _) = build_verifier_circuit(proof, VerificationKeyMode::Constant)?;
+    let circuit = circuit.with_public_inputs_mode(public_inputs_mode);
+    let instances = circuit.instances();
+    Ok((circuit, instances))
+}
+
+/// Like `verify_inside_snark_mock`, with the public inputs compressed into two instances, see
+/// `PublicInputsMode::Keccak256`
+pub fn verify_compressed_inside_snark_mock<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proof: ProofTuple<GoldilocksField, C, 2>,
+) -> Result<(), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) =
+        build_verifier_circuit_with_mode(proof, PublicInputsMode::Keccak256)?;
+    let degree = select_degree(&circuit, degree)?;
+    run_mock_prover(degree, &circuit, &instances)
+}
+
+/// Like `verify_inside_snark_solidity`, with the public inputs compressed into two instances.
+/// The caller recomputes them from the public inputs with `public_inputs_digest`.
+pub fn verify_compressed_inside_snark_solidity<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proof: ProofTuple<GoldilocksField, C, 2>,
+    kzg_param: &ParamsKZG<Bn256>,
+    save: Option<&Path>,
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) =
+        build_verifier_circuit_with_mode(proof, PublicInputsMode::Keccak256)?;
+    prove_on_evm(degree, circuit, instances, kzg_param, save)
+}
+
+/// Like `verify_inside_snark_mock`, with the public inputs packed three per instance, see
+/// `PublicInputsMode::Packed`
+pub fn verify_packed_inside_snark_mock<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proof: ProofTuple<GoldilocksField, C, 2>,
+) -> Result<(), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) = build_verifier_circuit_with_mode(proof, PublicInputsMode::Packed)?;
+    let degree = select_degree(&circuit, degree)?;
+    run_mock_prover(degree, &circuit, &instances)
+}
+
+/// Like `verify_inside_snark_solidity`, with the public inputs packed three per instance. The
+/// calldata of the verifier is made with `encode_packed_calldata`.
+pub fn verify_packed_inside_snark_solidity<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proof: ProofTuple<GoldilocksField, C, 2>,
+    kzg_param: &ParamsKZG<Bn256>,
+    save: Option<&Path>,
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) = build_verifier_circuit_with_mode(proof, PublicInputsMode::Packed)?;
+    prove_on_evm(degree, circuit, instances, kzg_param, save)
+}
+
+/// Calldata of the Solidity verifier of a circuit in `PublicInputsMode::Packed`, from the
+/// plonky2 public inputs, see `pack_public_inputs`.
+pub fn encode_packed_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    public_inputs: &[GoldilocksField],
+) -> Vec<u8> {
+    encode_calldata(vk_address, proof, &pack_public_inputs(public_inputs))
+}
+
+/// Builds the `BatchVerifier` of `proofs`, whose public inputs are the ones of every proof in
+/// turn.
+fn build_batch_verifier_circuit<C: GenericConfig<2, F = GoldilocksField>>(
+    proofs: Vec<ProofTuple<GoldilocksField, C, 2>>,
+) -> Result<(BatchVerifier<C>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let verifiers = proofs
+        .into_iter()
+        .map(|proof| Ok(build_verifier_circuit(proof, VerificationKeyMode::Constant)?.0))
+        .collect::<Result<Vec<_>, VerifierError>>()?;
+    let circuit = BatchVerifier::new(verifiers)?;
+    let instances = circuit.instances();
+    Ok((circuit, instances))
+}
+
+/// Like `verify_inside_snark_mock`, for several proofs verified in one circuit
+pub fn verify_batch_inside_snark_mock<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proofs: Vec<ProofTuple<GoldilocksField, C, 2>>,
+) -> Result<(), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) = build_batch_verifier_circuit(proofs)?;
+    let degree = select_degree(&circuit, degree)?;
+    run_mock_prover(degree, &circuit, &instances)
+}
+
+/// Like `verify_inside_snark_solidity`, for several proofs verified in one circuit and checked
+/// with one EVM call. The instances are the public inputs of every proof in turn.
+pub fn verify_batch_inside_snark_solidity<C: GenericConfig<2, F = GoldilocksField>>(
+    degree: Option<u32>,
+    proofs: Vec<ProofTuple<GoldilocksField, C, 2>>,
+    kzg_param: &ParamsKZG<Bn256>,
+    save: Option<&Path>,
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) = build_batch_verifier_circuit(proofs)?;
+    prove_on_evm(degree, circuit, instances, kzg_param, save)
+}
+
+pub fn make_checked_fri2kzg_snark_proof<C: GenericConfig<2, F = GoldilocksField>>(
+    // degree: u32,
+    proof: ProofTuple<GoldilocksField, C, 2>, kzg_param: &ParamsKZG<Bn256>, save: Option<&Path>
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let plonky2_vk = VerificationKeyValues::from(proof.1.clone());
+    let (circuit, instances) = build_verifier_circuit(proof, VerificationKeyMode::Constant)?;
     // generates halo2 solidity verifier
-    let vk = keygen_vk(kzg_param, &circuit).unwrap();
-    let pk = keygen_pk(kzg_param, vk.clone(), &circuit).unwrap();
-    let generator = SolidityGenerator::new(kzg_param, &vk, Bdfg21, instances.len());
-    let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
-    let mut evm = Evm::default();
-    let verifier_creation_code = compile_solidity(&verifier_solidity);
-    let verifier_address = evm.create(verifier_creation_code);
-    let vk_creation_code = compile_solidity(&vk_solidity);
-    let vk_address = evm.create(vk_creation_code);
-    // generates SNARK proof and runs EVM verifier
-    info!("{}", "Starting finalization phase".blue().bold());
+    let keygen_circuit = circuit.without_witnesses();
+    let vk = keygen_vk(kzg_param, &keygen_circuit).map_err(VerifierError::Keygen)?;
+    let pk = keygen_pk(kzg_param, vk.clone(), &keygen_circuit).map_err(VerifierError::Keygen)?;
+    info!("{}", "Starting generate checked proof".blue().bold());
     let now = Instant::now();
     // add blindness
     let mut rng = rand::thread_rng();
-    let proof = create_proof_checked(kzg_param, &pk, circuit.clone(), &instances, &mut rng);
+    let proof = create_proof_checked(kzg_param, &pk, circuit.clone(), &instances, &mut rng)?;
     info!("{}", "SNARK proof generated successfully!".green().bold());
     report_elapsed(now);
-    let calldata = encode_calldata(Some(vk_address.into()), &proof, &instances);
-    let (gas_cost, _output) = evm.call(verifier_address, calldata);
-    info!("{}", format!("Gas cost: {}", gas_cost).yellow().bold());
 
     if let Some(save_path) = save {
-        // save verifier and vk as solidity smart contract
-        std_ops::save_solidity(format!("{}_verifier.sol", save_path), &verifier_solidity);
-        std_ops::save_solidity(format!("{}_vk.sol", save_path), &vk_solidity);
+        // save the proof with the metadata needed to check it later
+        SnarkArtifact::new(pk.get_vk(), &plonky2_vk, &proof, &instances)
+            .save(std_ops::with_suffix(save_path, "_snark_artifact.json"))?;
     }
 
     Ok((proof, instances))
 }
 
-pub fn make_checked_fri2kzg_snark_proof(
-    // degree: u32,
-    proof: ProofTuple<GoldilocksField, Bn254PoseidonGoldilocksConfig, 2>, kzg_param: &ParamsKZG<Bn256>, save: Option<String>
-) -> Result<(Vec<u8>, Vec<Fr>)> {
-    let (proof_with_public_inputs, vd, cd) = proof;
-    let proof = ProofValues::<Fr, 2>::from(proof_with_public_inputs.proof);
-    let instances = proof_with_public_inputs
+/// Verifies a SHPLONK proof with the Keccak transcript used by the Solidity verifier,
+/// i.e. the same check the EVM verifier performs, without paying gas.
+pub fn verify_snark_proof(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Fr],
+) -> Result<(), VerifierError> {
+    let mut transcript = Keccak256Transcript::new(proof);
+    verify_proof::<_, VerifierSHPLONK<_>, _, _, SingleStrategy<_>>(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        &[&[instances]],
+        &mut transcript,
+    )
+    .map_err(VerifierError::ProofRejected)
+}
+
+/// Verifier circuit of `proof` for the plonky2 circuit `setup` was built for, with its instances.
+fn build_setup_circuit<C: GenericConfig<2, F = GoldilocksField>>(
+    setup: &VerifierSetup<C>,
+    proof: ProofWithPublicInputs<GoldilocksField, C, 2>,
+) -> Result<(Verifier<C>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    if proof.public_inputs.len() != setup.common_data.num_public_inputs {
+        return Err(VerifierError::ShapeMismatch(format!(
+            "expected {} public inputs, got {}",
+            setup.common_data.num_public_inputs,
+            proof.public_inputs.len()
+        )));
+    }
+    let public_inputs = proof
         .public_inputs
         .iter()
         .map(|e| goldilocks_to_fe(*e))
         .collect::<Vec<Fr>>();
-    let vk = VerificationKeyValues::from(vd.clone());
-    let common_data = CommonData::from(cd);
-    // runs mock prover
-    let circuit = Verifier::new(proof, instances.clone(), vk, common_data);
-    // let mock_prover = MockProver::run(degree, &circuit, vec![instances.clone()]).unwrap();
-    // mock_prover.assert_satisfied();
-    // info!("{}", "Mock prover passes".green().bold());
-    // generates halo2 solidity verifier
-    let vk = keygen_vk(kzg_param, &circuit).unwrap();
-    let pk = keygen_pk(kzg_param, vk.clone(), &circuit).unwrap();
-    info!("{}", "Starting generate checked proof".blue().bold());
+    let circuit = Verifier::<C>::new(
+        ProofValues::<Fr, 2>::from(proof.proof),
+        public_inputs,
+        setup.vk.clone(),
+        setup.common_data.clone(),
+    )
+    .with_vk_mode(setup.vk_mode)
+    .with_public_inputs_mode(setup.public_inputs_mode)
+    .with_accumulator(setup.accumulator);
+    let instances = circuit.instances();
+    Ok((circuit, instances))
+}
+
+/// Generates a SNARK proof for `proof` with keys from `setup`, skipping keygen.
+/// `proof` must be a proof of the plonky2 circuit `setup` was built for.
+pub fn prove<C: GenericConfig<2, F = GoldilocksField>>(
+    setup: &VerifierSetup<C>,
+    proof: ProofWithPublicInputs<GoldilocksField, C, 2>,
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) = build_setup_circuit(setup, proof)?;
     let now = Instant::now();
-    // add blindness
     let mut rng = rand::thread_rng();
-    let proof = create_proof_checked(kzg_param, &pk, circuit.clone(), &instances, &mut rng);
+    let proof = create_proof_checked(&setup.params, &setup.pk, circuit, &instances, &mut rng)?;
     info!("{}", "SNARK proof generated successfully!".green().bold());
     report_elapsed(now);
+    Ok((proof, instances))
+}
 
-    if let Some(save_path) = save {
-        // save verifier and vk as solidity smart contract
-        std_ops::save_snark_proof(format!("{}_snark_proof.json", save_path), &proof);
-        let u64_instances = proof_with_public_inputs.public_inputs.iter().map(|ins| {
-            ins.to_canonical_u64().to_string()
-        }).collect_vec();
-        std_ops::save_snark_instances(format!("{}_snark_instances.json", save_path), &u64_instances);
-    }
+/// Like `prove`, but with a `PoseidonTranscript` so that the proof can be verified by a halo2
+/// aggregation circuit instead of the Solidity verifier. The verifier circuit checks FRI and
+/// defers no pairing, so by default the proof is a leaf of the aggregation and its `instances`
+/// hold no accumulator. If `setup` was generated for a circuit `with_accumulator`, e.g. with
+/// `KzgAccumulator::neutral`, the first instances are the accumulator limbs an aggregation
+/// circuit reads from the proofs of another aggregation circuit.
+pub fn prove_for_aggregation<C: GenericConfig<2, F = GoldilocksField>>(
+    setup: &VerifierSetup<C>,
+    proof: ProofWithPublicInputs<GoldilocksField, C, 2>,
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let (circuit, instances) = build_setup_circuit(setup, proof)?;
+    let now = Instant::now();
+    let proof = {
+        let mut transcript = PoseidonTranscript::new(Vec::new());
+        create_proof::<_, ProverSHPLONK<_>, _, _, _, _>(
+            &setup.params,
+            &setup.pk,
+            &[circuit],
+            &[&[instances.as_slice()]],
+            rand::thread_rng(),
+            &mut transcript,
+        )
+        .map_err(VerifierError::Proving)?;
+        transcript.finalize()
+    };
+    verify_snark_proof_for_aggregation(&setup.params, setup.halo2_vk(), &proof, &instances)?;
+    info!("{}", "SNARK proof generated successfully!".green().bold());
+    report_elapsed(now);
+    Ok((proof, instances))
+}
+
+/// Verifies a SHPLONK proof made by `prove_for_aggregation`, i.e. the check the aggregation
+/// circuit replays with its accumulator.
+pub fn verify_snark_proof_for_aggregation(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Fr],
+) -> Result<(), VerifierError> {
+    let mut transcript = PoseidonTranscript::new(proof);
+    verify_proof::<_, VerifierSHPLONK<_>, _, _, SingleStrategy<_>>(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        &[&[instances]],
+        &mut transcript,
+    )
+    .map_err(VerifierError::ProofRejected)
+}
 
+/// Generates one SNARK proof for `proofs` with keys from `setup`, skipping keygen.
+/// `proofs[i]` must be a proof of the plonky2 circuit of the `i`-th slot of `setup`.
+pub fn prove_batch<C: GenericConfig<2, F = GoldilocksField>>(
+    setup: &BatchVerifierSetup<C>,
+    proofs: Vec<ProofWithPublicInputs<GoldilocksField, C, 2>>,
+) -> Result<(Vec<u8>, Vec<Fr>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    if proofs.len() != setup.slots.len() {
+        return Err(VerifierError::ShapeMismatch(format!(
+            "expected {} proofs, got {}",
+            setup.slots.len(),
+            proofs.len()
+        )));
+    }
+    let mut verifiers = vec![];
+    for (i, (proof, (vk, common_data))) in proofs.into_iter().zip(setup.slots.iter()).enumerate() {
+        if proof.public_inputs.len() != common_data.num_public_inputs {
+            return Err(VerifierError::ShapeMismatch(format!(
+                "expected {} public inputs in proof {i}, got {}",
+                common_data.num_public_inputs,
+                proof.public_inputs.len()
+            )));
+        }
+        let instances = proof
+            .public_inputs
+            .iter()
+            .map(|e| goldilocks_to_fe(*e))
+            .collect::<Vec<Fr>>();
+        verifiers.push(Verifier::<C>::new(
+            ProofValues::<Fr, 2>::from(proof.proof),
+            instances,
+            vk.clone(),
+            common_data.clone(),
+        ));
+    }
+    let circuit = BatchVerifier::new(verifiers)?.with_public_inputs_mode(setup.public_inputs_mode);
+    let instances = circuit.instances();
+    let now = Instant::now();
+    let mut rng = rand::thread_rng();
+    let proof = create_proof_checked(&setup.params, &setup.pk, circuit, &instances, &mut rng)?;
+    info!("{}", "SNARK proof generated successfully!".green().bold());
+    report_elapsed(now);
     Ok((proof, instances))
 }
 
 pub mod std_ops {
-    use std::io::Read;
+    use std::io::{self, Read};
     pub(crate) use std::{
         fs::{create_dir_all, File, self},
         io::Write
     };
 
-    use anyhow::Result;
+    use std::path::{Path, PathBuf};
 
-    const DIR_GENERATED: &str = "./generated-sc";
-    const DIR_SNARKPROOF: &str = "./snark-proof";
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Field64};
+    use plonky2::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
+    use plonky2::plonk::proof::ProofWithPublicInputs;
+    use plonky2::util::serialization::DefaultGateSerializer;
 
-    pub(crate) fn save_solidity(name: impl AsRef<str>, solidity: &str) {
-        create_dir_all(DIR_GENERATED).unwrap();
-        File::create(format!("{DIR_GENERATED}/{}", name.as_ref()))
-            .unwrap()
-            .write_all(solidity.as_bytes())
-            .unwrap();
+    use crate::plonky2_verifier::bn245_poseidon::plonky2_config::Bn254PoseidonGoldilocksConfig;
+    use crate::plonky2_verifier::chip::native_chip::utils::goldilocks_to_fe;
+    use crate::plonky2_verifier::error::VerifierError;
+
+    /// Default directory for generated Solidity contracts.
+    pub const DIR_GENERATED: &str = "./generated-sc";
+    /// Default directory for SNARK proofs and instances.
+    pub const DIR_SNARKPROOF: &str = "./snark-proof";
+
+    /// Appends `suffix` to the last component of `prefix`, e.g. `out/demo` + `_vk.sol`.
+    pub fn with_suffix(prefix: impl AsRef<Path>, suffix: &str) -> PathBuf {
+        let mut path = prefix.as_ref().as_os_str().to_owned();
+        path.push(suffix);
+        PathBuf::from(path)
     }
 
-    pub fn load_solidity(name: impl AsRef<str>) -> Result<String> {
-        let mut f = File::open(format!("{}/{}", DIR_GENERATED, name.as_ref()))?;
+    fn create_parent_dir(path: &Path) -> Result<(), VerifierError> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        Ok(())
+    }
+
+    pub fn save_solidity(path: impl AsRef<Path>, solidity: &str) -> Result<(), VerifierError> {
+        let path = path.as_ref();
+        create_parent_dir(path)?;
+        File::create(path)?.write_all(solidity.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn load_solidity(path: impl AsRef<Path>) -> Result<String, VerifierError> {
+        let mut f = File::open(path)?;
         let mut buffer = String::new();
         f.read_to_string(&mut buffer)?;
         Ok(buffer)
     }
 
-    pub(crate) fn save_snark_proof(name: impl AsRef<str>, proof: &Vec<u8>) {
-        let proof_json = serde_json::to_string(&proof).unwrap();
-        create_dir_all(DIR_SNARKPROOF).unwrap();
-        fs::write(format!("{}/{}", DIR_SNARKPROOF, name.as_ref()), proof_json).expect("Unable to write `snark proof` to file");
+    pub fn save_snark_proof(path: impl AsRef<Path>, proof: &Vec<u8>) -> Result<(), VerifierError> {
+        let path = path.as_ref();
+        let proof_json = serde_json::to_string(&proof)?;
+        create_parent_dir(path)?;
+        fs::write(path, proof_json)?;
+        Ok(())
+    }
+
+    pub fn load_snark_proof(path: impl AsRef<Path>) -> Result<Vec<u8>, VerifierError> {
+        let proof_json = fs::read(path)?;
+        Ok(serde_json::from_slice(&proof_json)?)
+    }
+
+    pub fn save_snark_instances(
+        path: impl AsRef<Path>,
+        instances: &Vec<String>,
+    ) -> Result<(), VerifierError> {
+        let path = path.as_ref();
+        let instances_json = serde_json::to_string(instances)?;
+        create_parent_dir(path)?;
+        fs::write(path, instances_json)?;
+        Ok(())
     }
 
-    pub fn load_snark_proof(name: impl AsRef<str>) -> Result<Vec<u8>, serde_json::Error> {
-        let proof_json = fs::read(format!("{}/{}", DIR_SNARKPROOF, name.as_ref())).expect(&format!("load proof {} error", name.as_ref()));
-        serde_json::from_slice(&proof_json)
+    pub fn load_snark_instances(path: impl AsRef<Path>) -> Result<Vec<String>, VerifierError> {
+        let instances_json = fs::read(path)?;
+        Ok(serde_json::from_slice(&instances_json)?)
     }
 
-    pub(crate) fn save_snark_instances(name: impl AsRef<str>, instances: &Vec<String>) {
-        let instances_json = serde_json::to_string(instances).expect(&format!("load instances {} error", name.as_ref()));
-        create_dir_all(DIR_SNARKPROOF).unwrap();
-        fs::write(format!("{}/{}", DIR_SNARKPROOF, name.as_ref()), instances_json).expect("Unable to write `snark instances` to file");
+    /// Loads instances saved by `save_snark_instances` as field elements for `verify_snark_proof`.
+    /// Every instance must be a canonical Goldilocks element.
+    pub fn load_snark_instances_as_fr(path: impl AsRef<Path>) -> Result<Vec<Fr>, VerifierError> {
+        load_snark_instances(path)?
+            .iter()
+            .map(|instance| {
+                let instance = instance
+                    .parse::<u64>()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                if instance >= GoldilocksField::ORDER {
+                    let reason = format!("instance {instance} is not a Goldilocks element");
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, reason).into());
+                }
+                Ok(goldilocks_to_fe(GoldilocksField::from_canonical_u64(
+                    instance,
+                )))
+            })
+            .collect()
     }
 
-    pub fn load_snark_instances(name: impl AsRef<str>) -> Result<Vec<String>, serde_json::Error> {
-        let instances_json = fs::read(format!("{}/{}", DIR_SNARKPROOF, name.as_ref())).unwrap();
-        serde_json::from_slice(&instances_json)
+    pub fn load_common_circuit_data(
+        path: impl AsRef<Path>,
+    ) -> Result<CommonCircuitData<GoldilocksField, 2>, VerifierError> {
+        let bytes = fs::read(path)?;
+        CommonCircuitData::from_bytes(bytes, &DefaultGateSerializer).map_err(invalid_data)
+    }
+
+    pub fn load_verifier_only_circuit_data(
+        path: impl AsRef<Path>,
+    ) -> Result<VerifierOnlyCircuitData<Bn254PoseidonGoldilocksConfig, 2>, VerifierError> {
+        let bytes = fs::read(path)?;
+        VerifierOnlyCircuitData::from_bytes(bytes).map_err(invalid_data)
+    }
+
+    pub fn load_plonky2_proof(
+        path: impl AsRef<Path>,
+        common_data: &CommonCircuitData<GoldilocksField, 2>,
+    ) -> Result<
+        ProofWithPublicInputs<GoldilocksField, Bn254PoseidonGoldilocksConfig, 2>,
+        VerifierError,
+    > {
+        let bytes = fs::read(path)?;
+        ProofWithPublicInputs::from_bytes(bytes, common_data).map_err(invalid_data)
+    }
+
+    fn invalid_data(e: impl std::fmt::Debug) -> VerifierError {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}")).into()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use halo2_proofs::halo2curves::bn256::{Bn256, Fr};
+    use halo2_proofs::halo2curves::ff::Field as _;
+    use halo2_proofs::halo2curves::pairing::Engine;
+    use halo2_proofs::plonk::{keygen_pk, keygen_vk, Circuit};
+    use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+    use halo2_solidity_verifier::encode_calldata;
     use log::{info, LevelFilter};
 
-    use super::{verify_inside_snark, verify_inside_snark_mock};
+    use super::{
+        build_batch_verifier_circuit, build_verifier_circuit, build_verifier_circuit_with_mode,
+        create_proof_checked, encode_packed_calldata, prove, prove_batch, prove_for_aggregation,
+        run_mock_prover, std_ops, verify_batch_inside_snark_mock,
+        verify_compressed_inside_snark_mock, verify_inside_snark, verify_inside_snark_mock,
+        verify_packed_inside_snark_mock, verify_snark_proof, verify_snark_proof_for_aggregation,
+        verify_universal_inside_snark_mock,
+    };
     use crate::plonky2_verifier::{
+        batch_verifier::BatchVerifier,
         bn245_poseidon::plonky2_config::{
-            standard_inner_stark_verifier_config, standard_stark_verifier_config,
+            high_arity_stark_verifier_config, standard_inner_stark_verifier_config,
+            standard_stark_verifier_config, Bn254Poseidon2GoldilocksConfig,
             Bn254PoseidonGoldilocksConfig,
         },
-        verifier_circuit::ProofTuple,
+        chip::native_chip::utils::goldilocks_to_fe,
+        degree::{row_budget, select_degree},
+        error::VerifierError,
+        profile::{cost_profile, costs_by_name},
+        types::{
+            common_data::CommonData, proof::ProofValues, verification_key::VerificationKeyValues,
+        },
+        verifier_circuit::{
+            pack_public_inputs, public_inputs_digest, unpack_public_inputs, KzgAccumulator,
+            ProofTuple, PublicInputsMode, VerificationKeyMode, Verifier,
+        },
+        verifier_setup::{BatchVerifierSetup, VerifierSetup},
     };
     use plonky2::{
-        field::{goldilocks_field::GoldilocksField, types::Field},
+        field::{
+            goldilocks_field::GoldilocksField,
+            types::{Field, Field64, PrimeField64},
+        },
+        fri::reduction_strategies::FriReductionStrategy,
         hash::{
             hashing::hash_n_to_hash_no_pad,
             poseidon::{PoseidonHash, PoseidonPermutation},
         },
-        iop::witness::{PartialWitness, WitnessWrite},
-        plonk::{circuit_builder::CircuitBuilder, config::PoseidonGoldilocksConfig},
+        iop::{
+            target::Target,
+            witness::{PartialWitness, WitnessWrite},
+        },
+        plonk::{
+            circuit_builder::CircuitBuilder,
+            config::{KeccakGoldilocksConfig, PoseidonGoldilocksConfig},
+        },
     };
 
     type F = GoldilocksField;
@@ -308,10 +803,475 @@ mod tests {
         proof
     }
 
+    /// A circuit looking inputs up in two tables, with the table rows laid out by plonky2.
+    fn generate_lookup_proof_tuple() -> ProofTuple<F, Bn254PoseidonGoldilocksConfig, D> {
+        let mut builder = CircuitBuilder::<F, D>::new(standard_stark_verifier_config());
+        let squares = Arc::new((0..256u16).map(|i| (i, i * i)).collect::<Vec<_>>());
+        let flips = Arc::new((0..256u16).map(|i| (i, i ^ 0x55)).collect::<Vec<_>>());
+        let squares_index = builder.add_lookup_table_from_pairs(squares);
+        let flips_index = builder.add_lookup_table_from_pairs(flips);
+        let inputs = (0..8)
+            .map(|_| builder.add_virtual_target())
+            .collect::<Vec<_>>();
+        for &input in inputs.iter() {
+            let square = builder.add_lookup_from_index(input, squares_index);
+            let flipped = builder.add_lookup_from_index(input, flips_index);
+            builder.register_public_input(square);
+            builder.register_public_input(flipped);
+        }
+        let data = builder.build::<Bn254PoseidonGoldilocksConfig>();
+
+        let mut pw = PartialWitness::new();
+        for (i, &input) in inputs.iter().enumerate() {
+            pw.set_target(input, F::from_canonical_usize(i * 37 % 256));
+        }
+        let proof = data.prove(pw).unwrap();
+        (proof, data.verifier_only, data.common)
+    }
+
+    /// A circuit of repeated squarings proved with the outer config and `reduction_strategy`.
+    fn generate_fri_proof_tuple(
+        reduction_strategy: FriReductionStrategy,
+    ) -> ProofTuple<F, Bn254PoseidonGoldilocksConfig, D> {
+        let mut config = standard_stark_verifier_config();
+        config.fri_config.reduction_strategy = reduction_strategy;
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let input = builder.add_virtual_target();
+        let mut output = input;
+        for _ in 0..1 << 12 {
+            output = builder.square(output);
+        }
+        builder.register_public_input(output);
+        let data = builder.build::<Bn254PoseidonGoldilocksConfig>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(input, F::from_canonical_u64(3));
+        let proof = data.prove(pw).unwrap();
+        (proof, data.verifier_only, data.common)
+    }
+
+    /// Repeated squarings scaled by the constant `scale`, so that circuits of different scales
+    /// share their `CommonData` but not their verification key.
+    fn generate_scaled_proof_tuple(scale: u64) -> ProofTuple<F, Bn254PoseidonGoldilocksConfig, D> {
+        let mut builder = CircuitBuilder::<F, D>::new(standard_stark_verifier_config());
+        let input = builder.add_virtual_target();
+        let mut output = input;
+        for _ in 0..1 << 12 {
+            output = builder.square(output);
+        }
+        let output = builder.mul_const(F::from_canonical_u64(scale), output);
+        builder.register_public_input(output);
+        let data = builder.build::<Bn254PoseidonGoldilocksConfig>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(input, F::from_canonical_u64(3));
+        let proof = data.prove(pw).unwrap();
+        (proof, data.verifier_only, data.common)
+    }
+
+    #[test]
+    fn test_fri_reduction_strategies_halo2_mock() {
+        for reduction_strategy in [
+            FriReductionStrategy::ConstantArityBits(2, 5),
+            FriReductionStrategy::ConstantArityBits(3, 2),
+            high_arity_stark_verifier_config()
+                .fri_config
+                .reduction_strategy,
+            FriReductionStrategy::Fixed(vec![2, 1, 3]),
+            FriReductionStrategy::MinSize(None),
+        ] {
+            let proof = generate_fri_proof_tuple(reduction_strategy);
+            verify_inside_snark_mock(None, proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_unsupported_fri_arity() {
+        let (_, _, cd) = generate_fri_proof_tuple(FriReductionStrategy::Fixed(vec![5]));
+        assert!(matches!(
+            CommonData::<Fr>::try_from(cd),
+            Err(VerifierError::UnsupportedConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_lookup_halo2_mock() {
+        let proof = generate_lookup_proof_tuple();
+        assert_ne!(proof.2.num_lookup_polys, 0);
+        verify_inside_snark_mock(None, proof).unwrap();
+    }
+
+    /// Verifies proofs of two different circuits in one halo2 circuit.
+    #[test]
+    fn test_batch_halo2_mock() {
+        let proofs = vec![
+            generate_fri_proof_tuple(FriReductionStrategy::ConstantArityBits(4, 5)),
+            generate_lookup_proof_tuple(),
+        ];
+        let num_public_inputs = proofs
+            .iter()
+            .map(|(proof, _, _)| proof.public_inputs.len())
+            .sum::<usize>();
+        let (circuit, instances) = build_batch_verifier_circuit(proofs.clone()).unwrap();
+        assert_eq!(instances.len(), num_public_inputs);
+        let degree = select_degree(&circuit, None).unwrap();
+
+        // the public inputs of the second proof come after the ones of the first proof
+        let mut wrong_instances = instances.clone();
+        wrong_instances.swap(0, 1);
+        assert!(matches!(
+            run_mock_prover(degree, &circuit, &wrong_instances),
+            Err(VerifierError::ConstraintFailure { .. })
+        ));
+
+        verify_batch_inside_snark_mock(None, proofs).unwrap();
+
+        assert!(matches!(
+            verify_batch_inside_snark_mock::<Bn254PoseidonGoldilocksConfig>(None, vec![]),
+            Err(VerifierError::ShapeMismatch(_))
+        ));
+    }
+
+    /// The 16 public inputs of the lookup circuit are exposed as their Keccak-256 digest.
+    #[test]
+    fn test_compressed_public_inputs_halo2_mock() {
+        let proof = generate_lookup_proof_tuple();
+        let public_inputs = proof
+            .0
+            .public_inputs
+            .iter()
+            .map(|e| e.to_canonical_u64())
+            .collect::<Vec<_>>();
+        let (circuit, instances) =
+            build_verifier_circuit_with_mode(proof.clone(), PublicInputsMode::Keccak256).unwrap();
+        assert_eq!(instances, public_inputs_digest(&public_inputs));
+        let degree = select_degree(&circuit, None).unwrap();
+
+        let mut wrong_public_inputs = public_inputs.clone();
+        wrong_public_inputs.swap(0, 1);
+        assert!(matches!(
+            run_mock_prover(
+                degree,
+                &circuit,
+                &public_inputs_digest(&wrong_public_inputs)
+            ),
+            Err(VerifierError::ConstraintFailure { .. })
+        ));
+
+        verify_compressed_inside_snark_mock(None, proof).unwrap();
+    }
+
+    /// The 16 public inputs of the lookup circuit are packed into 6 instances.
+    #[test]
+    fn test_packed_public_inputs_halo2_mock() {
+        let proof = generate_lookup_proof_tuple();
+        let public_inputs = proof.0.public_inputs.clone();
+        let (circuit, instances) =
+            build_verifier_circuit_with_mode(proof.clone(), PublicInputsMode::Packed).unwrap();
+        assert_eq!(instances.len(), 6);
+        assert_eq!(instances, pack_public_inputs(&public_inputs));
+        assert_eq!(
+            unpack_public_inputs(&instances, public_inputs.len()),
+            public_inputs
+        );
+        let unpacked_instances = public_inputs
+            .iter()
+            .map(|e| goldilocks_to_fe(*e))
+            .collect::<Vec<Fr>>();
+        assert_eq!(
+            encode_packed_calldata(None, &[], &public_inputs).len() + 32 * (16 - 6),
+            encode_calldata(None, &[], &unpacked_instances).len()
+        );
+        let degree = select_degree(&circuit, None).unwrap();
+
+        let mut wrong_instances = instances.clone();
+        wrong_instances[5] += Fr::ONE;
+        assert!(matches!(
+            run_mock_prover(degree, &circuit, &wrong_instances),
+            Err(VerifierError::ConstraintFailure { .. })
+        ));
+
+        verify_packed_inside_snark_mock(None, proof).unwrap();
+    }
+
+    /// Keys for a fixed list of circuits with different gates, one proof per slot.
+    #[test]
+    fn test_batch_setup_heterogeneous() {
+        let proofs = vec![
+            generate_fri_proof_tuple(FriReductionStrategy::ConstantArityBits(4, 5)),
+            generate_lookup_proof_tuple(),
+            generate_scaled_proof_tuple(5),
+            generate_scaled_proof_tuple(7),
+        ];
+        let slots = proofs
+            .iter()
+            .map(|(_, vd, cd)| {
+                (
+                    VerificationKeyValues::from(vd.clone()),
+                    CommonData::try_from(cd.clone()).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(slots[0].1.num_lookup_polys, 0);
+        assert_ne!(slots[1].1.num_lookup_polys, 0);
+        let circuit =
+            BatchVerifier::<Bn254PoseidonGoldilocksConfig>::from_common_data(slots.clone())
+                .unwrap();
+        let degree = select_degree(&circuit, None).unwrap();
+        let kzg_param = ParamsKZG::<Bn256>::setup(degree, rand::thread_rng());
+        let setup = BatchVerifierSetup::<Bn254PoseidonGoldilocksConfig>::new(
+            kzg_param,
+            slots,
+            PublicInputsMode::Packed,
+        )
+        .unwrap();
+
+        let mut bytes = vec![];
+        setup.write(&mut bytes).unwrap();
+        // the keys are bound to the `CommonData` of every slot
+        let mut other_slots = setup.slots.clone();
+        other_slots[1].1 = other_slots[0].1.clone();
+        assert!(matches!(
+            BatchVerifierSetup::<Bn254PoseidonGoldilocksConfig>::read(
+                &mut bytes.as_slice(),
+                setup.params.clone(),
+                other_slots,
+            ),
+            Err(VerifierError::ShapeMismatch(_))
+        ));
+        let setup =
+            BatchVerifierSetup::read(&mut bytes.as_slice(), setup.params, setup.slots).unwrap();
+        assert_eq!(setup.public_inputs_mode, PublicInputsMode::Packed);
+        let proofs = proofs
+            .into_iter()
+            .map(|(proof, _, _)| proof)
+            .collect::<Vec<_>>();
+        let (snark_proof, instances) = prove_batch(&setup, proofs.clone()).unwrap();
+        assert_eq!(instances.len(), setup.num_instances());
+        let packed = proofs
+            .iter()
+            .flat_map(|proof| pack_public_inputs(&proof.public_inputs))
+            .collect::<Vec<_>>();
+        assert_eq!(instances, packed);
+        verify_snark_proof(&setup.params, setup.halo2_vk(), &snark_proof, &instances).unwrap();
+
+        let mut swapped = proofs.clone();
+        swapped.swap(0, 1);
+        assert!(matches!(
+            prove_batch(&setup, swapped),
+            Err(VerifierError::ShapeMismatch(_))
+        ));
+        // same number of public inputs, but proofs of the circuit of the other slot
+        let mut swapped = proofs;
+        swapped.swap(2, 3);
+        assert!(matches!(
+            prove_batch(&setup, swapped),
+            Err(VerifierError::Proving(_) | VerifierError::ProofRejected(_))
+        ));
+    }
+
+    /// One set of halo2 keys verifies proofs of two circuits of the same `CommonData`, told
+    /// apart by the verification key digest they expose.
+    #[test]
+    fn test_universal_verifier_halo2_mock() {
+        let proofs = [
+            generate_scaled_proof_tuple(5),
+            generate_scaled_proof_tuple(7),
+        ];
+        let common_data = CommonData::<Fr>::try_from(proofs[0].2.clone()).unwrap();
+        let vks = proofs
+            .iter()
+            .map(|(_, vd, _)| VerificationKeyValues::<Fr>::from(vd.clone()))
+            .collect::<Vec<_>>();
+        assert_ne!(vks[0].digest(), vks[1].digest());
+
+        let keygen_circuit = |vk: VerificationKeyValues<Fr>| {
+            Verifier::<Bn254PoseidonGoldilocksConfig>::from_common_data(vk, common_data.clone())
+                .with_vk_mode(VerificationKeyMode::Witness)
+        };
+        let dummy_circuit = keygen_circuit(VerificationKeyValues::dummy(&common_data));
+        let degree = select_degree(&dummy_circuit, None).unwrap();
+        let kzg_param = ParamsKZG::<Bn256>::setup(degree, rand::thread_rng());
+        let dummy_halo2_vk = keygen_vk(&kzg_param, &dummy_circuit).unwrap();
+        for vk in vks.iter() {
+            let halo2_vk = keygen_vk(&kzg_param, &keygen_circuit(vk.clone())).unwrap();
+            assert_eq!(halo2_vk.transcript_repr(), dummy_halo2_vk.transcript_repr());
+        }
+
+        for (proof, vk) in proofs.iter().zip(vks.iter()) {
+            let (circuit, instances) =
+                build_verifier_circuit(proof.clone(), VerificationKeyMode::Witness).unwrap();
+            let digest_start = proof.0.public_inputs.len();
+            assert_eq!(instances[digest_start..], vk.digest());
+            verify_universal_inside_snark_mock(Some(degree), proof.clone()).unwrap();
+
+            // the exposed digest is bound to the verification key the proof verifies against
+            let mut wrong_instances = instances.clone();
+            wrong_instances[digest_start] += Fr::ONE;
+            assert!(matches!(
+                run_mock_prover(degree, &circuit, &wrong_instances),
+                Err(VerifierError::ConstraintFailure { .. })
+            ));
+        }
+    }
+
+    /// Verifies a `PoseidonGoldilocksConfig` proof directly, i.e. without the wrapping proof that
+    /// switches to `Bn254PoseidonGoldilocksConfig`, and compares the cost of both circuits.
+    #[test]
+    fn test_poseidon_goldilocks_halo2_mock() {
+        let hash_const =
+            hash_n_to_hash_no_pad::<F, PoseidonPermutation<F>>(&[F::from_canonical_u64(42)]);
+        let mut builder = CircuitBuilder::<F, D>::new(standard_stark_verifier_config());
+        let target = builder.add_virtual_target();
+        let expected_hash = builder.constant_hash(hash_const);
+        let hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(vec![target]);
+        builder.connect_hashes(hash, expected_hash);
+        builder.register_public_inputs(&expected_hash.elements);
+        let data = builder.build::<PoseidonGoldilocksConfig>();
+        let mut pw = PartialWitness::new();
+        pw.set_target(target, F::from_canonical_usize(42));
+        let proof = data.prove(pw).unwrap();
+        let proof = (proof, data.verifier_only, data.common);
+
+        let goldilocks_circuit = Verifier::<PoseidonGoldilocksConfig>::from_common_data(
+            VerificationKeyValues::from(proof.1.clone()),
+            CommonData::try_from(proof.2.clone()).unwrap(),
+        );
+        let bn254_circuit = Verifier::<Bn254PoseidonGoldilocksConfig>::from_common_data(
+            VerificationKeyValues::from(proof.1.clone()),
+            CommonData::try_from(proof.2.clone()).unwrap(),
+        );
+        let goldilocks_rows = row_budget(&goldilocks_circuit).unwrap().used_rows;
+        let bn254_rows = row_budget(&bn254_circuit).unwrap().used_rows;
+        info!(
+            "rows with PoseidonGoldilocksConfig: {goldilocks_rows}, \
+             with Bn254PoseidonGoldilocksConfig: {bn254_rows}"
+        );
+        assert!(goldilocks_rows > bn254_rows);
+
+        verify_inside_snark_mock(None, proof).unwrap();
+    }
+
+    /// Verifies a `KeccakGoldilocksConfig` proof directly, the only circuits configuring the
+    /// Keccak chip being those of Keccak proofs.
+    #[test]
+    fn test_keccak_goldilocks_halo2_mock() {
+        let mut builder = CircuitBuilder::<F, D>::new(standard_stark_verifier_config());
+        let input = builder.add_virtual_target();
+        let mut output = input;
+        for _ in 0..1 << 8 {
+            output = builder.square(output);
+        }
+        builder.register_public_input(output);
+        let data = builder.build::<KeccakGoldilocksConfig>();
+        let mut pw = PartialWitness::new();
+        pw.set_target(input, F::from_canonical_u64(3));
+        let proof = data.prove(pw).unwrap();
+        let proof = (proof, data.verifier_only, data.common);
+
+        let keccak_circuit = Verifier::<KeccakGoldilocksConfig>::from_common_data(
+            VerificationKeyValues::from(proof.1.clone()),
+            CommonData::try_from(proof.2.clone()).unwrap(),
+        );
+        assert!(keccak_circuit.required_chips().keccak);
+        let poseidon_circuit = Verifier::<PoseidonGoldilocksConfig>::from_common_data(
+            VerificationKeyValues::from(proof.1.clone()),
+            CommonData::try_from(proof.2.clone()).unwrap(),
+        );
+        assert!(!poseidon_circuit.required_chips().keccak);
+
+        verify_inside_snark_mock(None, proof).unwrap();
+    }
+
+    /// Repeated squarings, to be built with either BN254 Poseidon config.
+    fn squarings_builder() -> (CircuitBuilder<F, D>, Target) {
+        let mut builder = CircuitBuilder::<F, D>::new(standard_stark_verifier_config());
+        let input = builder.add_virtual_target();
+        let mut output = input;
+        for _ in 0..1 << 12 {
+            output = builder.square(output);
+        }
+        builder.register_public_input(output);
+        (builder, input)
+    }
+
+    fn generate_poseidon2_proof_tuple() -> ProofTuple<F, Bn254Poseidon2GoldilocksConfig, D> {
+        let (builder, input) = squarings_builder();
+        let data = builder.build::<Bn254Poseidon2GoldilocksConfig>();
+        let mut pw = PartialWitness::new();
+        pw.set_target(input, F::from_canonical_u64(3));
+        let proof = data.prove(pw).unwrap();
+        (proof, data.verifier_only, data.common)
+    }
+
+    /// Verifies a `Bn254Poseidon2GoldilocksConfig` proof and compares the cost with the same
+    /// circuit proved with `Bn254PoseidonGoldilocksConfig`: only the stages hashing are cheaper.
+    #[test]
+    fn test_poseidon2_halo2_mock() {
+        let poseidon_data = squarings_builder()
+            .0
+            .build::<Bn254PoseidonGoldilocksConfig>();
+        let proof = generate_poseidon2_proof_tuple();
+
+        let poseidon2_circuit = Verifier::<Bn254Poseidon2GoldilocksConfig>::from_common_data(
+            VerificationKeyValues::from(proof.1.clone()),
+            CommonData::try_from(proof.2.clone()).unwrap(),
+        );
+        let poseidon_circuit = Verifier::<Bn254PoseidonGoldilocksConfig>::from_common_data(
+            VerificationKeyValues::from(poseidon_data.verifier_only),
+            CommonData::try_from(poseidon_data.common).unwrap(),
+        );
+        let poseidon2_costs = costs_by_name(&cost_profile(&poseidon2_circuit).unwrap());
+        let poseidon_costs = costs_by_name(&cost_profile(&poseidon_circuit).unwrap());
+        for stage in [
+            "get_challenges",
+            "initial_merkle_proofs",
+            "commit_phase_merkle_proofs",
+        ] {
+            let (poseidon2_rows, _) = poseidon2_costs[stage];
+            let (poseidon_rows, _) = poseidon_costs[stage];
+            info!("{stage}: {poseidon_rows} rows with Poseidon, {poseidon2_rows} with Poseidon2");
+            assert!(poseidon2_rows < poseidon_rows, "{stage}");
+        }
+        for stage in ["eval_vanishing_poly", "reduced_openings"] {
+            assert_eq!(poseidon2_costs[stage], poseidon_costs[stage], "{stage}");
+        }
+        let poseidon2_rows = row_budget(&poseidon2_circuit).unwrap().used_rows;
+        let poseidon_rows = row_budget(&poseidon_circuit).unwrap().used_rows;
+        info!(
+            "rows with Bn254PoseidonGoldilocksConfig: {poseidon_rows}, \
+             with Bn254Poseidon2GoldilocksConfig: {poseidon2_rows}"
+        );
+        assert!(poseidon2_rows < poseidon_rows);
+
+        verify_inside_snark_mock(None, proof).unwrap();
+    }
+
+    #[test]
+    fn test_poseidon2_halo2_proof() {
+        let (proof, vd, cd) = generate_poseidon2_proof_tuple();
+        let kzg_param = ParamsKZG::<Bn256>::setup(19, rand::thread_rng());
+        let setup = VerifierSetup::<Bn254Poseidon2GoldilocksConfig>::new(
+            kzg_param,
+            VerificationKeyValues::from(vd),
+            CommonData::try_from(cd).unwrap(),
+        )
+        .unwrap();
+
+        let (snark_proof, mut instances) = prove(&setup, proof).unwrap();
+        verify_snark_proof(&setup.params, setup.halo2_vk(), &snark_proof, &instances).unwrap();
+
+        instances[0] += Fr::ONE;
+        assert!(matches!(
+            verify_snark_proof(&setup.params, setup.halo2_vk(), &snark_proof, &instances),
+            Err(VerifierError::ProofRejected(_))
+        ));
+    }
+
     #[test]
     fn test_recursive_halo2_mock() {
         let proof = generate_proof_tuple();
-        verify_inside_snark_mock(19, proof);
+        verify_inside_snark_mock(None, proof).unwrap();
     }
 
     #[test]
@@ -325,6 +1285,337 @@ mod tests {
         let proof = generate_proof_tuple();
 
         info!("start verify in snark");
-        verify_inside_snark(19, proof, None);
+        // local setup, for tests only
+        let kzg_param = ParamsKZG::<Bn256>::setup(19, rand::thread_rng());
+        verify_inside_snark(None, proof, &kzg_param, None).unwrap();
+    }
+
+    #[test]
+    fn test_prove_with_setup() {
+        let (proof, vd, cd) = generate_proof_tuple();
+        let kzg_param = ParamsKZG::<Bn256>::setup(19, rand::thread_rng());
+        let vk = VerificationKeyValues::from(vd);
+        let common_data = CommonData::try_from(cd).unwrap();
+        let setup = VerifierSetup::<Bn254PoseidonGoldilocksConfig>::new(
+            kzg_param.clone(),
+            vk.clone(),
+            common_data.clone(),
+        )
+        .unwrap();
+
+        let mut bytes = vec![];
+        setup.write(&mut bytes).unwrap();
+        let small_param = ParamsKZG::<Bn256>::setup(18, rand::thread_rng());
+        assert!(matches!(
+            VerifierSetup::<Bn254PoseidonGoldilocksConfig>::read(
+                &mut bytes.as_slice(),
+                small_param,
+                vk.clone(),
+                common_data.clone()
+            ),
+            Err(VerifierError::InvalidParams(_))
+        ));
+        let setup = VerifierSetup::read(&mut bytes.as_slice(), kzg_param, vk, common_data).unwrap();
+        prove(&setup, proof.clone()).unwrap();
+        let (snark_proof, mut instances) = prove(&setup, proof).unwrap();
+        verify_snark_proof(&setup.params, setup.halo2_vk(), &snark_proof, &instances).unwrap();
+
+        instances[0] += Fr::ONE;
+        assert!(matches!(
+            verify_snark_proof(&setup.params, setup.halo2_vk(), &snark_proof, &instances),
+            Err(VerifierError::ProofRejected(_))
+        ));
+    }
+
+    /// One setup with the verification key as witness proves for two circuits of the same
+    /// `CommonData`, and is written and read with its mode.
+    #[test]
+    fn test_prove_with_universal_setup() {
+        let proofs = [
+            generate_scaled_proof_tuple(5),
+            generate_scaled_proof_tuple(7),
+        ];
+        let common_data = CommonData::<Fr>::try_from(proofs[0].2.clone()).unwrap();
+        let vks = proofs
+            .iter()
+            .map(|(_, vd, _)| VerificationKeyValues::<Fr>::from(vd.clone()))
+            .collect::<Vec<_>>();
+        let kzg_param = ParamsKZG::<Bn256>::setup(19, rand::thread_rng());
+        let setup = VerifierSetup::<Bn254PoseidonGoldilocksConfig>::from_circuit(
+            kzg_param.clone(),
+            Verifier::from_common_data(vks[0].clone(), common_data.clone())
+                .with_vk_mode(VerificationKeyMode::Witness),
+        )
+        .unwrap();
+        assert_eq!(setup.num_instances(), common_data.num_public_inputs + 4);
+
+        let mut bytes = vec![];
+        setup.write(&mut bytes).unwrap();
+        for (proof, vk) in proofs.iter().zip(vks.iter()) {
+            let setup = VerifierSetup::<Bn254PoseidonGoldilocksConfig>::read(
+                &mut bytes.as_slice(),
+                kzg_param.clone(),
+                vk.clone(),
+                common_data.clone(),
+            )
+            .unwrap();
+            assert_eq!(setup.vk_mode, VerificationKeyMode::Witness);
+            let (snark_proof, instances) = prove(&setup, proof.0.clone()).unwrap();
+            assert_eq!(instances.len(), setup.num_instances());
+            assert_eq!(instances[common_data.num_public_inputs..], vk.digest());
+            verify_snark_proof(&setup.params, setup.halo2_vk(), &snark_proof, &instances).unwrap();
+        }
+
+        // keys with the verification key as constants are bound to it
+        let setup = VerifierSetup::<Bn254PoseidonGoldilocksConfig>::new(
+            kzg_param.clone(),
+            vks[0].clone(),
+            common_data.clone(),
+        )
+        .unwrap();
+        let mut bytes = vec![];
+        setup.write(&mut bytes).unwrap();
+        assert!(matches!(
+            VerifierSetup::<Bn254PoseidonGoldilocksConfig>::read(
+                &mut bytes.as_slice(),
+                kzg_param,
+                vks[1].clone(),
+                common_data,
+            ),
+            Err(VerifierError::ShapeMismatch(_))
+        ));
+    }
+
+    /// Keys built in `PublicInputsMode::Packed` keep exposing packed public inputs once read back.
+    #[test]
+    fn test_prove_with_packed_setup() {
+        let (proof, vd, cd) = generate_proof_tuple();
+        let kzg_param = ParamsKZG::<Bn256>::setup(19, rand::thread_rng());
+        let vk = VerificationKeyValues::from(vd);
+        let common_data = CommonData::try_from(cd).unwrap();
+        let setup = VerifierSetup::<Bn254PoseidonGoldilocksConfig>::from_circuit(
+            kzg_param.clone(),
+            Verifier::from_common_data(vk.clone(), common_data.clone())
+                .with_public_inputs_mode(PublicInputsMode::Packed),
+        )
+        .unwrap();
+        assert_eq!(
+            setup.num_instances(),
+            common_data.num_public_inputs.div_ceil(3)
+        );
+
+        let mut bytes = vec![];
+        setup.write(&mut bytes).unwrap();
+        let setup = VerifierSetup::<Bn254PoseidonGoldilocksConfig>::read(
+            &mut bytes.as_slice(),
+            kzg_param,
+            vk,
+            common_data,
+        )
+        .unwrap();
+        assert_eq!(setup.public_inputs_mode, PublicInputsMode::Packed);
+        let (snark_proof, instances) = prove(&setup, proof.clone()).unwrap();
+        assert_eq!(instances, pack_public_inputs(&proof.public_inputs));
+        verify_snark_proof(&setup.params, setup.halo2_vk(), &snark_proof, &instances).unwrap();
+    }
+
+    #[test]
+    fn test_prove_for_aggregation() {
+        let (proof, vd, cd) = generate_proof_tuple();
+        let kzg_param = ParamsKZG::<Bn256>::setup(19, rand::thread_rng());
+        let setup = VerifierSetup::<Bn254PoseidonGoldilocksConfig>::new(
+            kzg_param,
+            VerificationKeyValues::from(vd),
+            CommonData::try_from(cd).unwrap(),
+        )
+        .unwrap();
+
+        let (snark_proof, mut instances) = prove_for_aggregation(&setup, proof).unwrap();
+        verify_snark_proof_for_aggregation(
+            &setup.params,
+            setup.halo2_vk(),
+            &snark_proof,
+            &instances,
+        )
+        .unwrap();
+        // the challenges differ from the ones of the Keccak transcript
+        assert!(matches!(
+            verify_snark_proof(&setup.params, setup.halo2_vk(), &snark_proof, &instances),
+            Err(VerifierError::ProofRejected(_))
+        ));
+
+        instances[0] += Fr::ONE;
+        assert!(matches!(
+            verify_snark_proof_for_aggregation(
+                &setup.params,
+                setup.halo2_vk(),
+                &snark_proof,
+                &instances
+            ),
+            Err(VerifierError::ProofRejected(_))
+        ));
+    }
+
+    /// The neutral accumulator comes first, in the limbs snark-verifier reads from the instances
+    /// of an aggregation proof.
+    #[test]
+    fn test_prove_for_aggregation_with_accumulator() {
+        let (proof, vd, cd) = generate_proof_tuple();
+        let kzg_param = ParamsKZG::<Bn256>::setup(19, rand::thread_rng());
+        let accumulator = KzgAccumulator::neutral(&kzg_param);
+        assert_eq!(
+            Bn256::pairing(&accumulator.lhs, &kzg_param.s_g2()),
+            Bn256::pairing(&accumulator.rhs, &kzg_param.g2())
+        );
+        // `lhs` is the generator `(1, 2)`
+        assert_eq!(
+            accumulator.limbs()[..8],
+            [1u64, 0, 0, 0, 2, 0, 0, 0].map(Fr::from)
+        );
+
+        let circuit = Verifier::from_common_data(
+            VerificationKeyValues::from(vd),
+            CommonData::try_from(cd).unwrap(),
+        )
+        .with_accumulator(Some(accumulator));
+        let setup =
+            VerifierSetup::<Bn254PoseidonGoldilocksConfig>::from_circuit(kzg_param, circuit)
+                .unwrap();
+        let mut bytes = vec![];
+        setup.write(&mut bytes).unwrap();
+        let setup = VerifierSetup::<Bn254PoseidonGoldilocksConfig>::read(
+            &mut bytes.as_slice(),
+            setup.params,
+            setup.vk,
+            setup.common_data,
+        )
+        .unwrap();
+        assert_eq!(setup.accumulator, Some(accumulator));
+        assert_eq!(setup.num_instances(), 16 + proof.public_inputs.len());
+
+        let (snark_proof, instances) = prove_for_aggregation(&setup, proof.clone()).unwrap();
+        assert_eq!(instances[..16], accumulator.limbs()[..]);
+        let public_inputs = proof
+            .public_inputs
+            .iter()
+            .map(|e| goldilocks_to_fe(*e))
+            .collect::<Vec<Fr>>();
+        assert_eq!(instances[16..], public_inputs[..]);
+        verify_snark_proof_for_aggregation(
+            &setup.params,
+            setup.halo2_vk(),
+            &snark_proof,
+            &instances,
+        )
+        .unwrap();
+    }
+
+    /// Keys generated from `without_witnesses` fit the circuit with its accumulator.
+    #[test]
+    fn test_keygen_without_witnesses_with_accumulator() {
+        let (circuit, _) =
+            build_verifier_circuit(generate_proof_tuple(), VerificationKeyMode::Constant).unwrap();
+        let kzg_param = ParamsKZG::<Bn256>::setup(19, rand::thread_rng());
+        let circuit = circuit.with_accumulator(Some(KzgAccumulator::neutral(&kzg_param)));
+        let instances = circuit.instances();
+        let keygen_circuit = circuit.without_witnesses();
+        assert_eq!(keygen_circuit.instances().len(), instances.len());
+        assert_eq!(
+            select_degree(&keygen_circuit, None).unwrap(),
+            select_degree(&circuit, None).unwrap()
+        );
+
+        let halo2_vk = keygen_vk(&kzg_param, &keygen_circuit).unwrap();
+        let pk = keygen_pk(&kzg_param, halo2_vk, &keygen_circuit).unwrap();
+        let mut rng = rand::thread_rng();
+        let proof = create_proof_checked(&kzg_param, &pk, circuit, &instances, &mut rng).unwrap();
+        verify_snark_proof(&kzg_param, pk.get_vk(), &proof, &instances).unwrap();
+    }
+
+    #[test]
+    fn test_keygen_from_common_data() {
+        let (proof, vd, cd) = generate_proof_tuple();
+        let kzg_param = ParamsKZG::<Bn256>::setup(19, rand::thread_rng());
+        let vk = VerificationKeyValues::from(vd);
+        let common_data = CommonData::try_from(cd).unwrap();
+        let instances = proof
+            .public_inputs
+            .iter()
+            .map(|e| goldilocks_to_fe(*e))
+            .collect();
+        let circuit = Verifier::<Bn254PoseidonGoldilocksConfig>::new(
+            ProofValues::from(proof.proof),
+            instances,
+            vk.clone(),
+            common_data.clone(),
+        );
+        let dummy_circuit =
+            Verifier::<Bn254PoseidonGoldilocksConfig>::from_common_data(vk, common_data);
+        let halo2_vk = keygen_vk(&kzg_param, &circuit).unwrap();
+        let dummy_halo2_vk = keygen_vk(&kzg_param, &dummy_circuit).unwrap();
+        assert_eq!(halo2_vk.transcript_repr(), dummy_halo2_vk.transcript_repr());
+    }
+
+    #[test]
+    fn test_select_degree() {
+        let (_, vd, cd) = generate_proof_tuple();
+        let circuit = Verifier::<Bn254PoseidonGoldilocksConfig>::from_common_data(
+            VerificationKeyValues::from(vd),
+            CommonData::try_from(cd).unwrap(),
+        );
+        let budget = row_budget(&circuit).unwrap();
+        let total_rows = budget.used_rows + budget.blinding_rows;
+        assert!(total_rows <= 1 << budget.minimum_k);
+        assert!(total_rows > 1 << (budget.minimum_k - 1));
+        assert_eq!(select_degree(&circuit, None).unwrap(), budget.minimum_k);
+        assert!(matches!(
+            select_degree(&circuit, Some(budget.minimum_k - 1)),
+            Err(VerifierError::DegreeTooSmall { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cost_profile() {
+        let (_, vd, cd) = generate_proof_tuple();
+        let num_query_rounds = cd.config.fri_config.num_query_rounds;
+        let circuit = Verifier::<Bn254PoseidonGoldilocksConfig>::from_common_data(
+            VerificationKeyValues::from(vd),
+            CommonData::try_from(cd).unwrap(),
+        );
+        let profile = cost_profile(&circuit).unwrap();
+        let top_level_rows = profile
+            .stages
+            .iter()
+            .filter(|stage| stage.depth == 0)
+            .map(|stage| stage.rows)
+            .sum::<usize>();
+        assert_eq!(top_level_rows, profile.rows);
+        let queries = profile
+            .stage("verify_proof_with_challenges/verify_fri_proof/check_consistency")
+            .unwrap();
+        assert_eq!(queries.calls, num_query_rounds);
+        assert!(queries.lookups > 0);
+        let table = profile.to_table();
+        assert_eq!(table.lines().count(), profile.stages.len() + 2);
+        assert!(table.lines().next().unwrap().starts_with("stage"));
+        assert!(table.contains("    check_consistency"));
+        assert!(table.lines().last().unwrap().starts_with("total"));
+    }
+
+    #[test]
+    fn test_load_snark_instances_as_fr() {
+        let path = std::env::temp_dir().join("plonky2_verifier_test_instances.json");
+        let largest = (GoldilocksField::ORDER - 1).to_string();
+        std_ops::save_snark_instances(&path, &vec!["1".to_string(), largest]).unwrap();
+        assert_eq!(
+            std_ops::load_snark_instances_as_fr(&path).unwrap(),
+            vec![Fr::ONE, Fr::from(GoldilocksField::ORDER - 1)]
+        );
+
+        std_ops::save_snark_instances(&path, &vec![GoldilocksField::ORDER.to_string()]).unwrap();
+        assert!(matches!(
+            std_ops::load_snark_instances_as_fr(&path),
+            Err(VerifierError::Io(_))
+        ));
     }
 }
@@ -1,24 +1,46 @@
 use crate::plonky2_verifier::types::proof::ProofValues;
 use halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner, Value},
-    halo2curves::{bn256::Fr, ff::PrimeField},
+    arithmetic::CurveAffine,
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        ff::{Field, PrimeField},
+    },
     plonk::*,
+    poly::kzg::commitment::ParamsKZG,
+};
+use halo2wrong_maingate::{
+    decompose, AssignedValue, MainGate, MainGateConfig, RangeChip, RangeConfig,
 };
-use halo2wrong_maingate::{AssignedValue, MainGate, MainGateConfig, RangeChip, RangeConfig};
 use itertools::Itertools;
-use plonky2::plonk::{
-    circuit_data::{CommonCircuitData, VerifierOnlyCircuitData},
-    proof::ProofWithPublicInputs,
+use plonky2::{
+    field::{goldilocks_field::GoldilocksField, types::Field as _},
+    plonk::{
+        circuit_data::{CommonCircuitData, VerifierOnlyCircuitData},
+        config::GenericConfig,
+        proof::ProofWithPublicInputs,
+    },
 };
+use sha3::{Digest, Keccak256};
 use std::marker::PhantomData;
 
 use super::{
+    bn245_poseidon::{
+        native::{decode_fe, encode_fe},
+        plonky2_config::Bn254PoseidonGoldilocksConfig,
+    },
     chip::{
         goldilocks_chip::{GoldilocksChip, GoldilocksChipConfig},
-        native_chip::all_chip::AllChipConfig,
+        goldilocks_keccak_chip::GoldilocksKeccakChip,
+        hasher_chip::{AlgebraicHasherChip, HasherChip},
+        native_chip::{
+            all_chip::{AllChipConfig, OptionalChips},
+            utils::{fe_to_goldilocks, fe_to_u64},
+        },
         plonk::plonk_verifier_chip::PlonkVerifierChip,
     },
     context::RegionCtx,
+    profile::CostProfile,
     types::{
         assigned::{
             AssignedProofValues, AssignedProofWithPisValues, AssignedVerificationKeyValues,
@@ -55,15 +77,124 @@ impl<F: PrimeField> MainGateWithRangeConfig<F> {
     }
 }
 
+/// How the plonky2 verification key enters the `Verifier` circuit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerificationKeyMode {
+    /// The key is assigned as constants, so the halo2 keys are bound to one plonky2 circuit.
+    #[default]
+    Constant,
+    /// The key is a witness and `VerificationKeyValues::digest` is exposed after the public
+    /// inputs, so one set of halo2 keys serves every plonky2 circuit of the same `CommonData`
+    /// and the verifier of the halo2 proofs checks the digest against the circuits it accepts.
+    Witness,
+}
+
+/// How the public inputs of the plonky2 proof are exposed by the `Verifier` circuit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PublicInputsMode {
+    /// One instance per public input.
+    #[default]
+    Instances,
+    /// Three public inputs per instance, see `pack_public_inputs`.
+    Packed,
+    /// Two instances, the high and the low 128 bits of `public_inputs_digest`, so that the
+    /// calldata and the cost of the Solidity verifier don't grow with the public inputs.
+    Keccak256,
+}
+
+/// Limbs of a base field coordinate of a `KzgAccumulator`, as in the RNS of halo2wrong.
+pub const NUM_LIMBS: usize = 4;
+pub const NUM_LIMB_BITS: usize = 68;
+
+/// A KZG accumulator of snark-verifier, i.e. a deferred pairing check
+/// `e(lhs, [s]₂) = e(rhs, [1]₂)` that an aggregation circuit folds with the accumulators of the
+/// other proofs it verifies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KzgAccumulator {
+    pub lhs: G1Affine,
+    pub rhs: G1Affine,
+}
+
+impl KzgAccumulator {
+    /// `([1]₁, [s]₁)` of `params`, which holds for any `s` and so defers nothing. It gives the
+    /// proofs of the `Verifier` circuit the instances of an aggregation proof.
+    pub fn neutral(params: &ParamsKZG<Bn256>) -> Self {
+        Self {
+            lhs: params.get_g()[0],
+            rhs: params.get_g()[1],
+        }
+    }
+
+    /// The instances of snark-verifier's `LimbsEncoding<NUM_LIMBS, NUM_LIMB_BITS>`: the limbs of
+    /// `lhs.x`, `lhs.y`, `rhs.x` and `rhs.y`, least significant limb first.
+    pub fn limbs(&self) -> Vec<Fr> {
+        [self.lhs, self.rhs]
+            .iter()
+            .flat_map(|point| {
+                let coordinates = point.coordinates().unwrap();
+                [*coordinates.x(), *coordinates.y()]
+            })
+            .flat_map(|e| decompose::<_, Fr>(e, NUM_LIMBS, NUM_LIMB_BITS))
+            .collect()
+    }
+}
+
+/// The instances exposed in `PublicInputsMode::Packed`: every chunk of three public inputs
+/// `[x0, x1, x2]`, the last one padded with zeros, as `x0 + x1 * p + x2 * p^2` like `encode_fe`.
+pub fn pack_public_inputs(public_inputs: &[GoldilocksField]) -> Vec<Fr> {
+    public_inputs
+        .chunks(3)
+        .map(|chunk| {
+            let mut chunk = chunk.to_vec();
+            chunk.resize(3, GoldilocksField::ZERO);
+            encode_fe(chunk.try_into().unwrap())
+        })
+        .collect()
+}
+
+/// The first `num_public_inputs` public inputs packed in `instances` by `pack_public_inputs`.
+pub fn unpack_public_inputs(instances: &[Fr], num_public_inputs: usize) -> Vec<GoldilocksField> {
+    instances
+        .iter()
+        .flat_map(|instance| decode_fe(*instance))
+        .take(num_public_inputs)
+        .collect()
+}
+
+/// Keccak-256 of the big-endian bytes of `public_inputs`, i.e. Solidity's
+/// `keccak256(abi.encodePacked(public_inputs))` over `uint64` values, as the two instances
+/// exposed in `PublicInputsMode::Keccak256`: the high 128 bits, then the low 128 bits.
+pub fn public_inputs_digest(public_inputs: &[u64]) -> [Fr; 2] {
+    let mut hasher = Keccak256::new();
+    for public_input in public_inputs {
+        hasher.update(public_input.to_be_bytes());
+    }
+    let digest = hasher.finalize();
+    let half = |bytes: &[u8]| Fr::from_u128(u128::from_be_bytes(bytes.try_into().unwrap()));
+    [half(&digest[..16]), half(&digest[16..])]
+}
+
+/// Verifier circuit of plonky2 proofs made with the config `C`. `C::Hasher` decides how the
+/// Merkle trees and the transcript are verified, e.g. `PoseidonGoldilocksConfig` proofs can be
+/// verified directly, without wrapping them into a `Bn254PoseidonGoldilocksConfig` proof first,
+/// at the cost of a bigger circuit.
 #[derive(Clone)]
-pub struct Verifier {
+pub struct Verifier<C = Bn254PoseidonGoldilocksConfig> {
     proof: ProofValues<Fr, 2>,
     instances: Vec<Fr>,
     vk: VerificationKeyValues<Fr>,
     common_data: CommonData<Fr>,
+    witnessed: bool,
+    vk_mode: VerificationKeyMode,
+    public_inputs_mode: PublicInputsMode,
+    accumulator: Option<KzgAccumulator>,
+    _marker: PhantomData<C>,
 }
 
-impl Verifier {
+impl<C: GenericConfig<2, F = GoldilocksField>> Verifier<C>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
     pub fn new(
         proof: ProofValues<Fr, 2>,
         instances: Vec<Fr>,
@@ -75,6 +206,81 @@ impl Verifier {
             instances,
             vk,
             common_data,
+            witnessed: true,
+            vk_mode: VerificationKeyMode::Constant,
+            public_inputs_mode: PublicInputsMode::Instances,
+            accumulator: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Circuit without witnesses, shaped by `common_data` alone. Enough for keygen.
+    pub fn from_common_data(vk: VerificationKeyValues<Fr>, common_data: CommonData<Fr>) -> Self {
+        Self {
+            proof: ProofValues::dummy(&common_data),
+            instances: vec![Fr::ZERO; common_data.num_public_inputs],
+            vk,
+            common_data,
+            witnessed: false,
+            vk_mode: VerificationKeyMode::Constant,
+            public_inputs_mode: PublicInputsMode::Instances,
+            accumulator: None,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn vk(&self) -> &VerificationKeyValues<Fr> {
+        &self.vk
+    }
+
+    pub fn common_data(&self) -> &CommonData<Fr> {
+        &self.common_data
+    }
+
+    pub fn with_vk_mode(self, vk_mode: VerificationKeyMode) -> Self {
+        Self { vk_mode, ..self }
+    }
+
+    pub fn vk_mode(&self) -> VerificationKeyMode {
+        self.vk_mode
+    }
+
+    pub fn with_public_inputs_mode(self, public_inputs_mode: PublicInputsMode) -> Self {
+        Self {
+            public_inputs_mode,
+            ..self
+        }
+    }
+
+    pub fn public_inputs_mode(&self) -> PublicInputsMode {
+        self.public_inputs_mode
+    }
+
+    /// Exposes the limbs of `accumulator` before the other instances, where snark-verifier
+    /// expects the accumulator of an aggregation proof, e.g. `KzgAccumulator::neutral` so that
+    /// the proofs can be aggregated like the proofs of an aggregation circuit. The accumulator is
+    /// assigned as constants, so it is part of the halo2 keys.
+    pub fn with_accumulator(self, accumulator: Option<KzgAccumulator>) -> Self {
+        Self {
+            accumulator,
+            ..self
+        }
+    }
+
+    pub fn accumulator(&self) -> Option<KzgAccumulator> {
+        self.accumulator
+    }
+
+    /// The optional chips of `C::Hasher`, and `KeccakChip` to expose the digest of the public
+    /// inputs in `PublicInputsMode::Keccak256`.
+    pub fn required_chips(&self) -> OptionalChips {
+        let chips = <C::Hasher as HasherChip<Fr>>::REQUIRED_CHIPS;
+        match self.public_inputs_mode {
+            PublicInputsMode::Keccak256 => chips.union(OptionalChips {
+                keccak: true,
+                ..OptionalChips::NONE
+            }),
+            _ => chips,
         }
     }
 
@@ -89,7 +295,10 @@ impl Verifier {
 
         let public_inputs = instances
             .iter()
-            .map(|instance| goldilocks_chip.assign_value(ctx, Value::known(*instance)))
+            .map(|instance| {
+                let value = ctx.witness(*instance);
+                goldilocks_chip.assign_value(ctx, value)
+            })
             .collect::<Result<Vec<AssignedValue<Fr>>, Error>>()?;
 
         let wires_cap = MerkleCapValues::assign(config, ctx, &proof.wires_cap)?;
@@ -110,92 +319,221 @@ impl Verifier {
         })
     }
 
-    pub fn assign_verification_key(
+    /// The instances the circuit exposes: the limbs of the accumulator if any, the public inputs
+    /// or their digest, see `PublicInputsMode`, and the digest of the verification key in
+    /// `VerificationKeyMode::Witness`.
+    pub fn instances(&self) -> Vec<Fr> {
+        let mut instances = self
+            .accumulator
+            .map(|accumulator| accumulator.limbs())
+            .unwrap_or_default();
+        instances.extend(match self.public_inputs_mode {
+            PublicInputsMode::Instances => self.instances.clone(),
+            PublicInputsMode::Packed => {
+                let public_inputs = self
+                    .instances
+                    .iter()
+                    .map(|x| fe_to_goldilocks(*x))
+                    .collect::<Vec<_>>();
+                pack_public_inputs(&public_inputs)
+            }
+            PublicInputsMode::Keccak256 => {
+                let public_inputs = self
+                    .instances
+                    .iter()
+                    .map(|x| fe_to_u64(*x))
+                    .collect::<Vec<_>>();
+                public_inputs_digest(&public_inputs).to_vec()
+            }
+        });
+        if self.vk_mode == VerificationKeyMode::Witness {
+            instances.extend(self.vk.digest());
+        }
+        instances
+    }
+
+    /// Assigns the proof and the verification key in `ctx` and verifies the proof, returning the
+    /// assigned values of `instances` for the caller to expose.
+    pub fn verify_in_region(
         &self,
-        config: &GoldilocksChipConfig<Fr>,
+        goldilocks_chip_config: &GoldilocksChipConfig<Fr>,
         ctx: &mut RegionCtx<'_, Fr>,
-        vk: &VerificationKeyValues<Fr>,
-    ) -> Result<AssignedVerificationKeyValues<Fr>, Error> {
-        Ok(AssignedVerificationKeyValues {
-            constants_sigmas_cap: MerkleCapValues::assign_constant(
-                config,
+    ) -> Result<Vec<AssignedValue<Fr>>, Error> {
+        ctx.set_witnessed(self.witnessed);
+        let assigned_proof_with_pis = ctx.stage("assign_proof", |ctx| {
+            self.assign_proof_with_pis(goldilocks_chip_config, ctx, &self.proof, &self.instances)
+        })?;
+        let assigned_vk = ctx.stage("assign_vk", |ctx| {
+            self.assign_verification_key(goldilocks_chip_config, ctx, &self.vk)
+        })?;
+        let plonk_verifier_chip =
+            PlonkVerifierChip::<Fr, C::Hasher>::construct(goldilocks_chip_config);
+        let vk_digest = match self.vk_mode {
+            VerificationKeyMode::Constant => None,
+            VerificationKeyMode::Witness => Some(ctx.stage("vk_digest", |ctx| {
+                plonk_verifier_chip.get_verification_key_digest(ctx, &assigned_vk)
+            })?),
+        };
+        let public_inputs_hash = ctx.stage("public_inputs_hash", |ctx| {
+            plonk_verifier_chip.get_public_inputs_hash(ctx, &assigned_proof_with_pis.public_inputs)
+        })?;
+        let challenges = ctx.stage("get_challenges", |ctx| {
+            plonk_verifier_chip.get_challenges(
                 ctx,
-                &vk.constants_sigmas_cap,
-            )?,
-            circuit_digest: HashValues::assign_constant(config, ctx, &vk.circuit_digest)?,
-        })
-    }
-}
-
-impl Circuit<Fr> for Verifier {
-    type Config = GoldilocksChipConfig<Fr>;
-    type FloorPlanner = SimpleFloorPlanner;
-
-    fn without_witnesses(&self) -> Self {
-        Self {
-            proof: self.proof.clone(),
-            instances: self.instances.clone(),
-            vk: self.vk.clone(),
-            common_data: self.common_data.clone(),
+                &public_inputs_hash,
+                &assigned_vk.circuit_digest,
+                &self.common_data,
+                &assigned_proof_with_pis.proof,
+                self.common_data.config.num_challenges,
+            )
+        })?;
+        ctx.stage("verify_proof_with_challenges", |ctx| {
+            plonk_verifier_chip.verify_proof_with_challenges(
+                ctx,
+                &assigned_proof_with_pis.proof,
+                &public_inputs_hash,
+                &challenges,
+                &assigned_vk,
+                &self.common_data,
+            )
+        })?;
+        let mut exposed = vec![];
+        if let Some(accumulator) = self.accumulator {
+            let arithmetic_chip = GoldilocksChip::new(goldilocks_chip_config).arithmetic_chip();
+            for limb in accumulator.limbs() {
+                exposed.push(arithmetic_chip.assign_constant(ctx, limb)?);
+            }
         }
+        exposed.extend(match self.public_inputs_mode {
+            PublicInputsMode::Instances => assigned_proof_with_pis.public_inputs,
+            PublicInputsMode::Packed => ctx.stage("pack_public_inputs", |ctx| {
+                self.pack_assigned_public_inputs(
+                    goldilocks_chip_config,
+                    ctx,
+                    &assigned_proof_with_pis.public_inputs,
+                )
+            })?,
+            PublicInputsMode::Keccak256 => ctx
+                .stage("public_inputs_digest", |ctx| {
+                    GoldilocksKeccakChip::new(goldilocks_chip_config)
+                        .keccak256_be(ctx, &assigned_proof_with_pis.public_inputs)
+                })?
+                .to_vec(),
+        });
+        exposed.extend(vk_digest.into_iter().flat_map(|digest| digest.elements));
+        Ok(exposed)
     }
 
-    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
-        let all_chip_config = AllChipConfig::<Fr>::configure(meta);
-        GoldilocksChip::configure(&all_chip_config)
-    }
-
-    fn synthesize(
+    /// Synthesizes the circuit and returns the cost of every stage of the verification region.
+    pub fn synthesize_with_profile(
         &self,
-        config: Self::Config,
+        config: GoldilocksChipConfig<Fr>,
         mut layouter: impl Layouter<Fr>,
-    ) -> Result<(), Error> {
-        let goldilocks_chip_config = config.clone();
-        let goldilocks_chip = GoldilocksChip::new(&goldilocks_chip_config);
+    ) -> Result<CostProfile, Error> {
+        let goldilocks_chip = GoldilocksChip::new(&config);
         goldilocks_chip.load_table(&mut layouter)?;
-        let assigned_proof_with_pis = layouter.assign_region(
+        let mut profile = CostProfile::default();
+        let public_inputs = layouter.assign_region(
             || "Verify proof",
             |region| {
                 let ctx = &mut RegionCtx::new(region, 0);
-                let assigned_proof_with_pis = self.assign_proof_with_pis(
-                    &goldilocks_chip_config,
-                    ctx,
-                    &self.proof,
-                    &self.instances,
-                )?;
-                let assigned_vk =
-                    self.assign_verification_key(&goldilocks_chip_config, ctx, &self.vk)?;
-                let plonk_verifier_chip = PlonkVerifierChip::construct(&goldilocks_chip_config);
-                let public_inputs_hash = plonk_verifier_chip
-                    .get_public_inputs_hash(ctx, &assigned_proof_with_pis.public_inputs)?;
-                let challenges = plonk_verifier_chip.get_challenges(
-                    ctx,
-                    &public_inputs_hash,
-                    &assigned_vk.circuit_digest,
-                    &self.common_data,
-                    &assigned_proof_with_pis.proof,
-                    self.common_data.config.num_challenges,
-                )?;
-                plonk_verifier_chip.verify_proof_with_challenges(
-                    ctx,
-                    &assigned_proof_with_pis.proof,
-                    &public_inputs_hash,
-                    &challenges,
-                    &assigned_vk,
-                    &self.common_data,
-                )?;
-                Ok(assigned_proof_with_pis)
+                let public_inputs = self.verify_in_region(&config, ctx)?;
+                profile = ctx.take_profile();
+                Ok(public_inputs)
             },
         )?;
-        for (row, public_input) in
-            (0..self.instances.len()).zip_eq(assigned_proof_with_pis.public_inputs)
-        {
+        for (row, public_input) in (0..self.instances().len()).zip_eq(public_inputs) {
             goldilocks_chip.arithmetic_chip().expose_public(
                 layouter.namespace(|| ""),
                 public_input,
                 row,
             )?;
         }
+        Ok(profile)
+    }
+
+    /// In-circuit `pack_public_inputs`. The public inputs are range checked when assigned, so
+    /// the packed instances determine them.
+    fn pack_assigned_public_inputs(
+        &self,
+        config: &GoldilocksChipConfig<Fr>,
+        ctx: &mut RegionCtx<'_, Fr>,
+        public_inputs: &[AssignedValue<Fr>],
+    ) -> Result<Vec<AssignedValue<Fr>>, Error> {
+        let goldilocks_chip = GoldilocksChip::new(config);
+        let zero = goldilocks_chip.assign_constant(ctx, GoldilocksField::ZERO)?;
+        public_inputs
+            .chunks(3)
+            .map(|chunk| {
+                let mut chunk = chunk.to_vec();
+                chunk.resize(3, zero.clone());
+                goldilocks_chip
+                    .arithmetic_chip()
+                    .pack(ctx, chunk.try_into().unwrap())
+            })
+            .collect()
+    }
+
+    pub fn assign_verification_key(
+        &self,
+        config: &GoldilocksChipConfig<Fr>,
+        ctx: &mut RegionCtx<'_, Fr>,
+        vk: &VerificationKeyValues<Fr>,
+    ) -> Result<AssignedVerificationKeyValues<Fr>, Error> {
+        match self.vk_mode {
+            VerificationKeyMode::Constant => Ok(AssignedVerificationKeyValues {
+                constants_sigmas_cap: MerkleCapValues::assign_constant(
+                    config,
+                    ctx,
+                    &vk.constants_sigmas_cap,
+                )?,
+                circuit_digest: HashValues::assign_constant(config, ctx, &vk.circuit_digest)?,
+            }),
+            VerificationKeyMode::Witness => Ok(AssignedVerificationKeyValues {
+                constants_sigmas_cap: MerkleCapValues::assign(
+                    config,
+                    ctx,
+                    &vk.constants_sigmas_cap,
+                )?,
+                circuit_digest: HashValues::assign(config, ctx, &vk.circuit_digest)?,
+            }),
+        }
+    }
+}
+
+impl<C: GenericConfig<2, F = GoldilocksField>> Circuit<Fr> for Verifier<C>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    type Config = GoldilocksChipConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = OptionalChips;
+
+    fn without_witnesses(&self) -> Self {
+        Self::from_common_data(self.vk.clone(), self.common_data.clone())
+            .with_vk_mode(self.vk_mode)
+            .with_public_inputs_mode(self.public_inputs_mode)
+            .with_accumulator(self.accumulator)
+    }
+
+    fn params(&self) -> Self::Params {
+        self.required_chips()
+    }
+
+    fn configure_with_params(
+        meta: &mut ConstraintSystem<Fr>,
+        params: Self::Params,
+    ) -> Self::Config {
+        let all_chip_config = AllChipConfig::<Fr>::configure_with(meta, params);
+        GoldilocksChip::configure(&all_chip_config)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Self::configure_with_params(meta, <C::Hasher as HasherChip<Fr>>::REQUIRED_CHIPS)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        self.synthesize_with_profile(config, layouter)?;
         Ok(())
     }
 }
@@ -0,0 +1,450 @@
+use std::io::{Read, Write};
+
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::halo2curves::group::GroupEncoding;
+use halo2_proofs::plonk::{keygen_pk, keygen_vk, Circuit, ProvingKey, VerifyingKey};
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_proofs::SerdeFormat;
+use halo2_solidity_verifier::BatchOpenScheme::Bdfg21;
+use halo2_solidity_verifier::SolidityGenerator;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::PrimeField64;
+use plonky2::plonk::config::GenericConfig;
+use std::marker::PhantomData;
+
+use super::batch_verifier::BatchVerifier;
+use super::bn245_poseidon::plonky2_config::Bn254PoseidonGoldilocksConfig;
+use super::chip::hasher_chip::AlgebraicHasherChip;
+use super::error::VerifierError;
+use super::kzg_params::downsize;
+use super::types::{common_data::CommonData, verification_key::VerificationKeyValues};
+use super::verifier_circuit::{KzgAccumulator, PublicInputsMode, VerificationKeyMode, Verifier};
+
+/// Halo2 keys of the `Verifier` circuit for one inner plonky2 circuit.
+///
+/// By default the plonky2 verification key is assigned as constants, so the halo2 keys only
+/// depend on `VerificationKeyValues` and `CommonData` and can be reused for every proof of that
+/// circuit. In `VerificationKeyMode::Witness` they only depend on `CommonData`.
+/// `C` is the plonky2 config the proofs are made with, see `Verifier`.
+pub struct VerifierSetup<C = Bn254PoseidonGoldilocksConfig> {
+    pub params: ParamsKZG<Bn256>,
+    pub pk: ProvingKey<G1Affine>,
+    pub vk: VerificationKeyValues<Fr>,
+    pub common_data: CommonData<Fr>,
+    pub vk_mode: VerificationKeyMode,
+    pub public_inputs_mode: PublicInputsMode,
+    pub accumulator: Option<KzgAccumulator>,
+    _marker: PhantomData<C>,
+}
+
+impl<C: GenericConfig<2, F = GoldilocksField>> VerifierSetup<C>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    /// Runs `keygen_vk` and `keygen_pk` once, from the shape of the inner circuit alone.
+    pub fn new(
+        params: ParamsKZG<Bn256>,
+        vk: VerificationKeyValues<Fr>,
+        common_data: CommonData<Fr>,
+    ) -> Result<Self, VerifierError> {
+        Self::from_circuit(params, Verifier::from_common_data(vk, common_data))
+    }
+
+    /// Runs `keygen_vk` and `keygen_pk` once for `circuit`, a circuit without witnesses in the
+    /// modes the proofs will be verified in, e.g.
+    /// `Verifier::from_common_data(vk, common_data).with_vk_mode(VerificationKeyMode::Witness)`.
+    pub fn from_circuit(
+        params: ParamsKZG<Bn256>,
+        circuit: Verifier<C>,
+    ) -> Result<Self, VerifierError> {
+        let halo2_vk = keygen_vk(&params, &circuit).map_err(VerifierError::Keygen)?;
+        let pk = keygen_pk(&params, halo2_vk, &circuit).map_err(VerifierError::Keygen)?;
+        Ok(Self::with_circuit(params, pk, circuit))
+    }
+
+    fn with_circuit(
+        params: ParamsKZG<Bn256>,
+        pk: ProvingKey<G1Affine>,
+        circuit: Verifier<C>,
+    ) -> Self {
+        Self {
+            params,
+            pk,
+            vk: circuit.vk().clone(),
+            common_data: circuit.common_data().clone(),
+            vk_mode: circuit.vk_mode(),
+            public_inputs_mode: circuit.public_inputs_mode(),
+            accumulator: circuit.accumulator(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn halo2_vk(&self) -> &VerifyingKey<G1Affine> {
+        self.pk.get_vk()
+    }
+
+    /// The circuit without witnesses the keys were generated for.
+    pub fn circuit(&self) -> Verifier<C> {
+        Verifier::from_common_data(self.vk.clone(), self.common_data.clone())
+            .with_vk_mode(self.vk_mode)
+            .with_public_inputs_mode(self.public_inputs_mode)
+            .with_accumulator(self.accumulator)
+    }
+
+    /// The instances of the proofs, see `Verifier::instances`.
+    pub fn num_instances(&self) -> usize {
+        self.circuit().instances().len()
+    }
+
+    /// Renders the Solidity verifier and vk contracts for these keys.
+    pub fn render_solidity(&self) -> Result<(String, String), VerifierError> {
+        render_solidity(&self.params, self.halo2_vk(), self.num_instances())
+    }
+
+    /// Writes the modes, the accumulator and the plonky2 circuit and `CommonData` digests followed
+    /// by the halo2 proving key.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), VerifierError> {
+        write_header(writer, &self.circuit())?;
+        self.pk.write(writer, SerdeFormat::RawBytes)?;
+        Ok(())
+    }
+
+    /// Reads a proving key written by `write`, in the stored modes and with the stored
+    /// accumulator. `vk` and `common_data` are those of the inner plonky2 circuit, the stored
+    /// circuit digest must match `vk` unless the key is a witness and the stored `CommonData`
+    /// digest must match `common_data`. `params` are downsized to the `k` of the keys.
+    pub fn read<R: Read>(
+        reader: &mut R,
+        params: ParamsKZG<Bn256>,
+        vk: VerificationKeyValues<Fr>,
+        common_data: CommonData<Fr>,
+    ) -> Result<Self, VerifierError> {
+        let (pk, circuit) = read_proving_key(reader, &vk, &common_data)?;
+        Self::from_proving_key(params, pk, circuit)
+    }
+
+    /// Keys read by `read_proving_key`, e.g. to load `params` for the `k` of `pk` only after
+    /// reading it. `params` are downsized to that `k`.
+    pub fn from_proving_key(
+        params: ParamsKZG<Bn256>,
+        pk: ProvingKey<G1Affine>,
+        circuit: Verifier<C>,
+    ) -> Result<Self, VerifierError> {
+        let params = params_for_pk(params, &pk)?;
+        Ok(Self::with_circuit(params, pk, circuit))
+    }
+
+    /// Writes the modes, the accumulator and the plonky2 circuit and `CommonData` digests followed
+    /// by the halo2 verifying key.
+    pub fn write_vk<W: Write>(&self, writer: &mut W) -> Result<(), VerifierError> {
+        write_header(writer, &self.circuit())?;
+        self.halo2_vk().write(writer, SerdeFormat::RawBytes)?;
+        Ok(())
+    }
+}
+
+/// Halo2 keys of the `BatchVerifier` circuit for a fixed list of inner plonky2 circuits, one
+/// proof per slot.
+///
+/// Every slot has its own verification key and `CommonData`, so the circuits of the slots may
+/// use different gates, degrees and FRI parameters.
+pub struct BatchVerifierSetup<C = Bn254PoseidonGoldilocksConfig> {
+    pub params: ParamsKZG<Bn256>,
+    pub pk: ProvingKey<G1Affine>,
+    pub slots: Vec<(VerificationKeyValues<Fr>, CommonData<Fr>)>,
+    pub public_inputs_mode: PublicInputsMode,
+    circuit: BatchVerifier<C>,
+}
+
+impl<C: GenericConfig<2, F = GoldilocksField>> BatchVerifierSetup<C>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    /// Runs `keygen_vk` and `keygen_pk` once, from the shapes of the inner circuits alone. The
+    /// public inputs of every proof are exposed as `public_inputs_mode` says.
+    pub fn new(
+        params: ParamsKZG<Bn256>,
+        slots: Vec<(VerificationKeyValues<Fr>, CommonData<Fr>)>,
+        public_inputs_mode: PublicInputsMode,
+    ) -> Result<Self, VerifierError> {
+        let circuit = BatchVerifier::<C>::from_common_data(slots.clone())?
+            .with_public_inputs_mode(public_inputs_mode);
+        let halo2_vk = keygen_vk(&params, &circuit).map_err(VerifierError::Keygen)?;
+        let pk = keygen_pk(&params, halo2_vk, &circuit).map_err(VerifierError::Keygen)?;
+        Ok(Self {
+            params,
+            pk,
+            slots,
+            public_inputs_mode,
+            circuit,
+        })
+    }
+
+    pub fn halo2_vk(&self) -> &VerifyingKey<G1Affine> {
+        self.pk.get_vk()
+    }
+
+    /// The circuit without witnesses the keys were generated for.
+    pub fn circuit(&self) -> BatchVerifier<C> {
+        self.circuit.clone()
+    }
+
+    /// The instances of all the slots, one slot after the other.
+    pub fn num_instances(&self) -> usize {
+        self.circuit().instances().len()
+    }
+
+    /// Renders the Solidity verifier and vk contracts for these keys.
+    pub fn render_solidity(&self) -> Result<(String, String), VerifierError> {
+        render_solidity(&self.params, self.halo2_vk(), self.num_instances())
+    }
+
+    /// Writes the public inputs mode and the digests of the plonky2 circuit and the `CommonData`
+    /// of every slot followed by the halo2 proving key.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), VerifierError> {
+        write_public_inputs_mode(writer, self.public_inputs_mode)?;
+        for (vk, common_data) in self.slots.iter() {
+            write_circuit_digest(writer, vk)?;
+            writer.write_all(&common_data.digest())?;
+        }
+        self.pk.write(writer, SerdeFormat::RawBytes)?;
+        Ok(())
+    }
+
+    /// Reads a proving key written by `write`, in the stored public inputs mode. The stored
+    /// digests must match the verification keys and the `CommonData` of `slots`, in order.
+    /// `params` are downsized to the `k` of the keys.
+    pub fn read<R: Read>(
+        reader: &mut R,
+        params: ParamsKZG<Bn256>,
+        slots: Vec<(VerificationKeyValues<Fr>, CommonData<Fr>)>,
+    ) -> Result<Self, VerifierError> {
+        let public_inputs_mode = read_public_inputs_mode(reader)?;
+        for (vk, common_data) in slots.iter() {
+            check_circuit_digest(reader, vk)?;
+            check_common_data_digest(reader, common_data)?;
+        }
+        let circuit = BatchVerifier::<C>::from_common_data(slots.clone())?
+            .with_public_inputs_mode(public_inputs_mode);
+        let pk = ProvingKey::<G1Affine>::read::<_, BatchVerifier<C>>(
+            reader,
+            SerdeFormat::RawBytes,
+            circuit.params(),
+        )?;
+        let params = params_for_pk(params, &pk)?;
+        Ok(Self {
+            params,
+            pk,
+            slots,
+            public_inputs_mode,
+            circuit,
+        })
+    }
+}
+
+/// Renders the Solidity verifier and vk contracts for a halo2 verifying key.
+pub fn render_solidity(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instances: usize,
+) -> Result<(String, String), VerifierError> {
+    SolidityGenerator::new(params, vk, Bdfg21, num_instances)
+        .render_separately()
+        .map_err(|e| VerifierError::Solidity(e.to_string()))
+}
+
+/// Reads a halo2 verifying key written by `VerifierSetup::write_vk`, with the circuit without
+/// witnesses it was generated for, e.g. to count its instances.
+pub fn read_halo2_vk<C: GenericConfig<2, F = GoldilocksField>, R: Read>(
+    reader: &mut R,
+    vk: &VerificationKeyValues<Fr>,
+    common_data: &CommonData<Fr>,
+) -> Result<(VerifyingKey<G1Affine>, Verifier<C>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let circuit = read_header::<C, _>(reader, vk.clone(), common_data.clone())?;
+    let halo2_vk = VerifyingKey::<G1Affine>::read::<_, Verifier<C>>(
+        reader,
+        SerdeFormat::RawBytes,
+        circuit.params(),
+    )?;
+    Ok((halo2_vk, circuit))
+}
+
+/// Reads a halo2 proving key written by `VerifierSetup::write`, with the circuit without
+/// witnesses it was generated for.
+pub fn read_proving_key<C: GenericConfig<2, F = GoldilocksField>, R: Read>(
+    reader: &mut R,
+    vk: &VerificationKeyValues<Fr>,
+    common_data: &CommonData<Fr>,
+) -> Result<(ProvingKey<G1Affine>, Verifier<C>), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let circuit = read_header::<C, _>(reader, vk.clone(), common_data.clone())?;
+    let pk = ProvingKey::<G1Affine>::read::<_, Verifier<C>>(
+        reader,
+        SerdeFormat::RawBytes,
+        circuit.params(),
+    )?;
+    Ok((pk, circuit))
+}
+
+/// `params` downsized to the `k` of `pk`, failing if they are too small to prove with it.
+fn params_for_pk(
+    mut params: ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+) -> Result<ParamsKZG<Bn256>, VerifierError> {
+    downsize(&mut params, pk.get_vk().get_domain().k())?;
+    Ok(params)
+}
+
+/// Writes the modes and the accumulator of `circuit` and the digests of its plonky2 circuit and
+/// `CommonData`.
+fn write_header<C: GenericConfig<2, F = GoldilocksField>, W: Write>(
+    writer: &mut W,
+    circuit: &Verifier<C>,
+) -> Result<(), VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let vk_mode = match circuit.vk_mode() {
+        VerificationKeyMode::Constant => 0u8,
+        VerificationKeyMode::Witness => 1,
+    };
+    writer.write_all(&[vk_mode])?;
+    write_public_inputs_mode(writer, circuit.public_inputs_mode())?;
+    match circuit.accumulator() {
+        None => writer.write_all(&[0u8])?,
+        Some(accumulator) => {
+            writer.write_all(&[1u8])?;
+            writer.write_all(accumulator.lhs.to_bytes().as_ref())?;
+            writer.write_all(accumulator.rhs.to_bytes().as_ref())?;
+        }
+    }
+    write_circuit_digest(writer, circuit.vk())?;
+    writer.write_all(&circuit.common_data().digest())?;
+    Ok(())
+}
+
+/// Reads the header written by `write_header` and returns the circuit without witnesses of `vk`
+/// and `common_data` in the stored modes, with the stored accumulator.
+fn read_header<C: GenericConfig<2, F = GoldilocksField>, R: Read>(
+    reader: &mut R,
+    vk: VerificationKeyValues<Fr>,
+    common_data: CommonData<Fr>,
+) -> Result<Verifier<C>, VerifierError>
+where
+    C::Hasher: AlgebraicHasherChip<Fr>,
+{
+    let mut vk_mode = [0u8];
+    reader.read_exact(&mut vk_mode)?;
+    let vk_mode = match vk_mode[0] {
+        0 => VerificationKeyMode::Constant,
+        1 => VerificationKeyMode::Witness,
+        mode => {
+            return Err(VerifierError::ShapeMismatch(format!(
+                "unknown verification key mode {mode}"
+            )))
+        }
+    };
+    let public_inputs_mode = read_public_inputs_mode(reader)?;
+    let mut has_accumulator = [0u8];
+    reader.read_exact(&mut has_accumulator)?;
+    let accumulator = match has_accumulator[0] {
+        0 => None,
+        1 => Some(KzgAccumulator {
+            lhs: read_point(reader)?,
+            rhs: read_point(reader)?,
+        }),
+        flag => {
+            return Err(VerifierError::ShapeMismatch(format!(
+                "unknown accumulator flag {flag}"
+            )))
+        }
+    };
+    match vk_mode {
+        VerificationKeyMode::Constant => check_circuit_digest(reader, &vk)?,
+        // the keys serve every plonky2 circuit of `common_data`
+        VerificationKeyMode::Witness => reader.read_exact(&mut [0u8; 32])?,
+    }
+    check_common_data_digest(reader, &common_data)?;
+    Ok(Verifier::from_common_data(vk, common_data)
+        .with_vk_mode(vk_mode)
+        .with_public_inputs_mode(public_inputs_mode)
+        .with_accumulator(accumulator))
+}
+
+fn read_point<R: Read>(reader: &mut R) -> Result<G1Affine, VerifierError> {
+    let mut repr = <G1Affine as GroupEncoding>::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+    Option::from(G1Affine::from_bytes(&repr))
+        .ok_or_else(|| VerifierError::ShapeMismatch("invalid accumulator point".to_string()))
+}
+
+fn write_public_inputs_mode<W: Write>(
+    writer: &mut W,
+    public_inputs_mode: PublicInputsMode,
+) -> Result<(), VerifierError> {
+    let public_inputs_mode = match public_inputs_mode {
+        PublicInputsMode::Instances => 0u8,
+        PublicInputsMode::Packed => 1,
+        PublicInputsMode::Keccak256 => 2,
+    };
+    writer.write_all(&[public_inputs_mode])?;
+    Ok(())
+}
+
+fn read_public_inputs_mode<R: Read>(reader: &mut R) -> Result<PublicInputsMode, VerifierError> {
+    let mut public_inputs_mode = [0u8];
+    reader.read_exact(&mut public_inputs_mode)?;
+    match public_inputs_mode[0] {
+        0 => Ok(PublicInputsMode::Instances),
+        1 => Ok(PublicInputsMode::Packed),
+        2 => Ok(PublicInputsMode::Keccak256),
+        mode => Err(VerifierError::ShapeMismatch(format!(
+            "unknown public inputs mode {mode}"
+        ))),
+    }
+}
+
+fn write_circuit_digest<W: Write>(
+    writer: &mut W,
+    vk: &VerificationKeyValues<Fr>,
+) -> Result<(), VerifierError> {
+    for e in vk.circuit_digest.elements.iter() {
+        writer.write_all(&e.to_canonical_u64().to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn check_common_data_digest<R: Read>(
+    reader: &mut R,
+    common_data: &CommonData<Fr>,
+) -> Result<(), VerifierError> {
+    let mut digest = [0u8; 32];
+    reader.read_exact(&mut digest)?;
+    if digest != common_data.digest() {
+        return Err(VerifierError::ShapeMismatch(
+            "keys were generated for another CommonData".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_circuit_digest<R: Read>(
+    reader: &mut R,
+    vk: &VerificationKeyValues<Fr>,
+) -> Result<(), VerifierError> {
+    for e in vk.circuit_digest.elements.iter() {
+        let mut buf = [0u8; 8];
+        reader.read_exact(&mut buf)?;
+        if u64::from_le_bytes(buf) != e.to_canonical_u64() {
+            return Err(VerifierError::ShapeMismatch(
+                "keys were generated for another plonky2 circuit".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}